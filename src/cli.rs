@@ -1,3 +1,4 @@
+use clap::builder::PossibleValue;
 use clap::{Parser, Subcommand, ValueEnum, ValueHint};
 use clap_complete::Shell;
 use std::path::PathBuf;
@@ -10,19 +11,145 @@ pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
 
-    /// Path to the kubeconfig file
-    #[arg(long, short = 'k', global = true, env = "KUBECONFIG", value_hint = ValueHint::FilePath)]
+    /// Path to the kubeconfig file (defaults to $KHELP_KUBECONFIG, $KUBECONFIG, or ~/.kube/config)
+    #[arg(long, short = 'k', global = true, value_hint = ValueHint::FilePath)]
     pub kubeconfig: Option<PathBuf>,
+
+    /// Back up the kubeconfig into this directory before saving, keeping the newest 5
+    #[arg(long, global = true, value_name = "DIR", value_hint = ValueHint::DirPath)]
+    pub backup_dir: Option<PathBuf>,
+
+    /// Save even if a cluster server URL is not a valid http(s) URL
+    #[arg(long, global = true)]
+    pub allow_invalid: bool,
+
+    /// When to color output (overrides the `color` setting and NO_COLOR)
+    #[arg(long, global = true, value_enum, value_name = "WHEN")]
+    pub color: Option<ColorChoice>,
+
+    /// Suppress status messages and fail instead of prompting
+    #[arg(long, short = 'q', global = true)]
+    pub quiet: bool,
+
+    /// Increase logging verbosity (-v for info, -vv for debug)
+    #[arg(long, short = 'v', global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Format of log lines written to stderr
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value_t,
+        value_name = "FORMAT"
+    )]
+    pub log_format: LogFormat,
+}
+
+/// How log lines are written
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// One JSON object per line with timestamp, level, target, and message
+    Json,
+}
+
+/// When to write ANSI colors
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ColorChoice {
+    /// Color when writing to a terminal, unless NO_COLOR is set
+    Auto,
+    /// Always color, even when piped
+    Always,
+    /// Never color
+    Never,
 }
 
 #[derive(Debug, Clone, ValueEnum)]
 pub enum OutputFormat {
-    /// Human-readable table output (default)
+    /// Human-readable table output
     Table,
+    /// Tab-separated fields without markers or color
+    Plain,
     /// Bare names, one per line
     Name,
     /// JSON output
     Json,
+    /// YAML output
+    Yaml,
+}
+
+/// The format `export` writes the kubeconfig in
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum ExportFormat {
+    /// YAML, as kubectl writes it
+    #[default]
+    Yaml,
+    /// JSON, for tools that only accept JSON kubeconfigs
+    Json,
+}
+
+/// The shell a `prompt` segment is embedded in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum PromptShell {
+    /// Color only when writing to a terminal, with unwrapped escapes
+    #[default]
+    Plain,
+    /// Always color, wrapping escapes in the \001 \002 markers \[ \] stand for in PS1
+    Bash,
+    /// Always color, wrapping escapes in %{ %} for PROMPT
+    Zsh,
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum SortKey {
+    /// Sort by context name
+    Name,
+    /// Sort by cluster name, then context name
+    Cluster,
+    /// Sort by namespace, then context name
+    Namespace,
+    /// Most recently switched to first, never-used contexts last
+    Recent,
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum GroupKey {
+    /// Group contexts under the cluster they target
+    Cluster,
+}
+
+/// The shell to generate completions for
+#[derive(Debug, Clone, Copy)]
+pub enum ShellChoice {
+    /// Detect the shell khelp is running in
+    Auto,
+    /// A specific shell
+    Shell(Shell),
+}
+
+impl ValueEnum for ShellChoice {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            ShellChoice::Auto,
+            ShellChoice::Shell(Shell::Bash),
+            ShellChoice::Shell(Shell::Zsh),
+            ShellChoice::Shell(Shell::Fish),
+            ShellChoice::Shell(Shell::PowerShell),
+            ShellChoice::Shell(Shell::Elvish),
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        match self {
+            ShellChoice::Auto => {
+                Some(PossibleValue::new("auto").help("Detect the shell khelp is running in"))
+            }
+            ShellChoice::Shell(shell) => shell.to_possible_value(),
+        }
+    }
 }
 
 #[derive(Subcommand)]
@@ -30,29 +157,123 @@ pub enum Commands {
     /// List all available contexts
     #[command(visible_alias = "ls")]
     List {
-        /// Output format
-        #[arg(long, short = 'o', value_enum, default_value_t = OutputFormat::Table)]
-        output: OutputFormat,
+        /// Output format (defaults to a compact listing; `table` aligns columns)
+        #[arg(long, short = 'o', value_enum)]
+        output: Option<OutputFormat>,
+
+        /// Sort contexts (defaults to file order)
+        #[arg(long, value_enum)]
+        sort: Option<SortKey>,
+
+        /// Group contexts under headers in the default listing
+        #[arg(long, value_enum, conflicts_with_all = ["output", "current_only"])]
+        group_by: Option<GroupKey>,
+
+        /// Only print the current context
+        #[arg(long)]
+        current_only: bool,
+
+        /// Also show whether each context's client certificate is valid (✓), expiring
+        /// within 14 days (!), expired (✗), or unreadable (?); `-` for other auth
+        #[arg(long, short = 'w')]
+        wide: bool,
+
+        /// Print `<current>\t<name>\t<cluster>\t<user>\t<namespace>` lines without color,
+        /// `<current>` being `*` or empty; this format won't change between versions
+        #[arg(long, conflicts_with_all = ["output", "group_by", "wide"])]
+        porcelain: bool,
     },
 
     /// Get the current context
     Current {
+        /// Context to inspect instead of the current one (it stays current)
+        #[arg(value_hint = ValueHint::Other)]
+        context_name: Option<String>,
+
         /// Output format
         #[arg(long, short = 'o', value_enum, default_value_t = OutputFormat::Table)]
         output: OutputFormat,
+
+        /// Also show the server, auth type, and exec plugin command line
+        #[arg(long, short = 'w')]
+        wide: bool,
+
+        /// Also probe the API server and show whether it answered, and how fast
+        #[arg(long)]
+        check: bool,
+
+        /// Print using a template with {context}, {namespace}, {cluster}, {server}, {user}, {auth}
+        #[arg(long, short = 't', conflicts_with_all = ["output", "wide", "check"], value_hint = ValueHint::Other)]
+        template: Option<String>,
+
+        /// Copy the context name to the clipboard instead of printing the details
+        #[cfg(feature = "clipboard")]
+        #[arg(long, short = 'c', conflicts_with_all = ["output", "wide", "check", "template"])]
+        copy: bool,
     },
 
     /// Switch to a different context
     #[command(visible_aliases = ["use", "s"])]
     Switch {
+        /// Context to switch to, or `-` for the previously active one
         #[arg(value_hint = ValueHint::Other)]
         context_name: Option<String>,
+
+        /// Also set the default namespace of the context being switched to
+        #[arg(long, short = 'n', value_hint = ValueHint::Other)]
+        namespace: Option<String>,
+
+        /// Refuse to switch to clusters with TLS verification disabled
+        #[arg(long)]
+        strict: bool,
+
+        /// Switch even if --strict would refuse, or set a --namespace that isn't a valid name
+        #[arg(long, short = 'f')]
+        force: bool,
+
+        /// Leave the kubeconfig alone and print an `export KUBECONFIG=...` line that
+        /// points the current shell at a copy of just this context
+        #[arg(long)]
+        print_export: bool,
     },
 
     /// Edit a specific context
     Edit {
         #[arg(value_hint = ValueHint::Other)]
         context_name: Option<String>,
+
+        /// Edit the current context
+        #[arg(long, conflicts_with = "context_name")]
+        current: bool,
+
+        /// Edit the whole config, validating it before it is saved
+        #[arg(long, conflicts_with_all = ["context_name", "current"])]
+        all: bool,
+
+        /// Save namespaces that aren't valid Kubernetes names
+        #[arg(long, short = 'f')]
+        force: bool,
+
+        /// Set the server URL of the context's cluster instead of opening an editor
+        #[arg(long, value_name = "URL", conflicts_with = "all", value_hint = ValueHint::Url)]
+        set_server: Option<String>,
+
+        /// Set the context's default namespace instead of opening an editor
+        #[arg(long, value_name = "NAMESPACE", conflicts_with = "all", value_hint = ValueHint::Other)]
+        set_namespace: Option<String>,
+
+        /// Point the context at another existing cluster instead of opening an editor
+        #[arg(long, value_name = "CLUSTER", conflicts_with = "all", value_hint = ValueHint::Other)]
+        set_cluster: Option<String>,
+
+        /// Point the context at another existing user instead of opening an editor
+        #[arg(long, value_name = "USER", conflicts_with = "all", value_hint = ValueHint::Other)]
+        set_user: Option<String>,
+
+        /// Embed a PEM CA file in the context's cluster instead of opening an editor,
+        /// replacing any certificate-authority path
+        #[arg(long, value_name = "PATH", conflicts_with = "all", value_hint = ValueHint::FilePath)]
+        set_ca_file: Option<PathBuf>,
     },
 
     /// Export one or more contexts to stdout (can be redirected to a file)
@@ -60,25 +281,84 @@ pub enum Commands {
         /// Names of contexts to export (if none provided, interactive selection)
         #[arg(value_hint = ValueHint::Other, num_args = 0..)]
         context_names: Vec<String>,
+
+        /// Export the current context
+        #[arg(long, conflicts_with = "context_names")]
+        current: bool,
+
+        /// Replace tokens, keys, and passwords with a placeholder
+        #[arg(long)]
+        redact: bool,
+
+        /// Leave out contexts whose cluster or user is missing instead of failing
+        #[arg(long)]
+        skip_broken: bool,
+
+        /// Keep the original current-context if it is among the exported contexts
+        #[arg(long)]
+        keep_current: bool,
+
+        /// Leave out empty preferences, `namespace: default`, and other default values
+        #[arg(long)]
+        minify: bool,
+
+        /// Sort clusters, contexts, and users by name so re-exports are byte-identical
+        #[arg(long)]
+        sorted: bool,
+
+        /// Write each context to its own `<context-name>.yaml` file in this directory
+        #[arg(long, value_name = "DIR", value_hint = ValueHint::DirPath, conflicts_with = "keep_current")]
+        output_dir: Option<PathBuf>,
+
+        /// Add the exported entries to this kubeconfig file (created if missing) instead
+        /// of printing them, skipping names it already has
+        #[arg(long, value_name = "PATH", value_hint = ValueHint::FilePath, conflicts_with = "output_dir")]
+        merge_into: Option<PathBuf>,
+
+        /// Format to print the exported config in
+        #[arg(
+            long,
+            short = 'o',
+            value_enum,
+            default_value_t,
+            conflicts_with_all = ["output_dir", "merge_into"]
+        )]
+        output: ExportFormat,
     },
 
-    /// Delete a specific context (also removes orphaned cluster and user)
+    /// Delete one or more contexts (also removes orphaned clusters and users)
     #[command(visible_alias = "rm")]
     Delete {
-        /// Name of the context to delete
-        #[arg(value_hint = ValueHint::Other)]
-        context_name: Option<String>,
+        /// Names of contexts to delete (if none provided, interactive selection)
+        #[arg(value_hint = ValueHint::Other, num_args = 0..)]
+        context_names: Vec<String>,
+
+        /// Delete the current context
+        #[arg(long, conflicts_with = "context_names")]
+        current: bool,
+
+        /// Delete every context whose cluster or user doesn't exist
+        #[arg(long, visible_alias = "orphans-only", conflicts_with_all = ["context_names", "current"])]
+        broken: bool,
+
+        /// Context to switch to if the current context is deleted
+        #[arg(long, value_name = "CONTEXT", value_hint = ValueHint::Other)]
+        switch_to: Option<String>,
 
         /// Skip confirmation prompt
-        #[arg(long, short = 'f')]
+        #[arg(long, short = 'f', visible_alias = "yes", visible_short_alias = 'y')]
         force: bool,
     },
 
     /// Clean up orphaned clusters and users not referenced by any context
     Cleanup {
         /// Skip confirmation prompt
-        #[arg(long, short = 'f')]
+        #[arg(long, short = 'f', visible_alias = "yes", visible_short_alias = 'y')]
         force: bool,
+
+        /// Also remove contexts that reference a missing cluster or user
+        #[arg(long, short = 'a', visible_alias = "prune-broken")]
+        all: bool,
     },
 
     /// Rename a context
@@ -91,11 +371,23 @@ pub enum Commands {
         /// New name for the context
         #[arg(value_hint = ValueHint::Other)]
         new_name: String,
+
+        /// Refuse a new name that differs from another context only in case
+        #[arg(long)]
+        strict_case: bool,
+
+        /// Also rename the context's cluster and user when their names start with the old name
+        #[arg(long)]
+        cascade: bool,
+
+        /// Rename the cluster and user without asking
+        #[arg(long, short = 'f', requires = "cascade")]
+        force: bool,
     },
 
     /// Add contexts from an external kubeconfig file
     Add {
-        /// Path to the kubeconfig file to import
+        /// Path to the kubeconfig file to import, an http(s) URL to fetch it from, or `-` for stdin
         #[arg(value_hint = ValueHint::FilePath)]
         file_path: PathBuf,
 
@@ -110,21 +402,210 @@ pub enum Commands {
         /// Switch to the first newly added context after import
         #[arg(long, short = 's')]
         switch: bool,
+
+        /// Switch to the imported file's own current-context after import, falling
+        /// back to --switch when that context wasn't imported
+        #[arg(long)]
+        adopt_current: bool,
+
+        /// Only import these contexts (and the clusters and users they use)
+        #[arg(long, value_delimiter = ',', value_hint = ValueHint::Other)]
+        only: Vec<String>,
+
+        /// Pick which contexts to import from an interactive menu
+        #[arg(long, conflicts_with = "only")]
+        select: bool,
+
+        /// Merge into this file instead of the main kubeconfig (created if missing)
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        output: Option<PathBuf>,
+
+        /// Write without first showing the changes and asking to confirm them
+        #[arg(long, short = 'y')]
+        yes: bool,
+
+        /// Remove contexts, clusters, and users an earlier add imported from this same
+        /// file or URL that it no longer has, if they haven't been changed since
+        #[arg(long, conflicts_with_all = ["only", "select"])]
+        prune: bool,
+
+        /// Header to send when fetching from an https:// URL, e.g. "Authorization: Bearer ..."
+        #[arg(long = "header", short = 'H', value_hint = ValueHint::Other)]
+        headers: Vec<String>,
+    },
+
+    /// Create a new context from the cluster of one context and the user of another
+    Merge {
+        /// Name of the new context
+        #[arg(value_hint = ValueHint::Other)]
+        name: String,
+
+        /// Context whose cluster the new context uses
+        #[arg(long, value_hint = ValueHint::Other)]
+        cluster_from: String,
+
+        /// Context whose user the new context uses
+        #[arg(long, value_hint = ValueHint::Other)]
+        user_from: String,
+
+        /// Namespace for the new context
+        #[arg(long, short = 'n')]
+        namespace: Option<String>,
+
+        /// Use the namespace even if it isn't a valid Kubernetes name
+        #[arg(long, short = 'f', requires = "namespace")]
+        force: bool,
+    },
+
+    /// Set the default namespace of one or more contexts
+    #[command(visible_alias = "ns")]
+    Namespace {
+        /// Context name or glob pattern (e.g. "team-*"), followed by the namespace
+        #[arg(
+            num_args = 1..=2,
+            required_unless_present_any = ["list", "apply_rules"],
+            value_names = ["CONTEXT", "NAMESPACE"],
+            value_hint = ValueHint::Other
+        )]
+        args: Vec<String>,
+
+        /// Set the namespace of the current context
+        #[arg(long, conflicts_with = "all")]
+        current: bool,
+
+        /// Set the namespace of every context
+        #[arg(long, short = 'a')]
+        all: bool,
+
+        /// Fetch namespaces from the current context's cluster and pick one
+        #[arg(long, short = 'l', conflicts_with_all = ["args", "current", "all"])]
+        list: bool,
+
+        /// Set every context's namespace to what `namespace_rules` in the settings expect
+        #[arg(long, conflicts_with_all = ["args", "current", "all", "list"])]
+        apply_rules: bool,
+
+        /// Set the namespace even if it isn't a valid Kubernetes name
+        #[arg(long, short = 'f', conflicts_with = "list")]
+        force: bool,
+    },
+
+    /// Manage short aliases for context names
+    Alias {
+        #[command(subcommand)]
+        action: AliasAction,
+    },
+
+    /// Print the path of the kubeconfig file khelp operates on
+    ConfigPath,
+
+    /// Check connectivity to a cluster's API server
+    #[command(visible_alias = "ping")]
+    Check {
+        /// Context to check (defaults to the current context)
+        #[arg(long, short = 'c', value_hint = ValueHint::Other)]
+        context: Option<String>,
+
+        /// Skip TLS verification, whatever the cluster's setting
+        #[arg(long)]
+        insecure: bool,
+
+        /// Verify the server with this CA bundle instead of the cluster's CA
+        #[arg(long, value_name = "PATH", value_hint = ValueHint::FilePath, conflicts_with = "insecure")]
+        ca_file: Option<PathBuf>,
+    },
+
+    /// Show when a context's client certificate and cluster CA expire
+    CertInfo {
+        /// Context to inspect (defaults to the current context)
+        #[arg(long, short = 'c', value_hint = ValueHint::Other)]
+        context: Option<String>,
+    },
+
+    /// Pick a current context when it is unset or names a context that doesn't exist
+    ResetCurrent {
+        /// Context to make current (prompts when omitted)
+        #[arg(value_hint = ValueHint::Other)]
+        context_name: Option<String>,
+    },
+
+    /// Print a short `context:namespace` segment for a shell prompt (nothing without a current context)
+    Prompt {
+        /// Template with {context}, {namespace}, {cluster}, {server}, {user}, {auth}
+        #[arg(long, short = 'f', default_value = "{context}:{namespace}", value_hint = ValueHint::Other)]
+        format: String,
+
+        /// Shell whose prompt the segment goes into, so colors are kept inside $(...)
+        #[arg(long, value_enum, default_value_t)]
+        shell: PromptShell,
+    },
+
+    /// Print the current context's config as kubectl resolves it, with defaults filled in
+    Resolve {
+        /// Format to print the resolved config in
+        #[arg(long, short = 'o', value_enum, default_value_t)]
+        output: ExportFormat,
     },
 
     /// Generate or install shell completions
+    #[command(arg_required_else_help = true)]
     Completions {
+        /// Shell to generate completions for, or `auto` to detect it
+        /// (detected automatically with --install, --print-path, or --output)
         #[arg(value_enum)]
-        shell: Option<Shell>,
+        shell: Option<ShellChoice>,
+
+        /// Install the completions instead of printing them
         #[arg(long, short = 'i')]
         install: bool,
+
+        /// Print where the completion script and startup file are installed
+        #[arg(long, conflicts_with = "install")]
+        print_path: bool,
+
+        /// Write the completion script to a file instead of printing it
+        #[arg(
+            long,
+            short = 'o',
+            value_name = "PATH",
+            value_hint = ValueHint::FilePath,
+            conflicts_with_all = ["install", "print_path"]
+        )]
+        output: Option<PathBuf>,
     },
 
+    /// Browse contexts full-screen, switching, deleting, or editing the highlighted one
+    #[cfg(feature = "tui")]
+    #[command(visible_alias = "tui")]
+    Browse,
+
     /// Check for updates to khelp
     #[cfg(feature = "self_update")]
     Update {
         /// Apply the update if one is available
         #[arg(long, short = 'a')]
         apply: bool,
+
+        /// Seconds to wait for GitHub to answer the check for a newer release; the
+        /// download itself isn't limited
+        #[arg(long, value_name = "SECS", default_value_t = 10)]
+        timeout: u64,
     },
 }
+
+#[derive(Subcommand)]
+pub enum AliasAction {
+    /// Point an alias at a context
+    Set {
+        /// Short name to use in place of the context name
+        alias: String,
+
+        /// Context the alias resolves to
+        #[arg(value_hint = ValueHint::Other)]
+        context: String,
+    },
+
+    /// List all aliases
+    #[command(visible_alias = "ls")]
+    List,
+}
@@ -1,12 +1,26 @@
 use anyhow::{Context, Result};
 use console::style;
+use dialoguer::{MultiSelect, theme::ColorfulTheme};
+use flate2::read::GzDecoder;
 use log::{debug, warn};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+use crate::commands::output::status;
+use crate::commands::output::{can_prompt, ensure_can_prompt, warn_conflicting_fields};
+use crate::config::analysis::{find_empty_fields, find_orphans};
+use crate::config::imports::{
+    ImportRecord, imports_path, load_imports_from, save_import_record_to,
+};
 use crate::config::kubernetes::{ContextEntry, KubeConfig};
-use crate::config::operations::{load_kube_config_or_default, save_kube_config};
+use crate::config::operations::{
+    configured_kube_config_path, load_kube_config_or_default, load_kube_config_or_default_from,
+    save_kube_config, save_kube_config_to,
+};
+use crate::utils::confirm;
 
 #[derive(Debug)]
 pub struct ImportSummary {
@@ -19,10 +33,13 @@ pub struct ImportSummary {
     pub contexts_overwritten: Vec<String>,
     pub clusters_overwritten: Vec<String>,
     pub users_overwritten: Vec<String>,
+    pub contexts_pruned: Vec<String>,
+    pub clusters_pruned: Vec<String>,
+    pub users_pruned: Vec<String>,
 }
 
 impl ImportSummary {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self {
             contexts_added: Vec::new(),
             clusters_added: Vec::new(),
@@ -33,24 +50,30 @@ impl ImportSummary {
             contexts_overwritten: Vec::new(),
             clusters_overwritten: Vec::new(),
             users_overwritten: Vec::new(),
+            contexts_pruned: Vec::new(),
+            clusters_pruned: Vec::new(),
+            users_pruned: Vec::new(),
         }
     }
 
-    fn has_changes(&self) -> bool {
+    pub fn has_changes(&self) -> bool {
         !self.contexts_added.is_empty()
             || !self.clusters_added.is_empty()
             || !self.users_added.is_empty()
             || !self.contexts_overwritten.is_empty()
             || !self.clusters_overwritten.is_empty()
             || !self.users_overwritten.is_empty()
+            || !self.contexts_pruned.is_empty()
+            || !self.clusters_pruned.is_empty()
+            || !self.users_pruned.is_empty()
     }
 
-    fn print_summary(&self) {
-        eprintln!("\n{}", style("Import Summary:").green().bold());
-        eprintln!("{}", style("───────────────").green());
+    pub fn print_summary(&self) {
+        status!("\n{}", style("Import Summary:").green().bold());
+        status!("{}", style("───────────────").green());
 
         if !self.contexts_added.is_empty() {
-            eprintln!(
+            status!(
                 "{} {} context(s): {}",
                 style("✓").green(),
                 style("Added").green().bold(),
@@ -58,7 +81,7 @@ impl ImportSummary {
             );
         }
         if !self.clusters_added.is_empty() {
-            eprintln!(
+            status!(
                 "{} {} cluster(s): {}",
                 style("✓").green(),
                 style("Added").green().bold(),
@@ -66,7 +89,7 @@ impl ImportSummary {
             );
         }
         if !self.users_added.is_empty() {
-            eprintln!(
+            status!(
                 "{} {} user(s): {}",
                 style("✓").green(),
                 style("Added").green().bold(),
@@ -75,7 +98,7 @@ impl ImportSummary {
         }
 
         if !self.contexts_overwritten.is_empty() {
-            eprintln!(
+            status!(
                 "{} {} context(s): {}",
                 style("↻").yellow(),
                 style("Overwritten").yellow().bold(),
@@ -83,7 +106,7 @@ impl ImportSummary {
             );
         }
         if !self.clusters_overwritten.is_empty() {
-            eprintln!(
+            status!(
                 "{} {} cluster(s): {}",
                 style("↻").yellow(),
                 style("Overwritten").yellow().bold(),
@@ -91,7 +114,7 @@ impl ImportSummary {
             );
         }
         if !self.users_overwritten.is_empty() {
-            eprintln!(
+            status!(
                 "{} {} user(s): {}",
                 style("↻").yellow(),
                 style("Overwritten").yellow().bold(),
@@ -99,8 +122,33 @@ impl ImportSummary {
             );
         }
 
+        if !self.contexts_pruned.is_empty() {
+            status!(
+                "{} {} context(s): {}",
+                style("✗").red(),
+                style("Pruned").red().bold(),
+                self.contexts_pruned.join(", ")
+            );
+        }
+        if !self.clusters_pruned.is_empty() {
+            status!(
+                "{} {} cluster(s): {}",
+                style("✗").red(),
+                style("Pruned").red().bold(),
+                self.clusters_pruned.join(", ")
+            );
+        }
+        if !self.users_pruned.is_empty() {
+            status!(
+                "{} {} user(s): {}",
+                style("✗").red(),
+                style("Pruned").red().bold(),
+                self.users_pruned.join(", ")
+            );
+        }
+
         if !self.contexts_skipped.is_empty() {
-            eprintln!(
+            status!(
                 "{} {} context(s): {}",
                 style("−").dim(),
                 style("Skipped").dim(),
@@ -108,7 +156,7 @@ impl ImportSummary {
             );
         }
         if !self.clusters_skipped.is_empty() {
-            eprintln!(
+            status!(
                 "{} {} cluster(s): {}",
                 style("−").dim(),
                 style("Skipped").dim(),
@@ -116,7 +164,7 @@ impl ImportSummary {
             );
         }
         if !self.users_skipped.is_empty() {
-            eprintln!(
+            status!(
                 "{} {} user(s): {}",
                 style("−").dim(),
                 style("Skipped").dim(),
@@ -126,25 +174,192 @@ impl ImportSummary {
     }
 }
 
+/// First bytes of every gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// The names imported contexts ended up under in the main config
+#[derive(Debug, Default)]
+pub struct ImportedContexts {
+    /// The first context that was added or overwritten
+    pub first: Option<String>,
+    /// The imported file's own current-context, if it was added or overwritten
+    pub source_current: Option<String>,
+}
+
+/// How long to wait when fetching a kubeconfig from a URL
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How `add` merges an external kubeconfig into the main config
+pub struct AddOptions {
+    /// Rename conflicting entries
+    pub rename: bool,
+    /// Overwrite existing entries
+    pub overwrite: bool,
+    /// Switch to the first imported context
+    pub switch: bool,
+    /// Make the imported file's current-context the current one
+    pub adopt_current: bool,
+    /// Names of the contexts to import; all contexts if empty
+    pub only: Vec<String>,
+    /// Pick the contexts to import interactively
+    pub select: bool,
+    /// Config file to merge into instead of the main config
+    pub output: Option<PathBuf>,
+    /// `Name: value` headers sent when fetching from a URL
+    pub headers: Vec<String>,
+    /// Save without previewing the changes and asking first
+    pub yes: bool,
+    /// Remove entries an earlier add imported from the same source that it no longer has
+    pub prune: bool,
+}
+
+/// The source as a URL, if it has an http or https scheme
+fn as_url(source: &Path) -> Option<&str> {
+    source
+        .to_str()
+        .filter(|s| s.starts_with("https://") || s.starts_with("http://"))
+}
+
+/// Read a kubeconfig file, transparently decompressing it if it is gzipped
+fn read_kubeconfig_file(path: &Path) -> Result<String> {
+    let bytes =
+        fs::read(path).with_context(|| format!("Failed to read file: {}", path.display()))?;
+    let has_gz_extension = path.extension().is_some_and(|ext| ext == "gz");
+    decode_kubeconfig(bytes, &path.display().to_string(), has_gz_extension)
+}
+
+/// Read a kubeconfig piped in on stdin, such as the output of `khelp export`
+fn read_kubeconfig_stdin() -> Result<String> {
+    let mut bytes = Vec::new();
+    io::stdin()
+        .read_to_end(&mut bytes)
+        .context("Failed to read kubeconfig from stdin")?;
+    decode_kubeconfig(bytes, "stdin", false)
+}
+
+/// Download a kubeconfig over HTTP(S), sending the given `Name: value` headers
+///
+/// Headers usually carry credentials, so they are only ever sent over HTTPS.
+fn fetch_kubeconfig(url: &str, headers: &[String]) -> Result<String> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(FETCH_TIMEOUT))
+        .http_status_as_error(false)
+        .build()
+        .into();
+
+    if !headers.is_empty() && url.starts_with("http://") {
+        anyhow::bail!(
+            "Refusing to send headers to {} over plain HTTP; use an https:// URL",
+            url
+        );
+    }
+
+    let mut request = agent.get(url);
+    for header in headers {
+        let (name, value) = parse_header(header)?;
+        request = request.header(name, value);
+    }
+
+    debug!("Fetching kubeconfig from: {}", url);
+    let mut response = match request.call() {
+        Ok(response) => response,
+        Err(ureq::Error::Timeout(_)) => anyhow::bail!(
+            "Timed out after {}s fetching {}",
+            FETCH_TIMEOUT.as_secs(),
+            url
+        ),
+        Err(e) => return Err(e).with_context(|| format!("Failed to fetch {}", url)),
+    };
+
+    let status = response.status().as_u16();
+    if status != 200 {
+        anyhow::bail!(
+            "Fetching {} failed: server responded with HTTP {}",
+            url,
+            status
+        );
+    }
+
+    let bytes = response
+        .body_mut()
+        .read_to_vec()
+        .with_context(|| format!("Failed to read response from {}", url))?;
+    decode_kubeconfig(bytes, url, url.ends_with(".gz"))
+}
+
+/// Split a `Name: value` header into its trimmed name and value
+fn parse_header(header: &str) -> Result<(&str, &str)> {
+    let (name, value) = header
+        .split_once(':')
+        .with_context(|| format!("Invalid header '{}': expected 'Name: value'", header))?;
+    Ok((name.trim(), value.trim()))
+}
+
+/// Decode kubeconfig bytes, decompressing them if they are gzipped
+///
+/// Gzip is detected by a `.gz` extension or by the gzip magic bytes.
+fn decode_kubeconfig(bytes: Vec<u8>, source: &str, has_gz_extension: bool) -> Result<String> {
+    let is_gzip = has_gz_extension || bytes.starts_with(&GZIP_MAGIC);
+    if !is_gzip {
+        return String::from_utf8(bytes)
+            .with_context(|| format!("File is not valid UTF-8: {}", source));
+    }
+
+    debug!("Decompressing gzipped kubeconfig: {}", source);
+    let mut content = String::new();
+    GzDecoder::new(bytes.as_slice())
+        .read_to_string(&mut content)
+        .with_context(|| format!("Failed to decompress gzipped file: {}", source))?;
+    Ok(content)
+}
+
 /// Add contexts from an external kubeconfig file into the main config
 ///
 /// # Arguments
 ///
-/// * `file_path` - Path to the external kubeconfig file
-/// * `rename` - Whether to rename conflicting entries
-/// * `overwrite` - Whether to overwrite existing entries
-/// * `switch` - Whether to switch to the first imported context
-pub fn add_context(file_path: PathBuf, rename: bool, overwrite: bool, switch: bool) -> Result<()> {
-    // Validate file path
-    if !file_path.exists() {
-        anyhow::bail!("File not found: {}", file_path.display());
-    }
+/// * `file_path` - Path to the external kubeconfig file, an http(s) URL to fetch it from,
+///   or `-` to read it from stdin (which also means nothing is asked interactively)
+/// * `options` - How conflicts are handled and which contexts are imported
+pub fn add_context(file_path: PathBuf, options: AddOptions) -> Result<()> {
+    let AddOptions {
+        rename,
+        overwrite,
+        switch,
+        adopt_current,
+        only,
+        select,
+        output,
+        headers,
+        yes,
+        prune,
+    } = options;
 
     debug!("Loading external kubeconfig from: {}", file_path.display());
 
+    let from_stdin = file_path == Path::new("-");
+    if from_stdin && select {
+        anyhow::bail!("--select can't be used when reading the kubeconfig from stdin");
+    }
+    if from_stdin && prune {
+        anyhow::bail!("--prune needs a file or URL to remember what was imported from it");
+    }
+
     // Load external config
-    let external_config_content = fs::read_to_string(&file_path)
-        .with_context(|| format!("Failed to read file: {}", file_path.display()))?;
+    let url = as_url(&file_path);
+    if url.is_none() && !headers.is_empty() {
+        anyhow::bail!("--header can only be used when adding from a URL");
+    }
+    let external_config_content = match url {
+        Some(url) => fetch_kubeconfig(url, &headers)?,
+        None if from_stdin => read_kubeconfig_stdin()?,
+        None => {
+            // Validate file path
+            if !file_path.exists() {
+                anyhow::bail!("File not found: {}", file_path.display());
+            }
+            read_kubeconfig_file(&file_path)?
+        }
+    };
 
     // Check for empty file
     let trimmed = external_config_content.trim();
@@ -218,6 +433,15 @@ pub fn add_context(file_path: PathBuf, rename: bool, overwrite: bool, switch: bo
         }
     }
 
+    let only = if select {
+        select_contexts(&external_config)?
+    } else {
+        only
+    };
+    if !only.is_empty() {
+        keep_only_contexts(&mut external_config, &only)?;
+    }
+
     // Validate we have something to import
     if external_config.contexts.is_empty()
         && external_config.clusters.is_empty()
@@ -226,8 +450,43 @@ pub fn add_context(file_path: PathBuf, rename: bool, overwrite: bool, switch: bo
         anyhow::bail!("External kubeconfig contains no contexts, clusters, or users to import");
     }
 
+    // kubectl can't use entries with empty names or references, so don't import them
+    let empty_fields = find_empty_fields(&external_config);
+    if !empty_fields.is_empty() {
+        anyhow::bail!(
+            "Invalid kubeconfig file: {}\n\nRequired fields are empty:\n  - {}",
+            file_path.display(),
+            empty_fields.join("\n  - ")
+        );
+    }
+
+    // What this source last imported, so entries it dropped can be pruned
+    let source_record = ImportRecord::of(&external_config);
+    let imports = match source_key(&file_path, from_stdin) {
+        Some(source) => {
+            let config_path = match &output {
+                Some(path) => path.clone(),
+                None => configured_kube_config_path()?,
+            };
+            Some((imports_path(&config_path)?, source))
+        }
+        None => None,
+    };
+    let previous_record = match &imports {
+        Some((path, source)) if prune => {
+            load_imports_from(path)?.remove(source).unwrap_or_default()
+        }
+        _ => ImportRecord::default(),
+    };
+
     // Load main config (or create empty one if it doesn't exist or is empty)
-    let mut main_config = load_kube_config_or_default()?;
+    let mut main_config = match &output {
+        Some(path) => {
+            debug!("Merging into output file: {}", path.display());
+            load_kube_config_or_default_from(path)?
+        }
+        None => load_kube_config_or_default()?,
+    };
     debug!(
         "Main config loaded: {} contexts, {} clusters, {} users",
         main_config.contexts.len(),
@@ -235,8 +494,276 @@ pub fn add_context(file_path: PathBuf, rename: bool, overwrite: bool, switch: bo
         main_config.users.len()
     );
 
+    warn_conflicting_fields(&external_config);
+
+    let source_current = external_config.current_context.clone();
+    let (mut summary, imported) =
+        merge_external_config(&mut main_config, external_config, rename, overwrite);
+    if prune {
+        prune_stale_entries(
+            &mut main_config,
+            &previous_record,
+            &source_record,
+            &mut summary,
+        );
+    }
+    let record_imports = |config: &KubeConfig| match &imports {
+        Some((path, source)) => {
+            save_import_record_to(path, source, source_record.retained_in(config))
+        }
+        None => Ok(()),
+    };
+
+    // Check if any changes were made
+    if !summary.has_changes() {
+        record_imports(&main_config)?;
+        warn!("No changes made - all entries already exist in the main config");
+        summary.print_summary();
+        status!(
+            "\n{} Use {} to rename conflicting entries or {} to overwrite them.",
+            style("Tip:").cyan().bold(),
+            style("--rename").yellow(),
+            style("--overwrite").yellow()
+        );
+        return Ok(());
+    }
+
+    // Show what will change and ask before writing, unless told not to or unable to ask
+    let previewed = !yes && !from_stdin && can_prompt();
+    if previewed {
+        summary.print_summary();
+        status!();
+        if !confirm("Write these changes?", true, false)? {
+            status!("Import cancelled, nothing was written");
+            return Ok(());
+        }
+    }
+
+    // Save the config
+    let save = |config: &KubeConfig| match &output {
+        Some(path) => save_kube_config_to(config, path),
+        None => save_kube_config(config),
+    };
+    save(&main_config)?;
+    record_imports(&main_config)?;
+
+    // Print summary, unless it was already shown as the preview
+    if !previewed {
+        summary.print_summary();
+    }
+    if let Some(path) = &output {
+        status!("\nWrote merged config to {}", style(path.display()).cyan());
+    }
+
+    // Adopt the file's current context, or switch to the first added one, if requested
+    let adopted = if adopt_current {
+        adopted_current_context(&source_current, &imported)
+    } else {
+        None
+    };
+    let switch_to = match adopted {
+        Some(context_name) => Some(context_name),
+        None if switch => {
+            if imported.first.is_none() {
+                warn!("No new contexts were added to switch to");
+            }
+            imported.first
+        }
+        None => None,
+    };
+    if let Some(context_name) = switch_to {
+        main_config.current_context = context_name.clone();
+        save(&main_config)?;
+        status!(
+            "\nSwitched to context: {}",
+            style(&context_name).green().bold()
+        );
+    }
+
+    Ok(())
+}
+
+/// The context `--adopt-current` makes current: the imported file's current-context,
+/// under the name it was imported as
+///
+/// Warns and returns None when the file has no current-context or it wasn't imported,
+/// leaving `--switch`, if given, to decide.
+fn adopted_current_context(source_current: &str, imported: &ImportedContexts) -> Option<String> {
+    if source_current.is_empty() {
+        warn!("The imported file has no current-context to adopt");
+        return None;
+    }
+    if imported.source_current.is_none() {
+        warn!(
+            "The imported file's current-context '{}' wasn't imported, so it can't be adopted",
+            source_current
+        );
+    }
+    imported.source_current.clone()
+}
+
+/// The key an import source is recorded under: the URL, or the file's canonical path
+///
+/// Stdin has no lasting identity, so what is read from it isn't recorded.
+fn source_key(file_path: &Path, from_stdin: bool) -> Option<String> {
+    if from_stdin {
+        return None;
+    }
+    if let Some(url) = as_url(file_path) {
+        return Some(url.to_string());
+    }
+    let path = fs::canonicalize(file_path).unwrap_or_else(|_| file_path.to_path_buf());
+    Some(path.display().to_string())
+}
+
+/// Remove entries an earlier add imported from the same source that it no longer has
+///
+/// Only entries still carrying the name and data they were imported with are removed.
+/// The current context, and clusters and users a remaining context uses, are kept.
+fn prune_stale_entries(
+    main_config: &mut KubeConfig,
+    previous: &ImportRecord,
+    source: &ImportRecord,
+    summary: &mut ImportSummary,
+) {
+    let present = ImportRecord::of(main_config);
+    let stale = |recorded: &BTreeMap<String, u64>,
+                 incoming: &BTreeMap<String, u64>,
+                 present: &BTreeMap<String, u64>| {
+        recorded
+            .iter()
+            .filter(|(name, print)| {
+                !incoming.contains_key(*name) && present.get(*name) == Some(print)
+            })
+            .map(|(name, _)| name.clone())
+            .collect::<HashSet<String>>()
+    };
+
+    let mut stale_contexts = stale(&previous.contexts, &source.contexts, &present.contexts);
+    if stale_contexts.remove(&main_config.current_context) {
+        eprintln!(
+            "{} keeping the current context '{}', which its source no longer has",
+            style("Warning:").yellow().bold(),
+            main_config.current_context
+        );
+    }
+    main_config.contexts.retain(|c| {
+        let keep = !stale_contexts.contains(&c.name);
+        if !keep {
+            debug!("Pruned context: {}", c.name);
+            summary.contexts_pruned.push(c.name.clone());
+        }
+        keep
+    });
+
+    let used_clusters = main_config
+        .contexts
+        .iter()
+        .map(|c| c.context.cluster.clone())
+        .collect::<HashSet<String>>();
+    let used_users = main_config
+        .contexts
+        .iter()
+        .map(|c| c.context.user.clone())
+        .collect::<HashSet<String>>();
+
+    let stale_clusters = stale(&previous.clusters, &source.clusters, &present.clusters);
+    main_config.clusters.retain(|c| {
+        let keep = !stale_clusters.contains(&c.name) || used_clusters.contains(&c.name);
+        if !keep {
+            debug!("Pruned cluster: {}", c.name);
+            summary.clusters_pruned.push(c.name.clone());
+        }
+        keep
+    });
+
+    let stale_users = stale(&previous.users, &source.users, &present.users);
+    main_config.users.retain(|u| {
+        let keep = !stale_users.contains(&u.name) || used_users.contains(&u.name);
+        if !keep {
+            debug!("Pruned user: {}", u.name);
+            summary.users_pruned.push(u.name.clone());
+        }
+        keep
+    });
+}
+
+/// Let the user pick which of the external file's contexts to import
+fn select_contexts(external_config: &KubeConfig) -> Result<Vec<String>> {
+    let names: Vec<&str> = external_config
+        .contexts
+        .iter()
+        .map(|c| c.name.as_str())
+        .collect();
+
+    if names.is_empty() {
+        anyhow::bail!("External kubeconfig contains no contexts to select from");
+    }
+
+    ensure_can_prompt()?;
+    let selections = MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Select contexts to import (Space to select, Enter to confirm)")
+        .items(&names)
+        .interact()
+        .context("Failed to display interactive selection")?;
+
+    if selections.is_empty() {
+        anyhow::bail!("No contexts selected");
+    }
+
+    Ok(selections.iter().map(|&i| names[i].to_string()).collect())
+}
+
+/// Reduce an external config to the named contexts and the clusters and users they use
+fn keep_only_contexts(external_config: &mut KubeConfig, names: &[String]) -> Result<()> {
+    for name in names {
+        if !external_config.contexts.iter().any(|c| c.name == *name) {
+            anyhow::bail!("Context '{}' not found in the external kubeconfig", name);
+        }
+    }
+
+    external_config.contexts.retain(|c| names.contains(&c.name));
+
+    // Whatever the kept contexts don't use was only needed by the dropped ones
+    let (unused_clusters, unused_users) = find_orphans(external_config);
+    external_config
+        .clusters
+        .retain(|c| !unused_clusters.contains(&c.name));
+    external_config
+        .users
+        .retain(|u| !unused_users.contains(&u.name));
+
+    debug!(
+        "Importing only {} context(s), {} cluster(s), {} user(s)",
+        external_config.contexts.len(),
+        external_config.clusters.len(),
+        external_config.users.len()
+    );
+    Ok(())
+}
+
+/// Merge the entries of an external kubeconfig into the main config
+///
+/// Returns the import summary and the names the added or overwritten contexts got.
+/// The main config's current-context is never changed here, even when its entry is
+/// overwritten; only `--switch` and `--adopt-current` move it.
+pub fn merge_external_config(
+    main_config: &mut KubeConfig,
+    external_config: KubeConfig,
+    rename: bool,
+    overwrite: bool,
+) -> (ImportSummary, ImportedContexts) {
     let mut summary = ImportSummary::new();
 
+    // Names a renamed entry must avoid: everything already present plus every
+    // incoming name, so a rename never takes a name a later entry arrives with
+    let mut cluster_names = get_cluster_names(main_config);
+    cluster_names.extend(external_config.clusters.iter().map(|c| c.name.clone()));
+    let mut user_names = get_user_names(main_config);
+    user_names.extend(external_config.users.iter().map(|u| u.name.clone()));
+    let mut context_names = get_context_names(main_config);
+    context_names.extend(external_config.contexts.iter().map(|c| c.name.clone()));
+
     // Track name mappings for renamed entities
     let mut cluster_name_map: std::collections::HashMap<String, String> =
         std::collections::HashMap::new();
@@ -244,9 +771,16 @@ pub fn add_context(file_path: PathBuf, rename: bool, overwrite: bool, switch: bo
         std::collections::HashMap::new();
 
     // Import clusters
+    let mut seen = HashSet::new();
     for cluster in external_config.clusters {
         let cluster_name = cluster.name.clone();
 
+        if !seen.insert(cluster_name.clone()) {
+            warn_duplicate("cluster", &cluster_name);
+            summary.clusters_skipped.push(cluster_name);
+            continue;
+        }
+
         if let Some(existing_idx) = main_config
             .clusters
             .iter()
@@ -257,7 +791,8 @@ pub fn add_context(file_path: PathBuf, rename: bool, overwrite: bool, switch: bo
                 summary.clusters_overwritten.push(cluster_name.clone());
                 debug!("Overwritten cluster: {}", cluster_name);
             } else if rename {
-                let new_name = find_available_name(&cluster_name, &get_cluster_names(&main_config));
+                let new_name = find_available_name(&cluster_name, &cluster_names);
+                cluster_names.insert(new_name.clone());
                 cluster_name_map.insert(cluster_name.clone(), new_name.clone());
                 let mut renamed_cluster = cluster;
                 renamed_cluster.name = new_name.clone();
@@ -276,16 +811,24 @@ pub fn add_context(file_path: PathBuf, rename: bool, overwrite: bool, switch: bo
     }
 
     // Import users
+    let mut seen = HashSet::new();
     for user in external_config.users {
         let user_name = user.name.clone();
 
+        if !seen.insert(user_name.clone()) {
+            warn_duplicate("user", &user_name);
+            summary.users_skipped.push(user_name);
+            continue;
+        }
+
         if let Some(existing_idx) = main_config.users.iter().position(|u| u.name == user_name) {
             if overwrite {
                 main_config.users[existing_idx] = user;
                 summary.users_overwritten.push(user_name.clone());
                 debug!("Overwritten user: {}", user_name);
             } else if rename {
-                let new_name = find_available_name(&user_name, &get_user_names(&main_config));
+                let new_name = find_available_name(&user_name, &user_names);
+                user_names.insert(new_name.clone());
                 user_name_map.insert(user_name.clone(), new_name.clone());
                 let mut renamed_user = user;
                 renamed_user.name = new_name.clone();
@@ -304,11 +847,19 @@ pub fn add_context(file_path: PathBuf, rename: bool, overwrite: bool, switch: bo
     }
 
     // Import contexts
-    let mut first_added_context: Option<String> = None;
+    let mut imported = ImportedContexts::default();
+    let source_current = external_config.current_context;
 
+    let mut seen = HashSet::new();
     for mut context in external_config.contexts {
         let context_name = context.name.clone();
 
+        if !seen.insert(context_name.clone()) {
+            warn_duplicate("context", &context_name);
+            summary.contexts_skipped.push(context_name);
+            continue;
+        }
+
         // Update cluster and user references if they were renamed
         if let Some(new_cluster_name) = cluster_name_map.get(&context.context.cluster) {
             context.context.cluster = new_cluster_name.clone();
@@ -317,7 +868,7 @@ pub fn add_context(file_path: PathBuf, rename: bool, overwrite: bool, switch: bo
             context.context.user = new_user_name.clone();
         }
 
-        if let Some(existing_idx) = main_config
+        let stored_name = if let Some(existing_idx) = main_config
             .contexts
             .iter()
             .position(|c| c.name == context_name)
@@ -325,68 +876,48 @@ pub fn add_context(file_path: PathBuf, rename: bool, overwrite: bool, switch: bo
             if overwrite {
                 main_config.contexts[existing_idx] = context;
                 summary.contexts_overwritten.push(context_name.clone());
-                if first_added_context.is_none() {
-                    first_added_context = Some(context_name.clone());
-                }
                 debug!("Overwritten context: {}", context_name);
+                context_name.clone()
             } else if rename {
-                let new_name = find_available_name(&context_name, &get_context_names(&main_config));
+                let new_name = find_available_name(&context_name, &context_names);
+                context_names.insert(new_name.clone());
                 let mut renamed_context = context;
                 renamed_context.name = new_name.clone();
                 main_config.contexts.push(renamed_context);
                 summary.contexts_added.push(new_name.clone());
-                if first_added_context.is_none() {
-                    first_added_context = Some(new_name.clone());
-                }
                 debug!("Added renamed context: {} -> {}", context_name, new_name);
+                new_name
             } else {
                 summary.contexts_skipped.push(context_name.clone());
                 debug!("Skipped existing context: {}", context_name);
+                continue;
             }
         } else {
             main_config.contexts.push(context);
             summary.contexts_added.push(context_name.clone());
-            if first_added_context.is_none() {
-                first_added_context = Some(context_name.clone());
-            }
             debug!("Added context: {}", context_name);
-        }
-    }
+            context_name.clone()
+        };
 
-    // Check if any changes were made
-    if !summary.has_changes() {
-        warn!("No changes made - all entries already exist in the main config");
-        summary.print_summary();
-        eprintln!(
-            "\n{} Use {} to rename conflicting entries or {} to overwrite them.",
-            style("Tip:").cyan().bold(),
-            style("--rename").yellow(),
-            style("--overwrite").yellow()
-        );
-        return Ok(());
-    }
-
-    // Save the config
-    save_kube_config(&main_config)?;
-
-    // Print summary
-    summary.print_summary();
-
-    // Switch to first added context if requested
-    if switch {
-        if let Some(context_name) = first_added_context {
-            main_config.current_context = context_name.clone();
-            save_kube_config(&main_config)?;
-            eprintln!(
-                "\nSwitched to context: {}",
-                style(&context_name).green().bold()
-            );
-        } else {
-            warn!("No new contexts were added to switch to");
+        if imported.first.is_none() {
+            imported.first = Some(stored_name.clone());
+        }
+        if context_name == source_current {
+            imported.source_current = Some(stored_name);
         }
     }
 
-    Ok(())
+    (summary, imported)
+}
+
+/// Warn about an entry that appears more than once in the imported file
+fn warn_duplicate(kind: &str, name: &str) {
+    eprintln!(
+        "{} {} '{}' appears more than once in the imported file; skipping the duplicate",
+        style("Warning:").yellow().bold(),
+        kind,
+        name
+    );
 }
 
 /// Find an available name by appending a suffix
@@ -416,3 +947,358 @@ fn get_user_names(config: &KubeConfig) -> HashSet<String> {
 fn get_context_names(config: &KubeConfig) -> HashSet<String> {
     config.contexts.iter().map(|c| c.name.clone()).collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(yaml: &str) -> KubeConfig {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_rename_avoids_names_from_same_import() {
+        let mut main_config = parse(
+            r#"
+clusters:
+- name: prod
+  cluster: {server: "https://main.example.com"}
+users:
+- name: admin
+  user: {token: main}
+contexts:
+- name: prod
+  context: {cluster: prod, user: admin}
+"#,
+        );
+        // "prod" must be renamed, and the file already carries "prod-imported"
+        let external_config = parse(
+            r#"
+clusters:
+- name: prod
+  cluster: {server: "https://one.example.com"}
+- name: prod-imported
+  cluster: {server: "https://two.example.com"}
+- name: prod
+  cluster: {server: "https://dup.example.com"}
+users:
+- name: admin
+  user: {token: one}
+- name: admin-imported
+  user: {token: two}
+contexts:
+- name: prod
+  context: {cluster: prod, user: admin}
+- name: prod-imported
+  context: {cluster: prod-imported, user: admin-imported}
+"#,
+        );
+
+        let (summary, imported) =
+            merge_external_config(&mut main_config, external_config, true, false);
+
+        // Every imported entry should end up with a unique name
+        assert_eq!(
+            get_cluster_names(&main_config).len(),
+            main_config.clusters.len()
+        );
+        assert_eq!(get_user_names(&main_config).len(), main_config.users.len());
+        assert_eq!(
+            get_context_names(&main_config).len(),
+            main_config.contexts.len()
+        );
+        assert_eq!(main_config.clusters.len(), 3);
+        assert_eq!(summary.clusters_skipped, vec!["prod".to_string()]);
+
+        let renamed = main_config
+            .contexts
+            .iter()
+            .find(|c| c.name == "prod-imported-2")
+            .expect("conflicting context should be renamed past the incoming name");
+        assert_eq!(renamed.context.cluster, "prod-imported-2");
+        assert_eq!(renamed.context.user, "admin-imported-2");
+        assert_eq!(imported.first.as_deref(), Some("prod-imported-2"));
+
+        let incoming = main_config
+            .contexts
+            .iter()
+            .find(|c| c.name == "prod-imported")
+            .unwrap();
+        assert_eq!(incoming.context.cluster, "prod-imported");
+        let cluster = main_config
+            .clusters
+            .iter()
+            .find(|c| c.name == "prod-imported")
+            .unwrap();
+        assert_eq!(cluster.cluster.server, "https://two.example.com");
+    }
+
+    #[test]
+    fn test_keep_only_contexts_pulls_in_referenced_entries() {
+        let mut external_config = parse(
+            r#"
+clusters:
+- name: shared
+  cluster: {server: "https://shared.example.com"}
+- name: other
+  cluster: {server: "https://other.example.com"}
+users:
+- name: alice
+  user: {token: a}
+- name: bob
+  user: {token: b}
+contexts:
+- name: dev
+  context: {cluster: shared, user: alice}
+- name: staging
+  context: {cluster: shared, user: bob}
+- name: unrelated
+  context: {cluster: other, user: bob}
+"#,
+        );
+
+        keep_only_contexts(&mut external_config, &["dev".to_string()]).unwrap();
+
+        assert_eq!(get_context_names(&external_config).len(), 1);
+        assert!(get_context_names(&external_config).contains("dev"));
+        assert_eq!(
+            get_cluster_names(&external_config),
+            HashSet::from(["shared".to_string()])
+        );
+        assert_eq!(
+            get_user_names(&external_config),
+            HashSet::from(["alice".to_string()])
+        );
+
+        assert!(keep_only_contexts(&mut external_config, &["missing".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_overwrite_keeps_current_context() {
+        let mut main_config = parse(
+            r#"
+current-context: prod
+clusters:
+- name: prod
+  cluster: {server: "https://prod.example.com"}
+users:
+- name: admin
+  user: {token: expired}
+contexts:
+- name: dev
+  context: {cluster: prod, user: admin, namespace: dev}
+- name: prod
+  context: {cluster: prod, user: admin}
+"#,
+        );
+        let external_config = parse(
+            r#"
+current-context: other
+clusters:
+- name: prod
+  cluster: {server: "https://prod.example.com"}
+users:
+- name: admin
+  user: {token: fresh}
+contexts:
+- name: other
+  context: {cluster: prod, user: admin}
+- name: prod
+  context: {cluster: prod, user: admin}
+"#,
+        );
+
+        let (summary, imported) =
+            merge_external_config(&mut main_config, external_config, false, true);
+
+        assert_eq!(main_config.current_context, "prod");
+        assert_eq!(summary.contexts_overwritten, vec!["prod".to_string()]);
+        assert_eq!(summary.users_overwritten, vec!["admin".to_string()]);
+        assert_eq!(main_config.users[0].user.token.as_deref(), Some("fresh"));
+        // Only --switch and --adopt-current act on these
+        assert_eq!(imported.first.as_deref(), Some("other"));
+    }
+
+    #[test]
+    fn test_prune_keeps_edited_and_used_entries() {
+        let previous = ImportRecord::of(&parse(
+            r#"
+clusters:
+- name: shared
+  cluster: {server: "https://shared.example.com"}
+- name: old
+  cluster: {server: "https://old.example.com"}
+users:
+- name: alice
+  user: {token: a}
+- name: bob
+  user: {token: b}
+contexts:
+- name: dev
+  context: {cluster: shared, user: alice}
+- name: old
+  context: {cluster: old, user: bob}
+- name: edited
+  context: {cluster: old, user: bob}
+"#,
+        ));
+        let source = ImportRecord::of(&parse(
+            r#"
+clusters:
+- name: shared
+  cluster: {server: "https://shared.example.com"}
+users:
+- name: alice
+  user: {token: a}
+contexts:
+- name: dev
+  context: {cluster: shared, user: alice}
+"#,
+        ));
+        let mut main_config = parse(
+            r#"
+current-context: dev
+clusters:
+- name: shared
+  cluster: {server: "https://shared.example.com"}
+- name: old
+  cluster: {server: "https://old.example.com"}
+users:
+- name: alice
+  user: {token: a}
+- name: bob
+  user: {token: b}
+contexts:
+- name: dev
+  context: {cluster: shared, user: alice}
+- name: old
+  context: {cluster: old, user: bob}
+- name: edited
+  context: {cluster: old, user: bob, namespace: mine}
+"#,
+        );
+
+        let mut summary = ImportSummary::new();
+        prune_stale_entries(&mut main_config, &previous, &source, &mut summary);
+
+        // "edited" changed since it was imported, so it and what it uses stay
+        assert_eq!(summary.contexts_pruned, vec!["old".to_string()]);
+        assert!(summary.clusters_pruned.is_empty());
+        assert!(summary.users_pruned.is_empty());
+        assert_eq!(
+            get_context_names(&main_config),
+            HashSet::from(["dev".to_string(), "edited".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_merge_tracks_source_current_context() {
+        let mut main_config = parse(
+            r#"
+current-context: prod
+clusters:
+- name: prod
+  cluster: {server: "https://prod.example.com"}
+users:
+- name: admin
+  user: {token: old}
+contexts:
+- name: prod
+  context: {cluster: prod, user: admin}
+"#,
+        );
+        let external = r#"
+current-context: prod
+clusters:
+- name: staging
+  cluster: {server: "https://staging.example.com"}
+users:
+- name: ci
+  user: {token: new}
+contexts:
+- name: staging
+  context: {cluster: staging, user: ci}
+- name: prod
+  context: {cluster: staging, user: ci}
+"#;
+
+        // Renamed, the file's current context is adopted under its new name
+        let (_, imported) =
+            merge_external_config(&mut main_config.clone(), parse(external), true, false);
+        assert_eq!(imported.first.as_deref(), Some("staging"));
+        assert_eq!(imported.source_current.as_deref(), Some("prod-imported"));
+        assert_eq!(
+            adopted_current_context("prod", &imported).as_deref(),
+            Some("prod-imported")
+        );
+
+        // Skipped, it wasn't imported and isn't adopted
+        let (_, imported) = merge_external_config(&mut main_config, parse(external), false, false);
+        assert_eq!(imported.source_current, None);
+        assert_eq!(adopted_current_context("prod", &imported), None);
+        assert_eq!(adopted_current_context("", &imported), None);
+    }
+
+    /// Serve one HTTP response with the given body on a local port, returning its URL
+    fn serve_once(body: &'static str) -> String {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/kubeconfig", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+        });
+        url
+    }
+
+    #[test]
+    fn test_as_url() {
+        assert_eq!(
+            as_url(Path::new("https://example.com/config")),
+            Some("https://example.com/config")
+        );
+        assert_eq!(
+            as_url(Path::new("http://example.com/config")),
+            Some("http://example.com/config")
+        );
+        assert_eq!(as_url(Path::new("config.yaml")), None);
+        assert_eq!(as_url(Path::new("ftp://example.com/config")), None);
+    }
+
+    #[test]
+    fn test_parse_header() {
+        assert_eq!(
+            parse_header("Authorization:  Bearer abc ").unwrap(),
+            ("Authorization", "Bearer abc")
+        );
+        let err = parse_header("Authorization Bearer abc").unwrap_err();
+        assert!(err.to_string().starts_with("Invalid header"));
+    }
+
+    #[test]
+    fn test_fetch_kubeconfig_from_local_server() {
+        let url = serve_once("apiVersion: v1\nkind: Config\n");
+        let content = fetch_kubeconfig(&url, &[]).unwrap();
+        assert_eq!(content, "apiVersion: v1\nkind: Config\n");
+    }
+
+    #[test]
+    fn test_fetch_refuses_headers_over_plain_http() {
+        let headers = vec!["Authorization: Bearer secret".to_string()];
+        let err = fetch_kubeconfig("http://127.0.0.1:9/kubeconfig", &headers).unwrap_err();
+        assert!(err.to_string().contains("over plain HTTP"));
+    }
+}
@@ -0,0 +1,45 @@
+use anyhow::{Context, Result};
+use console::style;
+
+use crate::commands::output::status;
+use crate::config::operations::load_kube_config;
+use crate::config::settings::{Settings, set_alias_in, settings_path};
+
+/// Map a short alias to a context name in the settings file
+pub fn set_alias(alias: String, context: String) -> Result<()> {
+    let path = settings_path().context("Could not determine the settings file location")?;
+
+    // The context may be added later, so a missing one is only worth a warning
+    if let Ok(config) = load_kube_config()
+        && !config.contexts.iter().any(|c| c.name == context)
+    {
+        eprintln!(
+            "{} context '{}' does not exist in the current kubeconfig",
+            style("Warning:").yellow().bold(),
+            context
+        );
+    }
+
+    set_alias_in(&path, &alias, &context)?;
+
+    status!(
+        "{} Alias {} now points to {}",
+        style("✓").green(),
+        style(&alias).green().bold(),
+        style(&context).cyan()
+    );
+
+    Ok(())
+}
+
+/// Print all defined aliases, one `alias -> context` pair per line
+pub fn list_aliases(settings: &Settings) {
+    if settings.aliases.is_empty() {
+        status!("No aliases defined");
+        return;
+    }
+
+    for (alias, context) in &settings.aliases {
+        println!("{} -> {}", style(alias).green().bold(), context);
+    }
+}
@@ -0,0 +1,281 @@
+use anyhow::{Context, Result};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use log::debug;
+use std::fs;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use ureq::Proxy;
+use ureq::tls::{ClientCert, PemItem, RootCerts, TlsConfig};
+
+use crate::config::kubernetes::{ClusterData, UserData};
+use crate::error::KhelpError;
+
+/// Credentials khelp can present to an API server
+pub enum Credentials {
+    /// No credentials; only useful for unauthenticated endpoints like `/version`
+    Anonymous,
+    /// A bearer token, from `token` or `tokenFile`
+    Token(String),
+    /// A client certificate and its private key
+    ClientCert(ClientCert),
+}
+
+impl Credentials {
+    /// Resolve the credentials a user entry authenticates with
+    ///
+    /// Only token and client certificate auth are supported; plugins such as exec
+    /// and auth-provider are rejected rather than executed.
+    pub fn for_user(user_name: &str, user: &UserData) -> Result<Self> {
+        let unsupported = |auth_type| {
+            anyhow::Error::new(KhelpError::UnsupportedAuth {
+                auth_type,
+                user: user_name.to_string(),
+            })
+        };
+
+        if user.exec.is_some() {
+            return Err(unsupported("exec"));
+        }
+        if user.auth_provider.is_some() {
+            return Err(unsupported("auth-provider"));
+        }
+        if let Some(token) = &user.token {
+            return Ok(Self::Token(token.trim().to_string()));
+        }
+        if let Some(path) = &user.token_file {
+            let token = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read token file: {}", path))?;
+            return Ok(Self::Token(token.trim().to_string()));
+        }
+        if user.client_certificate_data.is_some() || user.client_certificate.is_some() {
+            return Ok(Self::ClientCert(client_cert(user)?));
+        }
+        if user.username.is_some() || user.password.is_some() {
+            return Err(unsupported("basic"));
+        }
+        Ok(Self::Anonymous)
+    }
+}
+
+/// Build an HTTP agent that trusts the cluster's CA, as kubectl would
+///
+/// Requests go through the cluster's `proxy-url` when one is set.
+pub fn build_agent(
+    cluster: &ClusterData,
+    credentials: &Credentials,
+    timeout: Duration,
+) -> Result<ureq::Agent> {
+    let client_cert = match credentials {
+        Credentials::ClientCert(cert) => Some(cert.clone()),
+        _ => None,
+    };
+
+    let tls_config = build_tls_config(cluster, client_cert)?;
+
+    let proxy = match &cluster.proxy_url {
+        Some(url) => {
+            debug!("Routing requests through proxy: {}", url);
+            Some(parse_proxy(url)?)
+        }
+        None => None,
+    };
+
+    Ok(ureq::Agent::config_builder()
+        .timeout_global(Some(timeout))
+        .http_status_as_error(false)
+        .tls_config(tls_config)
+        .proxy(proxy)
+        .build()
+        .into())
+}
+
+/// Check that a proxy accepts TCP connections, so a dead proxy isn't mistaken
+/// for an unreachable API server
+pub fn ensure_proxy_reachable(proxy_url: &str, timeout: Duration) -> Result<()> {
+    let proxy = parse_proxy(proxy_url)?;
+    let address = format!("{}:{}", proxy.host(), proxy.port());
+    debug!("Connecting to proxy at {}", address);
+
+    let addrs: Vec<_> = address
+        .to_socket_addrs()
+        .with_context(|| format!("Proxy {} is unreachable: could not resolve host", proxy_url))?
+        .collect();
+
+    let mut last_error = None;
+    for addr in addrs {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(_) => return Ok(()),
+            Err(e) => last_error = Some(e),
+        }
+    }
+
+    match last_error {
+        Some(e) => Err(e).with_context(|| format!("Proxy {} is unreachable", proxy_url)),
+        None => anyhow::bail!("Proxy {} is unreachable: no addresses found", proxy_url),
+    }
+}
+
+fn parse_proxy(proxy_url: &str) -> Result<Proxy> {
+    Proxy::new(proxy_url).with_context(|| format!("Invalid proxy-url: {}", proxy_url))
+}
+
+/// Send a `GET` for an API path, attaching a bearer token if there is one
+///
+/// HTTP error statuses are returned as responses, not errors.
+pub fn get(
+    agent: &ureq::Agent,
+    cluster: &ClusterData,
+    path: &str,
+    credentials: &Credentials,
+    timeout: Duration,
+) -> Result<ureq::http::Response<ureq::Body>> {
+    let url = format!("{}{}", cluster.server.trim_end_matches('/'), path);
+    debug!("Sending GET {}", url);
+
+    let mut request = agent.get(&url);
+    if let Credentials::Token(token) = credentials {
+        request = request.header("Authorization", format!("Bearer {}", token));
+    }
+
+    // Name the proxy in errors so it's clear which hop failed
+    let via = cluster
+        .proxy_url
+        .as_ref()
+        .map(|proxy| format!(" through proxy {}", proxy))
+        .unwrap_or_default();
+
+    match request.call() {
+        Ok(response) => {
+            debug!("API server responded with status: {}", response.status());
+            Ok(response)
+        }
+        Err(ureq::Error::Timeout(_)) => anyhow::bail!(
+            "Timed out after {}s waiting for {}{}",
+            timeout.as_secs(),
+            cluster.server,
+            via
+        ),
+        Err(e) => Err(e).with_context(|| format!("Failed to reach {}{}", cluster.server, via)),
+    }
+}
+
+/// Read PEM data that is either inline (base64-encoded) or in a file
+pub fn read_pem(
+    data: Option<&String>,
+    path: Option<&String>,
+    what: &str,
+) -> Result<Option<Vec<u8>>> {
+    if let Some(data) = data {
+        return STANDARD
+            .decode(data.trim())
+            .map(Some)
+            .with_context(|| format!("Failed to decode {}-data", what));
+    }
+    if let Some(path) = path {
+        return fs::read(path)
+            .map(Some)
+            .with_context(|| format!("Failed to read {}: {}", what, path));
+    }
+    Ok(None)
+}
+
+fn build_tls_config(cluster: &ClusterData, client_cert: Option<ClientCert>) -> Result<TlsConfig> {
+    let builder = TlsConfig::builder().client_cert(client_cert);
+
+    if cluster.insecure_skip_tls_verify == Some(true) {
+        debug!("Skipping TLS verification (insecure-skip-tls-verify)");
+        return Ok(builder.disable_verification(true).build());
+    }
+
+    let Some(ca_pem) = read_pem(
+        cluster.certificate_authority_data.as_ref(),
+        cluster.certificate_authority.as_ref(),
+        "certificate-authority",
+    )?
+    else {
+        debug!("No cluster CA configured, using default root certificates");
+        return Ok(builder.build());
+    };
+
+    let mut certs = Vec::new();
+    for item in ureq::tls::parse_pem(&ca_pem) {
+        if let PemItem::Certificate(cert) = item.context("Failed to parse cluster CA")? {
+            certs.push(cert);
+        }
+    }
+
+    if certs.is_empty() {
+        anyhow::bail!("Cluster CA does not contain any certificates");
+    }
+
+    Ok(builder
+        .root_certs(RootCerts::new_with_certs(&certs))
+        .build())
+}
+
+fn client_cert(user: &UserData) -> Result<ClientCert> {
+    let cert_pem = read_pem(
+        user.client_certificate_data.as_ref(),
+        user.client_certificate.as_ref(),
+        "client-certificate",
+    )?
+    .context("User has no client certificate")?;
+    let key_pem = read_pem(
+        user.client_key_data.as_ref(),
+        user.client_key.as_ref(),
+        "client-key",
+    )?
+    .context("User has a client certificate but no client key")?;
+
+    let mut chain = Vec::new();
+    for item in ureq::tls::parse_pem(&cert_pem) {
+        if let PemItem::Certificate(cert) = item.context("Failed to parse client certificate")? {
+            chain.push(cert);
+        }
+    }
+    if chain.is_empty() {
+        anyhow::bail!("Client certificate does not contain any certificates");
+    }
+
+    let key = ureq::tls::PrivateKey::from_pem(&key_pem).context("Failed to parse client key")?;
+
+    Ok(ClientCert::new_with_certs(&chain, key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_credentials_for_user() {
+        let token = UserData {
+            token: Some(" secret\n".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            Credentials::for_user("alice", &token),
+            Ok(Credentials::Token(t)) if t == "secret"
+        ));
+
+        let missing_token_file = UserData {
+            token_file: Some("/nonexistent/khelp-token".to_string()),
+            ..Default::default()
+        };
+        assert!(Credentials::for_user("alice", &missing_token_file).is_err());
+
+        let basic = UserData {
+            username: Some("admin".to_string()),
+            ..Default::default()
+        };
+        let error = Credentials::for_user("alice", &basic)
+            .err()
+            .expect("basic auth accepted");
+        assert!(error.to_string().contains("'basic'"));
+
+        assert!(matches!(
+            Credentials::for_user("alice", &UserData::default()),
+            Ok(Credentials::Anonymous)
+        ));
+    }
+}
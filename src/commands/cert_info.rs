@@ -0,0 +1,317 @@
+use anyhow::{Context, Result};
+use console::{StyledObject, style};
+use x509_parser::pem::Pem;
+use x509_parser::time::ASN1Time;
+
+use crate::commands::api::read_pem;
+use crate::commands::output::status;
+use crate::config::kubernetes::UserData;
+use crate::config::operations::load_kube_config;
+use crate::error::KhelpError;
+
+/// Certificates expiring within this many days get a warning
+const EXPIRY_WARNING_DAYS: i64 = 30;
+
+/// Client certificates expiring within this many days show as expiring in `list --wide`
+const HEALTH_WARNING_DAYS: i64 = 14;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// How a user's credentials look without contacting the cluster
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CredentialHealth {
+    /// The client certificate is valid for a while yet
+    Valid,
+    /// The client certificate expires within `HEALTH_WARNING_DAYS`
+    Expiring,
+    /// The client certificate has expired
+    Expired,
+    /// The user is missing, or its client certificate can't be read or parsed
+    Invalid,
+    /// Token, exec, and other auth that can't be checked offline
+    Unchecked,
+}
+
+impl CredentialHealth {
+    /// A one-character, colored marker for listings
+    pub fn symbol(self) -> StyledObject<&'static str> {
+        match self {
+            Self::Valid => style("✓").green(),
+            Self::Expiring => style("!").yellow().bold(),
+            Self::Expired => style("✗").red().bold(),
+            Self::Invalid => style("?").red(),
+            Self::Unchecked => style("-").dim(),
+        }
+    }
+
+    /// A word describing the health, for machine-readable output
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Valid => "valid",
+            Self::Expiring => "expiring",
+            Self::Expired => "expired",
+            Self::Invalid => "invalid",
+            Self::Unchecked => "unchecked",
+        }
+    }
+}
+
+/// Judge a user's client certificate by the earliest expiry in its bundle
+///
+/// now is a Unix timestamp. Users that don't authenticate with a client
+/// certificate are `Unchecked`.
+pub fn credential_health(user: Option<&UserData>, now: i64) -> CredentialHealth {
+    let Some(user) = user else {
+        return CredentialHealth::Invalid;
+    };
+    if user.auth_type() != "client-certificate" {
+        return CredentialHealth::Unchecked;
+    }
+
+    let pem = match read_pem(
+        user.client_certificate_data.as_ref(),
+        user.client_certificate.as_ref(),
+        "client-certificate",
+    ) {
+        Ok(Some(pem)) => pem,
+        Ok(None) => return CredentialHealth::Unchecked,
+        Err(_) => return CredentialHealth::Invalid,
+    };
+    let Some(not_after) = parse_certificates(&pem)
+        .ok()
+        .and_then(|certs| certs.iter().map(|c| c.not_after_timestamp).min())
+    else {
+        return CredentialHealth::Invalid;
+    };
+
+    let days_left = (not_after - now).div_euclid(SECONDS_PER_DAY);
+    if days_left < 0 {
+        CredentialHealth::Expired
+    } else if days_left < HEALTH_WARNING_DAYS {
+        CredentialHealth::Expiring
+    } else {
+        CredentialHealth::Valid
+    }
+}
+
+/// Subject and expiry of a single certificate
+pub struct CertExpiry {
+    subject: String,
+    not_after: String,
+    not_after_timestamp: i64,
+}
+
+/// Show the expiry of a context's client certificate and cluster CA
+///
+/// If context_name is provided, inspects that context.
+/// Otherwise, inspects the current context.
+pub fn show_cert_info(context_name: Option<String>) -> Result<()> {
+    let config = load_kube_config()?;
+
+    let context_name = context_name.unwrap_or_else(|| config.current_context.clone());
+    if context_name.is_empty() {
+        anyhow::bail!("No current context is set");
+    }
+
+    let context = config
+        .contexts
+        .iter()
+        .find(|c| c.name == context_name)
+        .ok_or_else(|| KhelpError::ContextNotFound(context_name.clone()))?;
+
+    let cluster = config
+        .clusters
+        .iter()
+        .find(|c| c.name == context.context.cluster)
+        .ok_or_else(|| KhelpError::ClusterNotFound {
+            cluster: context.context.cluster.clone(),
+            context: context_name.clone(),
+        })?;
+
+    let user = config
+        .users
+        .iter()
+        .find(|u| u.name == context.context.user)
+        .ok_or_else(|| KhelpError::UserNotFound {
+            user: context.context.user.clone(),
+            context: context_name.clone(),
+        })?;
+
+    status!(
+        "Certificates for context {}",
+        style(&context_name).green().bold()
+    );
+
+    let client_pem = read_pem(
+        user.user.client_certificate_data.as_ref(),
+        user.user.client_certificate.as_ref(),
+        "client-certificate",
+    )?;
+    let ca_pem = read_pem(
+        cluster.cluster.certificate_authority_data.as_ref(),
+        cluster.cluster.certificate_authority.as_ref(),
+        "certificate-authority",
+    )?;
+
+    if client_pem.is_none()
+        && let Some(auth_provider) = &user.user.auth_provider
+    {
+        println!(
+            "User {} authenticates with the {} auth provider:",
+            style(&user.name).cyan(),
+            style(&auth_provider.name).cyan()
+        );
+        for entry in auth_provider.display_config_redacted() {
+            println!("  {}", entry);
+        }
+    }
+
+    if client_pem.is_none() && ca_pem.is_none() {
+        status!("No client certificate or cluster CA is configured");
+        return Ok(());
+    }
+
+    let now = ASN1Time::now().timestamp();
+
+    if let Some(pem) = client_pem {
+        println!("Client certificate (user {}):", style(&user.name).cyan());
+        print_expiries(
+            &parse_certificates(&pem).context("Invalid client certificate")?,
+            now,
+        );
+    }
+
+    if let Some(pem) = ca_pem {
+        println!("Cluster CA (cluster {}):", style(&cluster.name).cyan());
+        print_expiries(
+            &parse_certificates(&pem).context("Invalid cluster CA")?,
+            now,
+        );
+    }
+
+    Ok(())
+}
+
+/// Print each certificate's expiry, warning about expired or soon-to-expire ones
+fn print_expiries(certs: &[CertExpiry], now: i64) {
+    for cert in certs {
+        let days_left = (cert.not_after_timestamp - now).div_euclid(SECONDS_PER_DAY);
+        let remaining = if days_left < 0 {
+            style(format!("expired {} day(s) ago", -days_left))
+                .red()
+                .bold()
+        } else if days_left < EXPIRY_WARNING_DAYS {
+            style(format!("expires in {} day(s)", days_left)).yellow()
+        } else {
+            style(format!("expires in {} day(s)", days_left)).green()
+        };
+
+        println!("  Subject: {}", cert.subject);
+        println!("  Not after: {} ({})", cert.not_after, remaining);
+
+        if days_left < 0 {
+            eprintln!(
+                "{} certificate '{}' has expired",
+                style("Warning:").yellow().bold(),
+                cert.subject
+            );
+        } else if days_left < EXPIRY_WARNING_DAYS {
+            eprintln!(
+                "{} certificate '{}' expires in {} day(s)",
+                style("Warning:").yellow().bold(),
+                cert.subject,
+                days_left
+            );
+        }
+    }
+}
+
+/// Parse every certificate in a PEM bundle
+pub fn parse_certificates(pem: &[u8]) -> Result<Vec<CertExpiry>> {
+    let mut certs = Vec::new();
+    for block in Pem::iter_from_buffer(pem) {
+        let block = block.context("Failed to read PEM data")?;
+        if block.label != "CERTIFICATE" {
+            continue;
+        }
+
+        let cert = block
+            .parse_x509()
+            .map_err(|e| anyhow::anyhow!("Failed to parse X.509 certificate: {}", e))?;
+        let not_after = cert.validity().not_after;
+        certs.push(CertExpiry {
+            subject: cert.subject().to_string(),
+            not_after: not_after.to_string(),
+            not_after_timestamp: not_after.timestamp(),
+        });
+    }
+
+    if certs.is_empty() {
+        anyhow::bail!("No certificates found in PEM data");
+    }
+
+    Ok(certs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TEST_CERT;
+
+    #[test]
+    fn test_parse_certificates() {
+        let bundle = format!("{}{}", TEST_CERT, TEST_CERT);
+        let certs = parse_certificates(bundle.as_bytes()).unwrap();
+
+        assert_eq!(certs.len(), 2);
+        assert_eq!(certs[0].subject, "CN=khelp-test");
+        // notAfter=Oct 11 18:42:19 2036 GMT
+        assert_eq!(certs[0].not_after_timestamp, 2107363339);
+
+        assert!(parse_certificates(b"not a certificate").is_err());
+    }
+
+    #[test]
+    fn test_credential_health() {
+        use base64::Engine;
+        use base64::engine::general_purpose::STANDARD;
+
+        let cert_user = UserData {
+            client_certificate_data: Some(STANDARD.encode(TEST_CERT)),
+            ..Default::default()
+        };
+        let not_after = 2107363339;
+
+        assert_eq!(
+            credential_health(Some(&cert_user), not_after - 100 * SECONDS_PER_DAY),
+            CredentialHealth::Valid
+        );
+        assert_eq!(
+            credential_health(Some(&cert_user), not_after - 3 * SECONDS_PER_DAY),
+            CredentialHealth::Expiring
+        );
+        assert_eq!(
+            credential_health(Some(&cert_user), not_after + 1),
+            CredentialHealth::Expired
+        );
+
+        let broken_user = UserData {
+            client_certificate_data: Some("not base64!".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            credential_health(Some(&broken_user), 0),
+            CredentialHealth::Invalid
+        );
+        assert_eq!(credential_health(None, 0), CredentialHealth::Invalid);
+
+        let token_user = UserData {
+            token: Some("secret".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            credential_health(Some(&token_user), 0),
+            CredentialHealth::Unchecked
+        );
+    }
+}
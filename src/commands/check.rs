@@ -0,0 +1,174 @@
+use anyhow::{Context, Result};
+use console::style;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::commands::api::{Credentials, build_agent, ensure_proxy_reachable, get};
+use crate::commands::output::status;
+use crate::config::kubernetes::ClusterData;
+use crate::config::operations::load_kube_config;
+use crate::error::KhelpError;
+
+/// How long to wait for the API server before giving up
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outcome of probing a cluster's API server
+pub enum Reachability {
+    /// The server answered `/version`, carrying its reported git version
+    Version(String),
+    /// The server answered but refused the request
+    Status(u16),
+}
+
+/// Check connectivity to the API server of a Kubernetes context
+///
+/// If context_name is provided, checks that context.
+/// Otherwise, checks the current context.
+/// insecure skips TLS verification and ca_file replaces the cluster's CA, for this
+/// check only; the kubeconfig isn't changed.
+/// The context's user is probed with its token (read from `tokenFile` if need be)
+/// or client certificate; other auth types are probed without credentials.
+pub fn check_connectivity(
+    context_name: Option<String>,
+    insecure: bool,
+    ca_file: Option<PathBuf>,
+) -> Result<()> {
+    let config = load_kube_config()?;
+
+    let context_name = context_name.unwrap_or_else(|| config.current_context.clone());
+    if context_name.is_empty() {
+        anyhow::bail!("No current context is set");
+    }
+
+    let context = config
+        .contexts
+        .iter()
+        .find(|c| c.name == context_name)
+        .ok_or_else(|| KhelpError::ContextNotFound(context_name.clone()))?;
+
+    let cluster = config
+        .clusters
+        .iter()
+        .find(|c| c.name == context.context.cluster)
+        .ok_or_else(|| KhelpError::ClusterNotFound {
+            cluster: context.context.cluster.clone(),
+            context: context_name.clone(),
+        })?;
+
+    status!(
+        "Checking connectivity for context {} ({})",
+        style(&context_name).green().bold(),
+        style(&cluster.cluster.server).cyan()
+    );
+
+    let mut cluster_data = cluster.cluster.clone();
+    if insecure {
+        cluster_data.insecure_skip_tls_verify = Some(true);
+    }
+    if let Some(ca_file) = ca_file {
+        status!("Using CA bundle {}", style(ca_file.display()).cyan());
+        cluster_data.certificate_authority = Some(ca_file.to_string_lossy().into_owned());
+        cluster_data.certificate_authority_data = None;
+        cluster_data.insecure_skip_tls_verify = None;
+    }
+    let verified = cluster_data.insecure_skip_tls_verify != Some(true);
+    if !verified {
+        eprintln!(
+            "{} TLS verification is skipped{}; a success doesn't show the server's certificate is valid",
+            style("Warning:").yellow().bold(),
+            if insecure {
+                " (--insecure)"
+            } else {
+                " (insecure-skip-tls-verify)"
+            }
+        );
+    }
+    // Appended to the result so an insecure success can't be mistaken for a secure one
+    let unverified_note = if verified {
+        String::new()
+    } else {
+        format!(" {}", style("(TLS not verified)").yellow())
+    };
+
+    if let Some(proxy_url) = &cluster.cluster.proxy_url {
+        status!("Using proxy {}", style(proxy_url).cyan());
+        ensure_proxy_reachable(proxy_url, PROBE_TIMEOUT)?;
+    }
+
+    let credentials = match config.users.iter().find(|u| u.name == context.context.user) {
+        Some(user) => match Credentials::for_user(&user.name, &user.user) {
+            Ok(credentials) => {
+                if user.user.token.is_none()
+                    && let Some(token_file) = &user.user.token_file
+                {
+                    status!("Using token from {}", style(token_file).cyan());
+                }
+                credentials
+            }
+            // A token file that can't be read is only reported, like an unsupported
+            // auth type, so the server's reachability is still checked
+            Err(e)
+                if matches!(
+                    e.downcast_ref::<KhelpError>(),
+                    Some(KhelpError::UnsupportedAuth { .. })
+                ) || (user.user.token.is_none() && user.user.token_file.is_some()) =>
+            {
+                eprintln!(
+                    "{} {:#}; checking without credentials",
+                    style("Warning:").yellow().bold(),
+                    e
+                );
+                Credentials::Anonymous
+            }
+            Err(e) => return Err(e),
+        },
+        None => Credentials::Anonymous,
+    };
+
+    match probe_server(&cluster_data, &credentials, PROBE_TIMEOUT)? {
+        Reachability::Version(version) => {
+            println!(
+                "{} Server is reachable, version: {}{}",
+                style("✓").green(),
+                style(version).green().bold(),
+                unverified_note
+            );
+        }
+        Reachability::Status(code) => {
+            println!(
+                "{} Server is reachable, but responded with HTTP {}{}",
+                style("✓").green(),
+                style(code).yellow(),
+                unverified_note
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Send a `GET /version` to a cluster's API server with the given credentials
+pub fn probe_server(
+    cluster: &ClusterData,
+    credentials: &Credentials,
+    timeout: Duration,
+) -> Result<Reachability> {
+    let agent = build_agent(cluster, credentials, timeout)?;
+    let mut response = get(&agent, cluster, "/version", credentials, timeout)?;
+
+    let status = response.status().as_u16();
+    if status != 200 {
+        return Ok(Reachability::Status(status));
+    }
+
+    let body = response
+        .body_mut()
+        .read_to_string()
+        .context("Failed to read response from API server")?;
+    let version = serde_json::from_str::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|v| v.get("gitVersion")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| "unknown".to_string());
+
+    Ok(Reachability::Version(version))
+}
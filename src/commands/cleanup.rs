@@ -1,13 +1,17 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
 use console::style;
-use dialoguer::{Confirm, theme::ColorfulTheme};
 use log::debug;
-use std::collections::HashSet;
 
+use crate::commands::output::status;
+use crate::config::analysis::{find_broken_contexts, find_orphans};
 use crate::config::operations::{load_kube_config, save_kube_config};
+use crate::config::usage::{remove_usage_in, update_usage};
+use crate::utils::confirm;
 
 /// Clean up orphaned clusters and users not referenced by any context
-pub fn cleanup_orphans(force: bool) -> Result<()> {
+///
+/// If prune_broken is true, also removes contexts whose cluster or user doesn't exist.
+pub fn cleanup_orphans(force: bool, prune_broken: bool) -> Result<()> {
     let mut config = load_kube_config()?;
     debug!(
         "Loaded kube config with {} clusters, {} users, {} contexts",
@@ -16,74 +20,83 @@ pub fn cleanup_orphans(force: bool) -> Result<()> {
         config.contexts.len()
     );
 
-    // Find all referenced clusters and users
-    let referenced_clusters: HashSet<String> = config
-        .contexts
-        .iter()
-        .map(|c| c.context.cluster.clone())
-        .collect();
+    // Find contexts that can't be used because their cluster or user is missing
+    let broken_contexts: Vec<String> = if prune_broken {
+        find_broken_contexts(&config)
+    } else {
+        Vec::new()
+    };
 
-    let referenced_users: HashSet<String> = config
+    // Clusters and users are orphaned if none of the contexts that will remain use them
+    let mut remaining = config.clone();
+    remaining
         .contexts
-        .iter()
-        .map(|c| c.context.user.clone())
-        .collect();
-
-    // Find orphaned clusters
-    let orphaned_clusters: Vec<String> = config
-        .clusters
-        .iter()
-        .filter(|c| !referenced_clusters.contains(&c.name))
-        .map(|c| c.name.clone())
-        .collect();
-
-    // Find orphaned users
-    let orphaned_users: Vec<String> = config
-        .users
-        .iter()
-        .filter(|u| !referenced_users.contains(&u.name))
-        .map(|u| u.name.clone())
-        .collect();
+        .retain(|c| !broken_contexts.contains(&c.name));
+    let (orphaned_clusters, orphaned_users) = find_orphans(&remaining);
 
-    if orphaned_clusters.is_empty() && orphaned_users.is_empty() {
-        eprintln!("No orphaned clusters or users found");
+    if broken_contexts.is_empty() && orphaned_clusters.is_empty() && orphaned_users.is_empty() {
+        if prune_broken {
+            status!("No broken contexts or orphaned clusters or users found");
+        } else {
+            status!("No orphaned clusters or users found");
+        }
         return Ok(());
     }
 
     // Display what will be cleaned up
-    eprintln!("Found orphaned resources:");
+    if !broken_contexts.is_empty() {
+        status!("Found broken contexts (missing cluster or user):");
+        for context in &broken_contexts {
+            status!("  - {}", style(context).yellow());
+        }
+        status!();
+    }
+    if !orphaned_clusters.is_empty() || !orphaned_users.is_empty() {
+        status!("Found orphaned resources:");
+    }
     if !orphaned_clusters.is_empty() {
-        eprintln!("\nClusters:");
+        status!("\nClusters:");
         for cluster in &orphaned_clusters {
-            eprintln!("  - {}", style(cluster).cyan());
+            status!("  - {}", style(cluster).cyan());
         }
     }
     if !orphaned_users.is_empty() {
-        eprintln!("\nUsers:");
+        status!("\nUsers:");
         for user in &orphaned_users {
-            eprintln!("  - {}", style(user).cyan());
+            status!("  - {}", style(user).cyan());
         }
     }
-    eprintln!();
+    status!();
 
     // Confirmation prompt
-    if !force {
-        let confirmed = Confirm::with_theme(&ColorfulTheme::default())
-            .with_prompt("Delete these orphaned resources?")
-            .default(false)
-            .interact()
-            .context("Failed to get confirmation")?;
+    let prompt = if broken_contexts.is_empty() {
+        "Delete these orphaned resources?"
+    } else {
+        "Delete these broken contexts and orphaned resources?"
+    };
+    if !confirm(prompt, false, force)? {
+        eprintln!("Cleanup cancelled");
+        return Ok(());
+    }
 
-        if !confirmed {
-            eprintln!("Cleanup cancelled");
-            return Ok(());
-        }
+    // Remove broken contexts
+    for context in &broken_contexts {
+        config.contexts.retain(|c| &c.name != context);
+        status!(
+            "{} Deleted broken context: {}",
+            style("✓").green(),
+            style(context).yellow()
+        );
+    }
+    if broken_contexts.contains(&config.current_context) {
+        debug!("Current context was broken, clearing current-context");
+        config.current_context = String::new();
     }
 
     // Remove orphaned clusters
     for cluster in &orphaned_clusters {
         config.clusters.retain(|c| &c.name != cluster);
-        eprintln!(
+        status!(
             "{} Deleted orphaned cluster: {}",
             style("✓").green(),
             style(cluster).cyan()
@@ -93,7 +106,7 @@ pub fn cleanup_orphans(force: bool) -> Result<()> {
     // Remove orphaned users
     for user in &orphaned_users {
         config.users.retain(|u| &u.name != user);
-        eprintln!(
+        status!(
             "{} Deleted orphaned user: {}",
             style("✓").green(),
             style(user).cyan()
@@ -103,11 +116,24 @@ pub fn cleanup_orphans(force: bool) -> Result<()> {
     // Save the config
     save_kube_config(&config)?;
 
-    eprintln!(
-        "Cleaned up {} cluster(s) and {} user(s)",
-        orphaned_clusters.len(),
-        orphaned_users.len()
-    );
+    if !broken_contexts.is_empty() {
+        update_usage(|path| remove_usage_in(path, &broken_contexts));
+    }
+
+    if prune_broken {
+        status!(
+            "Cleaned up {} context(s), {} cluster(s) and {} user(s)",
+            broken_contexts.len(),
+            orphaned_clusters.len(),
+            orphaned_users.len()
+        );
+    } else {
+        status!(
+            "Cleaned up {} cluster(s) and {} user(s)",
+            orphaned_clusters.len(),
+            orphaned_users.len()
+        );
+    }
 
     Ok(())
 }
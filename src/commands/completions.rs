@@ -5,287 +5,551 @@ use log::debug;
 use std::env;
 use std::fs;
 use std::io::{self, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use crate::commands::output::status;
+use crate::config::operations::home_directory;
+use crate::error::KhelpError;
 
 /// All command names and aliases for use in completion guards
-const ALL_COMMANDS: &str =
-    "list ls current switch use s edit export delete rm rename mv add completions";
+const ALL_COMMANDS: &str = "list ls current switch use s edit export delete rm rename mv add check ping merge namespace ns config-path alias cert-info reset-current resolve prompt completions";
 
 /// Commands (and aliases) that accept context names as arguments
-const CONTEXT_COMMANDS: &str = "switch|use|s|edit|export|delete|rm|rename|mv";
+const CONTEXT_COMMANDS: &str = "current|switch|use|s|edit|export|delete|rm|rename|mv|reset-current";
+
+/// Shell pipeline listing the namespaces of the current context's cluster, one per line
+const LIST_NAMESPACES: &str = "kubectl get namespaces -o name 2>/dev/null | sed 's|^namespace/||'";
 
 /// Generate shell completions
 ///
-/// This function uses the clap_complete crate to generate shell completions for
-/// the specified shell.
-pub fn generate_completions(shell: Shell, install: bool) -> Result<()> {
+/// Prints the completion script for the specified shell, writes it to output when
+/// given, or installs it along with a line in the shell's startup file.
+pub fn generate_completions(shell: Shell, install: bool, output: Option<&Path>) -> Result<()> {
     debug!(
         "Running completions command with shell: {:?}, install: {}",
         shell, install
     );
 
     if install {
-        install_completions(shell)?;
-    } else {
-        // Generate a custom completion script based on the shell type
-        // This completely avoids using clap_complete for stdout output
-        match shell {
-            Shell::Bash => {
-                // Simple bash completions
-                println!("# Bash completions for khelp");
-                println!("_khelp_completions() {{");
-                println!("  local cur prev");
-                println!("  COMPREPLY=()");
-                println!("  cur=\"${{COMP_WORDS[COMP_CWORD]}}\"");
-                println!("  prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"");
-                println!();
-                println!("  if [ \"$COMP_CWORD\" -eq 1 ]; then");
-                println!("    COMPREPLY=( $(compgen -W \"{ALL_COMMANDS}\" -- \"$cur\") )");
-                println!("    return 0");
-                println!("  fi");
-                println!();
-                println!("  if [ \"$COMP_CWORD\" -ge 2 ]; then");
-                println!("    case \"$prev\" in");
-                println!("      {CONTEXT_COMMANDS})");
-                println!(
-                    "        COMPREPLY=( $(compgen -W \"$(kubectl config get-contexts -o name 2>/dev/null)\" -- \"$cur\") )"
-                );
-                println!("        ;;");
-                println!("      completions)");
-                println!(
-                    "        COMPREPLY=( $(compgen -W \"bash zsh fish powershell elvish\" -- \"$cur\") )"
-                );
-                println!("        ;;");
-                println!("    esac");
-                println!("  fi");
-                println!("}}");
-                println!();
-                println!("complete -F _khelp_completions khelp");
-            }
-            Shell::Zsh => {
-                // Simple zsh completions
-                println!("#compdef khelp");
-                println!();
-                println!("_khelp() {{");
-                println!("  local -a commands");
-                println!("  commands=(");
-                println!("    'list:List all available contexts'");
-                println!("    'ls:List all available contexts'");
-                println!("    'current:Get the current context'");
-                println!("    'switch:Switch to a different context'");
-                println!("    'use:Switch to a different context'");
-                println!("    's:Switch to a different context'");
-                println!("    'edit:Edit a specific context'");
-                println!("    'export:Export a specific context to stdout'");
-                println!("    'delete:Delete a specific context'");
-                println!("    'rm:Delete a specific context'");
-                println!("    'rename:Rename a context'");
-                println!("    'mv:Rename a context'");
-                println!("    'add:Add contexts from an external kubeconfig file'");
-                println!("    'completions:Generate shell completions'");
-                println!("  )");
-                println!();
-                println!("  _arguments -C \\");
-                println!("    '1: :->command' \\");
-                println!("    '2: :->argument' \\");
-                println!("    '*::arg:->args'");
-                println!();
-                println!("  case $state in");
-                println!("    (command)");
-                println!("      _describe -t commands 'khelp commands' commands");
-                println!("      ;;");
-                println!("    (argument)");
-                println!("      case $line[1] in");
-                println!("        ({CONTEXT_COMMANDS})");
-                println!("          local -a contexts");
-                println!(
-                    "          contexts=(${{{{(f)\"$(kubectl config get-contexts -o name 2>/dev/null)\"}}}}"
-                );
-                println!("          _describe 'contexts' contexts");
-                println!("          ;;");
-                println!("        (completions)");
-                println!("          local -a shells");
-                println!("          shells=('bash' 'zsh' 'fish' 'powershell' 'elvish')");
-                println!("          _describe 'shells' shells");
-                println!("          ;;");
-                println!("      esac");
-                println!("      ;;");
-                println!("  esac");
-                println!("}}");
-                println!();
-                println!("_khelp");
-            }
-            Shell::Fish => {
-                let all_cmds = ALL_COMMANDS;
-                // Simple fish completions
-                println!("# Fish completions for khelp");
-                println!();
-                println!("function __khelp_get_contexts");
-                println!("    kubectl config get-contexts -o name 2>/dev/null");
-                println!("end");
-                println!();
-                println!("# Main commands and aliases");
-                for (cmd, desc) in [
-                    ("list", "List all available contexts"),
-                    ("ls", "List all available contexts"),
-                    ("current", "Get the current context"),
-                    ("switch", "Switch to a different context"),
-                    ("use", "Switch to a different context"),
-                    ("s", "Switch to a different context"),
-                    ("edit", "Edit a specific context"),
-                    ("export", "Export a specific context to stdout"),
-                    ("delete", "Delete a specific context"),
-                    ("rm", "Delete a specific context"),
-                    ("rename", "Rename a context"),
-                    ("mv", "Rename a context"),
-                    ("add", "Add contexts from an external kubeconfig file"),
-                    ("completions", "Generate shell completions"),
-                ] {
-                    println!(
-                        "complete -c khelp -f -n \"not __fish_seen_subcommand_from {all_cmds}\" -a {cmd} -d \"{desc}\""
-                    );
-                }
-                println!();
-                println!("# File path completion for add command");
-                println!(
-                    "complete -c khelp -F -n \"__fish_seen_subcommand_from add\" -d \"Kubeconfig file\""
-                );
-                println!();
-                println!("# Context name completions");
-                for cmd in [
-                    "switch", "use", "s", "edit", "export", "delete", "rm", "rename", "mv",
-                ] {
-                    println!(
-                        "complete -c khelp -f -n \"__fish_seen_subcommand_from {cmd}\" -a \"(__khelp_get_contexts)\" -d \"Kubernetes context\""
-                    );
-                }
-                println!();
-                println!("# Shell completions");
-                println!(
-                    "complete -c khelp -f -n \"__fish_seen_subcommand_from completions\" -a \"bash zsh fish powershell elvish\" -d \"Shell\""
-                );
-            }
-            Shell::PowerShell => {
-                // PowerShell completions
-                println!("# PowerShell completions for khelp");
-                println!("# Add this to your PowerShell profile ($PROFILE)");
-                println!();
-                println!("Register-ArgumentCompleter -Native -CommandName khelp -ScriptBlock {{");
-                println!("    param($wordToComplete, $commandAst, $cursorPosition)");
-                println!();
-                println!("    $commands = @(");
-                println!(
-                    "        @{{ Name = 'list'; Description = 'List all available contexts' }}"
-                );
-                println!("        @{{ Name = 'ls'; Description = 'List all available contexts' }}");
-                println!(
-                    "        @{{ Name = 'current'; Description = 'Get the current context' }}"
-                );
-                println!(
-                    "        @{{ Name = 'switch'; Description = 'Switch to a different context' }}"
-                );
-                println!(
-                    "        @{{ Name = 'use'; Description = 'Switch to a different context' }}"
-                );
-                println!(
-                    "        @{{ Name = 's'; Description = 'Switch to a different context' }}"
-                );
-                println!("        @{{ Name = 'edit'; Description = 'Edit a specific context' }}");
-                println!(
-                    "        @{{ Name = 'export'; Description = 'Export a specific context to stdout' }}"
-                );
-                println!(
-                    "        @{{ Name = 'delete'; Description = 'Delete a specific context' }}"
-                );
-                println!("        @{{ Name = 'rm'; Description = 'Delete a specific context' }}");
-                println!("        @{{ Name = 'rename'; Description = 'Rename a context' }}");
-                println!("        @{{ Name = 'mv'; Description = 'Rename a context' }}");
-                println!(
-                    "        @{{ Name = 'add'; Description = 'Add contexts from an external kubeconfig file' }}"
-                );
-                println!(
-                    "        @{{ Name = 'completions'; Description = 'Generate shell completions' }}"
-                );
-                println!(
-                    "        @{{ Name = 'update'; Description = 'Check for updates to khelp' }}"
-                );
-                println!("    )");
-                println!();
-                println!("    $elements = $commandAst.CommandElements");
-                println!("    $command = $elements[1].Value");
-                println!();
-                println!("    # Complete subcommands");
-                println!(
-                    "    if ($elements.Count -eq 1 -or ($elements.Count -eq 2 -and $wordToComplete)) {{"
-                );
-                println!(
-                    "        $commands | Where-Object {{ $_.Name -like \"$wordToComplete*\" }} | ForEach-Object {{"
-                );
-                println!(
-                    "            [System.Management.Automation.CompletionResult]::new($_.Name, $_.Name, 'ParameterValue', $_.Description)"
-                );
-                println!("        }}");
-                println!("        return");
-                println!("    }}");
-                println!();
-                println!("    # Complete context names for relevant commands");
-                println!(
-                    "    if ($command -in @('switch', 'use', 's', 'edit', 'export', 'delete', 'rm', 'rename', 'mv')) {{"
-                );
-                println!("        $contexts = kubectl config get-contexts -o name 2>$null");
-                println!("        if ($contexts) {{");
-                println!(
-                    "            $contexts | Where-Object {{ $_ -like \"$wordToComplete*\" }} | ForEach-Object {{"
-                );
-                println!(
-                    "                [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', \"Kubernetes context\")"
-                );
-                println!("            }}");
-                println!("        }}");
-                println!("        return");
-                println!("    }}");
-                println!();
-                println!("    # Complete shells for completions command");
-                println!("    if ($command -eq 'completions') {{");
-                println!(
-                    "        @('bash', 'zsh', 'fish', 'powershell', 'elvish') | Where-Object {{ $_ -like \"$wordToComplete*\" }} | ForEach-Object {{"
-                );
-                println!(
-                    "            [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', \"Shell\")"
-                );
-                println!("        }}");
-                println!("    }}");
-                println!("}}");
+        return install_completions(shell);
+    }
+
+    let Some(path) = output else {
+        let mut stdout = io::stdout().lock();
+        write_completion_script(shell, &mut stdout)
+            .and_then(|()| stdout.flush())
+            .context("Failed to write completions to stdout")?;
+        return Ok(());
+    };
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    let mut script = Vec::new();
+    write_completion_script(shell, &mut script)?;
+    fs::write(path, script)
+        .with_context(|| format!("Failed to write completions to {}", path.display()))?;
+
+    status!(
+        "{} Wrote {:?} completions to {}",
+        style("✓").green(),
+        shell,
+        style(path.display()).cyan()
+    );
+    Ok(())
+}
+
+/// Write the completion script for a shell
+///
+/// The scripts are written by hand rather than with clap_complete so they can
+/// complete context and namespace names.
+fn write_completion_script(shell: Shell, out: &mut impl Write) -> io::Result<()> {
+    match shell {
+        Shell::Bash => {
+            // Simple bash completions
+            writeln!(out, "# Bash completions for khelp")?;
+            writeln!(out, "_khelp_completions() {{")?;
+            writeln!(out, "  local cur prev")?;
+            writeln!(out, "  COMPREPLY=()")?;
+            writeln!(out, "  cur=\"${{COMP_WORDS[COMP_CWORD]}}\"")?;
+            writeln!(out, "  prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"")?;
+            writeln!(out)?;
+            writeln!(out, "  if [ \"$COMP_CWORD\" -eq 1 ]; then")?;
+            writeln!(
+                out,
+                "    COMPREPLY=( $(compgen -W \"{ALL_COMMANDS}\" -- \"$cur\") )"
+            )?;
+            writeln!(out, "    return 0")?;
+            writeln!(out, "  fi")?;
+            writeln!(out)?;
+            writeln!(
+                out,
+                "  # namespace takes an optional context, then a namespace"
+            )?;
+            writeln!(out, "  case \"${{COMP_WORDS[1]}}\" in")?;
+            writeln!(out, "    namespace|ns)")?;
+            writeln!(out, "      local words")?;
+            writeln!(out, "      words=\"$({LIST_NAMESPACES})\"")?;
+            writeln!(out, "      if [ \"$COMP_CWORD\" -eq 2 ]; then")?;
+            writeln!(
+                out,
+                "        words=\"$words $(kubectl config get-contexts -o name 2>/dev/null)\""
+            )?;
+            writeln!(out, "      fi")?;
+            writeln!(
+                out,
+                "      COMPREPLY=( $(compgen -W \"$words\" -- \"$cur\") )"
+            )?;
+            writeln!(out, "      return 0")?;
+            writeln!(out, "      ;;")?;
+            writeln!(out, "  esac")?;
+            writeln!(out)?;
+            writeln!(out, "  if [ \"$COMP_CWORD\" -ge 2 ]; then")?;
+            writeln!(out, "    case \"$prev\" in")?;
+            writeln!(out, "      {CONTEXT_COMMANDS})")?;
+            writeln!(
+                out,
+                "        COMPREPLY=( $(compgen -W \"$(kubectl config get-contexts -o name 2>/dev/null)\" -- \"$cur\") )"
+            )?;
+            writeln!(out, "        ;;")?;
+            writeln!(out, "      completions)")?;
+            writeln!(
+                out,
+                "        COMPREPLY=( $(compgen -W \"auto bash zsh fish powershell elvish\" -- \"$cur\") )"
+            )?;
+            writeln!(out, "        ;;")?;
+            writeln!(out, "    esac")?;
+            writeln!(out, "  fi")?;
+            writeln!(out, "}}")?;
+            writeln!(out)?;
+            writeln!(out, "complete -F _khelp_completions khelp")?;
+        }
+        Shell::Zsh => {
+            // Simple zsh completions
+            writeln!(out, "#compdef khelp")?;
+            writeln!(out)?;
+            writeln!(out, "_khelp() {{")?;
+            writeln!(out, "  local -a commands")?;
+            writeln!(out, "  commands=(")?;
+            writeln!(out, "    'list:List all available contexts'")?;
+            writeln!(out, "    'ls:List all available contexts'")?;
+            writeln!(out, "    'current:Get the current context'")?;
+            writeln!(out, "    'switch:Switch to a different context'")?;
+            writeln!(out, "    'use:Switch to a different context'")?;
+            writeln!(out, "    's:Switch to a different context'")?;
+            writeln!(out, "    'edit:Edit a specific context'")?;
+            writeln!(out, "    'export:Export a specific context to stdout'")?;
+            writeln!(out, "    'delete:Delete one or more contexts'")?;
+            writeln!(out, "    'rm:Delete one or more contexts'")?;
+            writeln!(out, "    'rename:Rename a context'")?;
+            writeln!(out, "    'mv:Rename a context'")?;
+            writeln!(
+                out,
+                "    'add:Add contexts from an external kubeconfig file'"
+            )?;
+            writeln!(out, "    'cert-info:Show when certificates expire'")?;
+            writeln!(
+                out,
+                "    'reset-current:Pick a current context when it is unset or missing'"
+            )?;
+            writeln!(
+                out,
+                "    'resolve:Print the current context as kubectl resolves it'"
+            )?;
+            writeln!(
+                out,
+                "    'prompt:Print the current context for a shell prompt'"
+            )?;
+            writeln!(out, "    'alias:Manage short aliases for context names'")?;
+            writeln!(
+                out,
+                "    'config-path:Print the path of the kubeconfig file khelp operates on'"
+            )?;
+            writeln!(
+                out,
+                "    'namespace:Set the default namespace of one or more contexts'"
+            )?;
+            writeln!(
+                out,
+                "    'ns:Set the default namespace of one or more contexts'"
+            )?;
+            writeln!(out, "    'merge:Create a context from existing contexts'")?;
+            writeln!(out, "    'check:Check connectivity to a cluster'")?;
+            writeln!(out, "    'ping:Check connectivity to a cluster'")?;
+            writeln!(out, "    'completions:Generate shell completions'")?;
+            writeln!(out, "  )")?;
+            writeln!(out)?;
+            writeln!(out, "  _arguments -C \\")?;
+            writeln!(out, "    '1: :->command' \\")?;
+            writeln!(out, "    '2: :->argument' \\")?;
+            writeln!(out, "    '*::arg:->args'")?;
+            writeln!(out)?;
+            writeln!(out, "  case $state in")?;
+            writeln!(out, "    (command)")?;
+            writeln!(out, "      _describe -t commands 'khelp commands' commands")?;
+            writeln!(out, "      ;;")?;
+            writeln!(out, "    (argument)")?;
+            writeln!(out, "      case $line[1] in")?;
+            writeln!(out, "        ({CONTEXT_COMMANDS})")?;
+            writeln!(out, "          local -a contexts")?;
+            writeln!(
+                out,
+                "          contexts=(${{(f)\"$(kubectl config get-contexts -o name 2>/dev/null)\"}})"
+            )?;
+            writeln!(out, "          _describe 'contexts' contexts")?;
+            writeln!(out, "          ;;")?;
+            writeln!(out, "        (namespace|ns)")?;
+            writeln!(out, "          local -a namespaces contexts")?;
+            writeln!(
+                out,
+                "          namespaces=(${{(f)\"$({LIST_NAMESPACES})\"}})"
+            )?;
+            writeln!(
+                out,
+                "          contexts=(${{(f)\"$(kubectl config get-contexts -o name 2>/dev/null)\"}})"
+            )?;
+            writeln!(out, "          _describe 'namespaces' namespaces")?;
+            writeln!(out, "          _describe 'contexts' contexts")?;
+            writeln!(out, "          ;;")?;
+            writeln!(out, "        (completions)")?;
+            writeln!(out, "          local -a shells")?;
+            writeln!(
+                out,
+                "          shells=('auto' 'bash' 'zsh' 'fish' 'powershell' 'elvish')"
+            )?;
+            writeln!(out, "          _describe 'shells' shells")?;
+            writeln!(out, "          ;;")?;
+            writeln!(out, "      esac")?;
+            writeln!(out, "      ;;")?;
+            writeln!(out, "    (args)")?;
+            writeln!(out, "      case $line[1] in")?;
+            writeln!(out, "        (namespace|ns)")?;
+            writeln!(out, "          local -a namespaces")?;
+            writeln!(
+                out,
+                "          namespaces=(${{(f)\"$({LIST_NAMESPACES})\"}})"
+            )?;
+            writeln!(out, "          _describe 'namespaces' namespaces")?;
+            writeln!(out, "          ;;")?;
+            writeln!(out, "      esac")?;
+            writeln!(out, "      ;;")?;
+            writeln!(out, "  esac")?;
+            writeln!(out, "}}")?;
+            writeln!(out)?;
+            writeln!(out, "_khelp")?;
+        }
+        Shell::Fish => {
+            let all_cmds = ALL_COMMANDS;
+            // Simple fish completions
+            writeln!(out, "# Fish completions for khelp")?;
+            writeln!(out)?;
+            writeln!(out, "function __khelp_get_contexts")?;
+            writeln!(out, "    kubectl config get-contexts -o name 2>/dev/null")?;
+            writeln!(out, "end")?;
+            writeln!(out)?;
+            writeln!(out, "function __khelp_get_namespaces")?;
+            writeln!(out, "    {LIST_NAMESPACES}")?;
+            writeln!(out, "end")?;
+            writeln!(out)?;
+            writeln!(out, "# Main commands and aliases")?;
+            for (cmd, desc) in [
+                ("list", "List all available contexts"),
+                ("ls", "List all available contexts"),
+                ("current", "Get the current context"),
+                ("switch", "Switch to a different context"),
+                ("use", "Switch to a different context"),
+                ("s", "Switch to a different context"),
+                ("edit", "Edit a specific context"),
+                ("export", "Export a specific context to stdout"),
+                ("delete", "Delete one or more contexts"),
+                ("rm", "Delete one or more contexts"),
+                ("rename", "Rename a context"),
+                ("mv", "Rename a context"),
+                ("add", "Add contexts from an external kubeconfig file"),
+                ("cert-info", "Show when certificates expire"),
+                (
+                    "reset-current",
+                    "Pick a current context when it is unset or missing",
+                ),
+                (
+                    "resolve",
+                    "Print the current context as kubectl resolves it",
+                ),
+                ("prompt", "Print the current context for a shell prompt"),
+                ("alias", "Manage short aliases for context names"),
+                (
+                    "config-path",
+                    "Print the path of the kubeconfig file khelp operates on",
+                ),
+                (
+                    "namespace",
+                    "Set the default namespace of one or more contexts",
+                ),
+                ("ns", "Set the default namespace of one or more contexts"),
+                ("merge", "Create a context from existing contexts"),
+                ("check", "Check connectivity to a cluster"),
+                ("ping", "Check connectivity to a cluster"),
+                ("completions", "Generate shell completions"),
+            ] {
+                writeln!(
+                    out,
+                    "complete -c khelp -f -n \"not __fish_seen_subcommand_from {all_cmds}\" -a {cmd} -d \"{desc}\""
+                )?;
             }
-            _ => {
-                println!("# Completions not supported for this shell");
-                println!("# Supported shells: bash, zsh, fish, powershell");
+            writeln!(out)?;
+            writeln!(out, "# File path completion for add command")?;
+            writeln!(
+                out,
+                "complete -c khelp -F -n \"__fish_seen_subcommand_from add\" -d \"Kubeconfig file\""
+            )?;
+            writeln!(out)?;
+            writeln!(out, "# Context name completions")?;
+            for cmd in [
+                "current",
+                "switch",
+                "use",
+                "s",
+                "edit",
+                "export",
+                "delete",
+                "rm",
+                "rename",
+                "mv",
+                "reset-current",
+            ] {
+                writeln!(
+                    out,
+                    "complete -c khelp -f -n \"__fish_seen_subcommand_from {cmd}\" -a \"(__khelp_get_contexts)\" -d \"Kubernetes context\""
+                )?;
             }
+            writeln!(out)?;
+            writeln!(
+                out,
+                "# Namespace completions (the optional context comes first)"
+            )?;
+            writeln!(
+                out,
+                "complete -c khelp -f -n \"__fish_seen_subcommand_from namespace ns\" -a \"(__khelp_get_namespaces)\" -d \"Namespace\""
+            )?;
+            writeln!(
+                out,
+                "complete -c khelp -f -n \"__fish_seen_subcommand_from namespace ns; and test (count (commandline -opc)) -eq 2\" -a \"(__khelp_get_contexts)\" -d \"Kubernetes context\""
+            )?;
+            writeln!(out)?;
+            writeln!(out, "# Shell completions")?;
+            writeln!(
+                out,
+                "complete -c khelp -f -n \"__fish_seen_subcommand_from completions\" -a \"auto bash zsh fish powershell elvish\" -d \"Shell\""
+            )?;
+        }
+        Shell::PowerShell => {
+            // PowerShell completions
+            writeln!(out, "# PowerShell completions for khelp")?;
+            writeln!(out, "# Add this to your PowerShell profile ($PROFILE)")?;
+            writeln!(out)?;
+            writeln!(
+                out,
+                "Register-ArgumentCompleter -Native -CommandName khelp -ScriptBlock {{"
+            )?;
+            writeln!(
+                out,
+                "    param($wordToComplete, $commandAst, $cursorPosition)"
+            )?;
+            writeln!(out)?;
+            writeln!(out, "    $commands = @(")?;
+            writeln!(
+                out,
+                "        @{{ Name = 'list'; Description = 'List all available contexts' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 'ls'; Description = 'List all available contexts' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 'current'; Description = 'Get the current context' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 'switch'; Description = 'Switch to a different context' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 'use'; Description = 'Switch to a different context' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 's'; Description = 'Switch to a different context' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 'edit'; Description = 'Edit a specific context' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 'export'; Description = 'Export a specific context to stdout' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 'delete'; Description = 'Delete one or more contexts' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 'rm'; Description = 'Delete one or more contexts' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 'rename'; Description = 'Rename a context' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 'mv'; Description = 'Rename a context' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 'add'; Description = 'Add contexts from an external kubeconfig file' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 'cert-info'; Description = 'Show when certificates expire' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 'reset-current'; Description = 'Pick a current context when it is unset or missing' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 'resolve'; Description = 'Print the current context as kubectl resolves it' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 'prompt'; Description = 'Print the current context for a shell prompt' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 'alias'; Description = 'Manage short aliases for context names' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 'config-path'; Description = 'Print the path of the kubeconfig file khelp operates on' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 'namespace'; Description = 'Set the default namespace of one or more contexts' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 'ns'; Description = 'Set the default namespace of one or more contexts' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 'merge'; Description = 'Create a context from existing contexts' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 'check'; Description = 'Check connectivity to a cluster' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 'ping'; Description = 'Check connectivity to a cluster' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 'completions'; Description = 'Generate shell completions' }}"
+            )?;
+            writeln!(
+                out,
+                "        @{{ Name = 'update'; Description = 'Check for updates to khelp' }}"
+            )?;
+            writeln!(out, "    )")?;
+            writeln!(out)?;
+            writeln!(out, "    $elements = $commandAst.CommandElements")?;
+            writeln!(out, "    $command = $elements[1].Value")?;
+            writeln!(out)?;
+            writeln!(out, "    # Complete subcommands")?;
+            writeln!(
+                out,
+                "    if ($elements.Count -eq 1 -or ($elements.Count -eq 2 -and $wordToComplete)) {{"
+            )?;
+            writeln!(
+                out,
+                "        $commands | Where-Object {{ $_.Name -like \"$wordToComplete*\" }} | ForEach-Object {{"
+            )?;
+            writeln!(
+                out,
+                "            [System.Management.Automation.CompletionResult]::new($_.Name, $_.Name, 'ParameterValue', $_.Description)"
+            )?;
+            writeln!(out, "        }}")?;
+            writeln!(out, "        return")?;
+            writeln!(out, "    }}")?;
+            writeln!(out)?;
+            writeln!(out, "    # Complete context names for relevant commands")?;
+            writeln!(
+                out,
+                "    if ($command -in @('current', 'switch', 'use', 's', 'edit', 'export', 'delete', 'rm', 'rename', 'mv', 'reset-current')) {{"
+            )?;
+            writeln!(
+                out,
+                "        $contexts = kubectl config get-contexts -o name 2>$null"
+            )?;
+            writeln!(out, "        if ($contexts) {{")?;
+            writeln!(
+                out,
+                "            $contexts | Where-Object {{ $_ -like \"$wordToComplete*\" }} | ForEach-Object {{"
+            )?;
+            writeln!(
+                out,
+                "                [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', \"Kubernetes context\")"
+            )?;
+            writeln!(out, "            }}")?;
+            writeln!(out, "        }}")?;
+            writeln!(out, "        return")?;
+            writeln!(out, "    }}")?;
+            writeln!(out)?;
+            writeln!(out, "    # Complete shells for completions command")?;
+            writeln!(out, "    if ($command -eq 'completions') {{")?;
+            writeln!(
+                out,
+                "        @('auto', 'bash', 'zsh', 'fish', 'powershell', 'elvish') | Where-Object {{ $_ -like \"$wordToComplete*\" }} | ForEach-Object {{"
+            )?;
+            writeln!(
+                out,
+                "            [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', \"Shell\")"
+            )?;
+            writeln!(out, "        }}")?;
+            writeln!(out, "    }}")?;
+            writeln!(out, "}}")?;
+        }
+        _ => {
+            writeln!(out, "# Completions not supported for this shell")?;
+            writeln!(out, "# Supported shells: bash, zsh, fish, powershell")?;
         }
     }
 
-    // Ensure output is flushed
-    io::stdout().flush().context("Failed to flush stdout")?;
-
     Ok(())
 }
 
 /// Detect the current shell
 ///
-/// On Unix systems, this checks the $SHELL environment variable.
+/// On Unix systems, this first inspects the parent process to find the shell that
+/// actually invoked khelp, then falls back to the $SHELL environment variable.
 /// On Windows, this checks for PowerShell via PSModulePath environment variable.
 pub fn detect_shell() -> Result<Shell> {
-    // Try $SHELL first (Unix systems and some Windows terminals like Git Bash)
+    // Prefer the invoking shell, since $SHELL only reflects the login shell
+    if let Some(shell) = parent_process_name().as_deref().and_then(shell_from_name) {
+        debug!("Detected shell from parent process: {:?}", shell);
+        return Ok(shell);
+    }
+
+    // Try $SHELL next (Unix systems and some Windows terminals like Git Bash)
     if let Ok(shell_path) = env::var("SHELL") {
         let path = PathBuf::from(&shell_path);
         if let Some(shell_name) = path.file_name().and_then(|s| s.to_str()) {
             debug!("Detected shell from $SHELL: {}", shell_name);
-            return match shell_name {
-                "bash" => Ok(Shell::Bash),
-                "zsh" => Ok(Shell::Zsh),
-                "fish" => Ok(Shell::Fish),
-                "pwsh" | "powershell" => Ok(Shell::PowerShell),
-                _ => anyhow::bail!(
+            return match shell_from_name(shell_name) {
+                Some(shell) => Ok(shell),
+                None => anyhow::bail!(
                     "Unsupported shell: {}. Please specify a supported shell (bash, zsh, fish, powershell)",
                     shell_name
                 ),
@@ -313,6 +577,115 @@ pub fn detect_shell() -> Result<Shell> {
     )
 }
 
+/// Map a shell executable name to a supported shell
+fn shell_from_name(name: &str) -> Option<Shell> {
+    // Login shells are reported with a leading dash (e.g. "-zsh")
+    let name = name.trim().trim_start_matches('-');
+    let name = name.strip_suffix(".exe").unwrap_or(name);
+
+    match name {
+        "bash" => Some(Shell::Bash),
+        "zsh" => Some(Shell::Zsh),
+        "fish" => Some(Shell::Fish),
+        "pwsh" | "powershell" => Some(Shell::PowerShell),
+        _ => None,
+    }
+}
+
+/// Get the executable name of the process that launched khelp
+#[cfg(unix)]
+fn parent_process_name() -> Option<String> {
+    let ppid = std::os::unix::process::parent_id();
+
+    // Linux exposes the command name directly; elsewhere ask ps
+    let name = fs::read_to_string(format!("/proc/{}/comm", ppid))
+        .ok()
+        .or_else(|| {
+            let output = std::process::Command::new("ps")
+                .args(["-o", "comm=", "-p", &ppid.to_string()])
+                .output()
+                .ok()?;
+            output
+                .status
+                .success()
+                .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+        })?;
+
+    // ps may report a full path
+    let name = name.trim();
+    let name = name.rsplit('/').next().unwrap_or(name).to_string();
+    debug!("Parent process ({}) is: {}", ppid, name);
+    Some(name)
+}
+
+#[cfg(not(unix))]
+fn parent_process_name() -> Option<String> {
+    None
+}
+
+/// Locations written by `completions --install` for a shell
+pub struct CompletionPaths {
+    /// The completion script itself
+    pub script: PathBuf,
+    /// The shell startup file updated to load the script, if any
+    pub rc_file: Option<PathBuf>,
+}
+
+/// Get the paths that installing completions for a shell writes to
+pub fn completion_paths(shell: Shell) -> Result<CompletionPaths> {
+    let home = || home_directory().ok_or(KhelpError::NoHomeDirectory);
+
+    let paths = match shell {
+        Shell::Bash => CompletionPaths {
+            script: home()?.join(".bash_completion.d").join("khelp"),
+            rc_file: Some(home()?.join(".bashrc")),
+        },
+        Shell::Zsh => CompletionPaths {
+            script: home()?.join(".zfunc").join("_khelp"),
+            rc_file: Some(home()?.join(".zshrc")),
+        },
+        Shell::Fish => CompletionPaths {
+            // Fish loads everything in its completions directory automatically
+            script: home()?.join(".config/fish/completions/khelp.fish"),
+            rc_file: None,
+        },
+        Shell::PowerShell => {
+            // Determine the PowerShell profile path based on platform
+            let profile_dir = if cfg!(target_os = "windows") {
+                // Windows: Use Documents\PowerShell for PowerShell 7+ or Documents\WindowsPowerShell for 5.x
+                dirs::document_dir()
+                    .context("Could not find Documents directory")?
+                    .join("PowerShell")
+            } else {
+                // Unix: PowerShell Core uses ~/.config/powershell
+                dirs::config_dir()
+                    .context("Could not find config directory")?
+                    .join("powershell")
+            };
+            CompletionPaths {
+                script: profile_dir.join("khelp_completions.ps1"),
+                rc_file: Some(profile_dir.join("Microsoft.PowerShell_profile.ps1")),
+            }
+        }
+        _ => anyhow::bail!("Completions installation not implemented for {:?}", shell),
+    };
+
+    Ok(paths)
+}
+
+/// Print where completions for a shell are installed, without writing anything
+pub fn print_completion_paths(shell: Shell) -> Result<()> {
+    let paths = completion_paths(shell)?;
+
+    println!("Completion script: {}", paths.script.display());
+    match paths.rc_file {
+        Some(rc_file) => println!("Startup file: {}", rc_file.display()),
+        None => println!("Startup file: (none, loaded automatically)"),
+    }
+
+    Ok(())
+}
+
 /// Install completions for the specified shell
 fn install_completions(shell: Shell) -> Result<()> {
     debug!(
@@ -369,10 +742,13 @@ fn install_completions(shell: Shell) -> Result<()> {
 
 /// Install Bash completions
 fn install_bash_completions() -> Result<()> {
-    eprintln!("Installing Bash completions for khelp...");
+    status!("Installing Bash completions for khelp...");
 
-    let home = dirs::home_dir().context("Could not find home directory")?;
-    let completions_dir = home.join(".bash_completion.d");
+    let paths = completion_paths(Shell::Bash)?;
+    let completions_file = paths.script;
+    let completions_dir = completions_file
+        .parent()
+        .context("Invalid completions path")?;
 
     debug!(
         "Creating completions directory: {}",
@@ -380,7 +756,7 @@ fn install_bash_completions() -> Result<()> {
     );
 
     // Create completions directory if it doesn't exist
-    fs::create_dir_all(&completions_dir).context("Failed to create completions directory")?;
+    fs::create_dir_all(completions_dir).context("Failed to create completions directory")?;
 
     // Generate the completion script content
     let content = format!(
@@ -393,6 +769,11 @@ _khelp_get_contexts() {{
     kubectl config get-contexts -o name 2>/dev/null
 }}
 
+# Get the namespaces of the current context's cluster
+_khelp_get_namespaces() {{
+    {LIST_NAMESPACES}
+}}
+
 # Complete khelp commands and options
 _khelp_complete() {{
     local cur prev words cword
@@ -404,6 +785,16 @@ _khelp_complete() {{
         return 0
     fi
 
+    # namespace takes an optional context, then a namespace
+    if [[ "${{words[1]}}" == namespace || "${{words[1]}}" == ns ]]; then
+        if [[ $cword -eq 2 ]]; then
+            COMPREPLY=($(compgen -W "$(_khelp_get_namespaces) $(_khelp_get_contexts)" -- "$cur"))
+        else
+            COMPREPLY=($(compgen -W "$(_khelp_get_namespaces)" -- "$cur"))
+        fi
+        return 0
+    fi
+
     # Complete second argument based on first argument
     if [[ $cword -eq 2 ]]; then
         case "$prev" in
@@ -414,7 +805,7 @@ _khelp_complete() {{
                 ;;
             completions)
                 # Complete with shell names
-                COMPREPLY=($(compgen -W "bash zsh fish powershell elvish" -- "$cur"))
+                COMPREPLY=($(compgen -W "auto bash zsh fish powershell elvish" -- "$cur"))
                 return 0
                 ;;
             *)
@@ -432,7 +823,6 @@ complete -F _khelp_complete khelp
     );
 
     // Write the completion script
-    let completions_file = completions_dir.join("khelp");
     fs::write(&completions_file, content).context("Failed to write completion script")?;
 
     // Make the script executable
@@ -445,8 +835,9 @@ complete -F _khelp_complete khelp
     }
 
     // Update .bashrc if needed
-    let bashrc_path = home.join(".bashrc");
-    if let Ok(bashrc_content) = fs::read_to_string(&bashrc_path) {
+    if let Some(bashrc_path) = paths.rc_file
+        && let Ok(bashrc_content) = fs::read_to_string(&bashrc_path)
+    {
         let source_line = format!("source {}", completions_file.display());
         if !bashrc_content.contains(&source_line) {
             let mut bashrc_file = fs::OpenOptions::new()
@@ -460,13 +851,13 @@ complete -F _khelp_complete khelp
         }
     }
 
-    eprintln!(
+    status!(
         "{}",
         style("Bash completions installed successfully!")
             .green()
             .bold()
     );
-    eprintln!(
+    status!(
         "Please run 'source ~/.bash_completion.d/khelp' to enable completions in your current session."
     );
 
@@ -475,13 +866,16 @@ complete -F _khelp_complete khelp
 
 /// Install Zsh completions
 fn install_zsh_completions() -> Result<()> {
-    eprintln!("Installing Zsh completions for khelp...");
+    status!("Installing Zsh completions for khelp...");
 
-    let home = dirs::home_dir().context("Could not find home directory")?;
-    let completions_dir = home.join(".zfunc");
+    let paths = completion_paths(Shell::Zsh)?;
+    let completions_file = paths.script;
+    let completions_dir = completions_file
+        .parent()
+        .context("Invalid completions path")?;
 
     // Create completions directory if it doesn't exist
-    fs::create_dir_all(&completions_dir).context("Failed to create completions directory")?;
+    fs::create_dir_all(completions_dir).context("Failed to create completions directory")?;
 
     // Generate the completion script content
     let content = format!(
@@ -496,6 +890,13 @@ _khelp_get_contexts() {{
     _describe 'contexts' contexts
 }}
 
+# Function to get the namespaces of the current context's cluster
+_khelp_get_namespaces() {{
+    local -a namespaces
+    namespaces=(${{(f)"$({LIST_NAMESPACES})"}})
+    _describe 'namespaces' namespaces
+}}
+
 # Define the completion function
 _khelp() {{
     local line state
@@ -516,11 +917,22 @@ _khelp() {{
                 "s[Switch to a different context]" \
                 "edit[Edit a specific context]" \
                 "export[Export a specific context to stdout]" \
-                "delete[Delete a specific context]" \
-                "rm[Delete a specific context]" \
+                "delete[Delete one or more contexts]" \
+                "rm[Delete one or more contexts]" \
                 "rename[Rename a context]" \
                 "mv[Rename a context]" \
                 "add[Add contexts from an external kubeconfig file]" \
+                "cert-info[Show when certificates expire]" \
+                "reset-current[Pick a current context when it is unset or missing]" \
+                "resolve[Print the current context as kubectl resolves it]" \
+                "prompt[Print the current context for a shell prompt]" \
+                "alias[Manage short aliases for context names]" \
+                "config-path[Print the path of the kubeconfig file khelp operates on]" \
+                "namespace[Set the default namespace of one or more contexts]" \
+                "ns[Set the default namespace of one or more contexts]" \
+                "merge[Create a context from existing contexts]" \
+                "check[Check connectivity to a cluster]" \
+                "ping[Check connectivity to a cluster]" \
                 "completions[Generate shell completions]"
             ;;
         argument)
@@ -528,8 +940,19 @@ _khelp() {{
                 {CONTEXT_COMMANDS})
                     _khelp_get_contexts
                     ;;
+                namespace|ns)
+                    _khelp_get_namespaces
+                    _khelp_get_contexts
+                    ;;
                 completions)
-                    _values "shell" "bash" "zsh" "fish" "powershell" "elvish"
+                    _values "shell" "auto" "bash" "zsh" "fish" "powershell" "elvish"
+                    ;;
+            esac
+            ;;
+        args)
+            case $line[1] in
+                namespace|ns)
+                    _khelp_get_namespaces
                     ;;
             esac
             ;;
@@ -542,7 +965,6 @@ compdef _khelp khelp
     );
 
     // Write the completion script
-    let completions_file = completions_dir.join("_khelp");
     fs::write(&completions_file, content).context("Failed to write completion script")?;
 
     // Make the script executable
@@ -555,8 +977,8 @@ compdef _khelp khelp
     }
 
     // Update .zshrc if needed
-    let zshrc_path = home.join(".zshrc");
-    if let Ok(zshrc_content) = fs::read_to_string(&zshrc_path)
+    if let Some(zshrc_path) = paths.rc_file
+        && let Ok(zshrc_content) = fs::read_to_string(&zshrc_path)
         && !zshrc_content.contains("fpath=(~/.zfunc")
     {
         let mut zshrc_file = fs::OpenOptions::new()
@@ -570,30 +992,30 @@ compdef _khelp khelp
         debug!("Added fpath configuration to ~/.zshrc");
     }
 
-    eprintln!(
+    status!(
         "{}",
         style("Zsh completions installed successfully!")
             .green()
             .bold()
     );
-    eprintln!("Please run 'source ~/.zshrc' to enable completions in your current session.");
+    status!("Please run 'source ~/.zshrc' to enable completions in your current session.");
 
     Ok(())
 }
 
 /// Install Fish completions
 fn install_fish_completions() -> Result<()> {
-    eprintln!("Installing Fish completions for khelp...");
+    status!("Installing Fish completions for khelp...");
 
-    let home = dirs::home_dir().context("Could not find home directory")?;
-    debug!("Home directory: {}", home.display());
-
-    let completions_dir = home.join(".config/fish/completions");
+    let completions_file = completion_paths(Shell::Fish)?.script;
+    let completions_dir = completions_file
+        .parent()
+        .context("Invalid completions path")?;
     debug!("Fish completions directory: {}", completions_dir.display());
 
     // Create completions directory if it doesn't exist
     debug!("Creating fish completions directory...");
-    fs::create_dir_all(&completions_dir).context("Failed to create fish completions directory")?;
+    fs::create_dir_all(completions_dir).context("Failed to create fish completions directory")?;
     debug!("Directory created successfully");
 
     // Generate the completion script content
@@ -607,9 +1029,14 @@ function __khelp_get_contexts
     kubectl config get-contexts -o name 2>/dev/null
 end
 
+function __khelp_get_namespaces
+    {LIST_NAMESPACES}
+end
+
 # Define command completions (including aliases)
 "#,
-    );
+    )
+    .replace("{LIST_NAMESPACES}", LIST_NAMESPACES);
 
     for (cmd, desc) in [
         ("list", "List all available contexts"),
@@ -620,10 +1047,33 @@ end
         ("s", "Switch to a different context"),
         ("edit", "Edit a specific context"),
         ("export", "Export a specific context to stdout"),
-        ("delete", "Delete a specific context"),
-        ("rm", "Delete a specific context"),
+        ("delete", "Delete one or more contexts"),
+        ("rm", "Delete one or more contexts"),
         ("rename", "Rename a context"),
         ("mv", "Rename a context"),
+        ("check", "Check connectivity to a cluster"),
+        ("ping", "Check connectivity to a cluster"),
+        ("merge", "Create a context from existing contexts"),
+        (
+            "namespace",
+            "Set the default namespace of one or more contexts",
+        ),
+        ("ns", "Set the default namespace of one or more contexts"),
+        (
+            "config-path",
+            "Print the path of the kubeconfig file khelp operates on",
+        ),
+        ("alias", "Manage short aliases for context names"),
+        ("cert-info", "Show when certificates expire"),
+        (
+            "reset-current",
+            "Pick a current context when it is unset or missing",
+        ),
+        (
+            "resolve",
+            "Print the current context as kubectl resolves it",
+        ),
+        ("prompt", "Print the current context for a shell prompt"),
         ("add", "Add contexts from an external kubeconfig file"),
         ("completions", "Generate shell completions"),
     ] {
@@ -642,22 +1092,39 @@ complete -c khelp -F -n "__fish_seen_subcommand_from add" -d "Kubeconfig file"
     );
 
     for cmd in [
-        "switch", "use", "s", "edit", "export", "delete", "rm", "rename", "mv",
+        "current",
+        "switch",
+        "use",
+        "s",
+        "edit",
+        "export",
+        "delete",
+        "rm",
+        "rename",
+        "mv",
+        "reset-current",
     ] {
         content.push_str(&format!(
             "complete -c khelp -f -n \"__fish_seen_subcommand_from {cmd}\" -a \"(__khelp_get_contexts)\" -d \"Kubernetes context\"\n"
         ));
     }
 
+    content.push_str(
+        r#"
+# Define namespace completions, after the optional context
+complete -c khelp -f -n "__fish_seen_subcommand_from namespace ns" -a "(__khelp_get_namespaces)" -d "Namespace"
+complete -c khelp -f -n "__fish_seen_subcommand_from namespace ns; and test (count (commandline -opc)) -eq 2" -a "(__khelp_get_contexts)" -d "Kubernetes context"
+"#,
+    );
+
     content.push_str(
         r#"
 # Define shell completions for the completions command
-complete -c khelp -f -n "__fish_seen_subcommand_from completions" -a "bash zsh fish powershell elvish" -d "Shell"
+complete -c khelp -f -n "__fish_seen_subcommand_from completions" -a "auto bash zsh fish powershell elvish" -d "Shell"
 "#,
     );
 
     // Write the completion script
-    let completions_file = completions_dir.join("khelp.fish");
     debug!(
         "Writing completion script to: {}",
         completions_file.display()
@@ -665,38 +1132,32 @@ complete -c khelp -f -n "__fish_seen_subcommand_from completions" -a "bash zsh f
     fs::write(&completions_file, content).context("Failed to write fish completion script")?;
     debug!("Fish completion script written successfully");
 
-    eprintln!(
+    status!(
         "{}",
         style("Fish completions installed successfully!")
             .green()
             .bold()
     );
-    eprintln!("Fish will automatically load the completions for new sessions.");
+    status!("Fish will automatically load the completions for new sessions.");
 
     Ok(())
 }
 
 /// Install PowerShell completions
 fn install_powershell_completions() -> Result<()> {
-    eprintln!("Installing PowerShell completions for khelp...");
-
-    // Determine the PowerShell profile path based on platform
-    let profile_dir = if cfg!(target_os = "windows") {
-        // Windows: Use Documents\PowerShell for PowerShell 7+ or Documents\WindowsPowerShell for 5.x
-        dirs::document_dir()
-            .context("Could not find Documents directory")?
-            .join("PowerShell")
-    } else {
-        // Unix: PowerShell Core uses ~/.config/powershell
-        dirs::config_dir()
-            .context("Could not find config directory")?
-            .join("powershell")
-    };
+    status!("Installing PowerShell completions for khelp...");
+
+    let paths = completion_paths(Shell::PowerShell)?;
+    let completions_file = paths.script;
+    let profile_path = paths.rc_file.context("Missing PowerShell profile path")?;
+    let profile_dir = completions_file
+        .parent()
+        .context("Invalid completions path")?;
 
     debug!("PowerShell profile directory: {}", profile_dir.display());
 
     // Create the profile directory if it doesn't exist
-    fs::create_dir_all(&profile_dir).context("Failed to create PowerShell profile directory")?;
+    fs::create_dir_all(profile_dir).context("Failed to create PowerShell profile directory")?;
 
     // Generate the completion script content
     let content = r#"# khelp PowerShell completions
@@ -714,11 +1175,22 @@ Register-ArgumentCompleter -Native -CommandName khelp -ScriptBlock {
         @{ Name = 's'; Description = 'Switch to a different context' }
         @{ Name = 'edit'; Description = 'Edit a specific context' }
         @{ Name = 'export'; Description = 'Export a specific context to stdout' }
-        @{ Name = 'delete'; Description = 'Delete a specific context' }
-        @{ Name = 'rm'; Description = 'Delete a specific context' }
+        @{ Name = 'delete'; Description = 'Delete one or more contexts' }
+        @{ Name = 'rm'; Description = 'Delete one or more contexts' }
         @{ Name = 'rename'; Description = 'Rename a context' }
         @{ Name = 'mv'; Description = 'Rename a context' }
         @{ Name = 'add'; Description = 'Add contexts from an external kubeconfig file' }
+        @{ Name = 'cert-info'; Description = 'Show when certificates expire' }
+        @{ Name = 'reset-current'; Description = 'Pick a current context when it is unset or missing' }
+        @{ Name = 'resolve'; Description = 'Print the current context as kubectl resolves it' }
+        @{ Name = 'prompt'; Description = 'Print the current context for a shell prompt' }
+        @{ Name = 'alias'; Description = 'Manage short aliases for context names' }
+        @{ Name = 'config-path'; Description = 'Print the path of the kubeconfig file khelp operates on' }
+        @{ Name = 'namespace'; Description = 'Set the default namespace of one or more contexts' }
+        @{ Name = 'ns'; Description = 'Set the default namespace of one or more contexts' }
+        @{ Name = 'merge'; Description = 'Create a context from existing contexts' }
+        @{ Name = 'check'; Description = 'Check connectivity to a cluster' }
+        @{ Name = 'ping'; Description = 'Check connectivity to a cluster' }
         @{ Name = 'completions'; Description = 'Generate shell completions' }
         @{ Name = 'update'; Description = 'Check for updates to khelp' }
     )
@@ -735,7 +1207,7 @@ Register-ArgumentCompleter -Native -CommandName khelp -ScriptBlock {
     }
 
     # Complete context names for relevant commands (including aliases)
-    if ($command -in @('switch', 'use', 's', 'edit', 'export', 'delete', 'rm', 'rename', 'mv')) {
+    if ($command -in @('current', 'switch', 'use', 's', 'edit', 'export', 'delete', 'rm', 'rename', 'mv', 'reset-current')) {
         $contexts = kubectl config get-contexts -o name 2>$null
         if ($contexts) {
             $contexts | Where-Object { $_ -like "$wordToComplete*" } | ForEach-Object {
@@ -747,7 +1219,7 @@ Register-ArgumentCompleter -Native -CommandName khelp -ScriptBlock {
 
     # Complete shells for completions command
     if ($command -eq 'completions') {
-        @('bash', 'zsh', 'fish', 'powershell', 'elvish') | Where-Object { $_ -like "$wordToComplete*" } | ForEach-Object {
+        @('auto', 'bash', 'zsh', 'fish', 'powershell', 'elvish') | Where-Object { $_ -like "$wordToComplete*" } | ForEach-Object {
             [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', "Shell")
         }
     }
@@ -755,7 +1227,6 @@ Register-ArgumentCompleter -Native -CommandName khelp -ScriptBlock {
 "#;
 
     // Write the completion script to a separate file
-    let completions_file = profile_dir.join("khelp_completions.ps1");
     debug!(
         "Writing completion script to: {}",
         completions_file.display()
@@ -764,7 +1235,6 @@ Register-ArgumentCompleter -Native -CommandName khelp -ScriptBlock {
         .context("Failed to write PowerShell completion script")?;
 
     // Update the PowerShell profile to source the completions
-    let profile_path = profile_dir.join("Microsoft.PowerShell_profile.ps1");
     let source_line = format!(". \"{}\"", completions_file.display());
 
     // Check if the profile exists and if it already sources our completions
@@ -786,17 +1256,34 @@ Register-ArgumentCompleter -Native -CommandName khelp -ScriptBlock {
         debug!("Added source line to PowerShell profile");
     }
 
-    eprintln!(
+    status!(
         "{}",
         style("PowerShell completions installed successfully!")
             .green()
             .bold()
     );
-    eprintln!("Completions will be loaded automatically in new PowerShell sessions.");
-    eprintln!(
+    status!("Completions will be loaded automatically in new PowerShell sessions.");
+    status!(
         "To enable in current session, run: . \"{}\"",
         completions_file.display()
     );
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_generate_completions_writes_to_output_path() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("completions/khelp.bash");
+
+        generate_completions(Shell::Bash, false, Some(&path)).expect("Failed to generate");
+
+        let script = fs::read_to_string(&path).expect("Failed to read script");
+        assert!(script.contains("complete -F _khelp_completions khelp"));
+    }
+}
@@ -0,0 +1,30 @@
+use anyhow::Result;
+use console::style;
+
+use crate::commands::output::status;
+use crate::config::operations::{configured_kube_config_path, resolve_config_path};
+
+/// Print the kubeconfig path khelp reads from and writes to
+///
+/// The path goes to stdout so it can be used in scripts; anything noteworthy
+/// about it (where it was set, missing file, symlink target) goes to stderr.
+/// source names where the path came from, such as `--kubeconfig` or `KHELP_KUBECONFIG`.
+pub fn show_config_path(source: &str) -> Result<()> {
+    let path = configured_kube_config_path()?;
+    println!("{}", path.display());
+    status!("  (from {})", source);
+
+    let resolved = resolve_config_path(&path)?;
+    if resolved != path {
+        status!("  -> {}", style(resolved.display()).cyan());
+    }
+
+    if !resolved.exists() {
+        eprintln!(
+            "{} no file exists at this path yet",
+            style("Warning:").yellow().bold()
+        );
+    }
+
+    Ok(())
+}
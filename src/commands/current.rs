@@ -1,60 +1,489 @@
 use crate::cli::OutputFormat;
-use crate::config::kubernetes::KubeConfig;
+use crate::commands::api::Credentials;
+use crate::commands::check::{Reachability, probe_server};
+use crate::commands::output::can_prompt;
+use crate::commands::reset_current::reset_current_context;
+use crate::config::kubernetes::{ContextData, KubeConfig};
+use crate::utils::confirm;
+use anyhow::Result;
 use console::style;
 use serde::Serialize;
+use std::fs;
+use std::time::{Duration, Instant};
+
+/// How long `current --check` waits for the API server, kept short since it's a
+/// quick look rather than a diagnosis like `check`
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Placeholders understood by `current --template`
+const TEMPLATE_PLACEHOLDERS: [&str; 6] =
+    ["context", "namespace", "cluster", "server", "user", "auth"];
 
 #[derive(Serialize)]
 struct CurrentContextInfo {
     name: String,
     cluster: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    server: Option<String>,
     user: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     namespace: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    auth_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reachability: Option<ReachabilityInfo>,
+}
+
+/// Outcome of a quick probe of a context's API server
+#[derive(Serialize)]
+struct ReachabilityInfo {
+    reachable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    elapsed_ms: u64,
+}
+
+impl ReachabilityInfo {
+    /// One-line summary such as `yes (HTTP 401 in 120ms)`
+    fn summary(&self) -> String {
+        match (&self.version, self.status, &self.error) {
+            (Some(version), _, _) => format!("yes ({} in {}ms)", version, self.elapsed_ms),
+            (_, Some(status), _) => format!("yes (HTTP {} in {}ms)", status, self.elapsed_ms),
+            (_, _, error) => format!(
+                "no (after {}ms: {})",
+                self.elapsed_ms,
+                error.as_deref().unwrap_or("no answer")
+            ),
+        }
+    }
+}
+
+/// Probe a context's API server with a `GET /version`, without credentials
+///
+/// Any HTTP answer, even a refusal, counts as reachable.
+fn probe_reachability(config: &KubeConfig, context_name: &str) -> ReachabilityInfo {
+    let cluster = config
+        .contexts
+        .iter()
+        .find(|c| c.name == context_name)
+        .and_then(|context| {
+            config
+                .clusters
+                .iter()
+                .find(|c| c.name == context.context.cluster)
+        });
+
+    let started = Instant::now();
+    let result = match cluster {
+        Some(cluster) => probe_server(&cluster.cluster, &Credentials::Anonymous, CHECK_TIMEOUT),
+        None => Err(anyhow::anyhow!("no cluster to probe")),
+    };
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+
+    let (version, status, error) = match result {
+        Ok(Reachability::Version(version)) => (Some(version), None, None),
+        Ok(Reachability::Status(status)) => (None, Some(status), None),
+        Err(e) => (None, None, Some(format!("{:#}", e))),
+    };
+    ReachabilityInfo {
+        reachable: error.is_none(),
+        version,
+        status,
+        error,
+        elapsed_ms,
+    }
+}
+
+/// Collect a context along with its resolved cluster server and auth type
+fn context_info(config: &KubeConfig, context_name: &str) -> Option<CurrentContextInfo> {
+    let context = config.contexts.iter().find(|c| c.name == context_name)?;
+
+    let server = config
+        .clusters
+        .iter()
+        .find(|c| c.name == context.context.cluster)
+        .map(|c| c.cluster.server.clone());
+
+    let auth_type = config
+        .users
+        .iter()
+        .find(|u| u.name == context.context.user)
+        .map(|u| u.user.auth_type().to_string());
+
+    Some(CurrentContextInfo {
+        name: context.name.clone(),
+        cluster: context.context.cluster.clone(),
+        server,
+        user: context.context.user.clone(),
+        namespace: context.context.namespace.clone(),
+        auth_type,
+        reachability: None,
+    })
+}
+
+/// Print the server and authentication details of a context
+fn print_wide_details(config: &KubeConfig, context: &ContextData) {
+    if let Some(cluster) = config.clusters.iter().find(|c| c.name == context.cluster) {
+        println!("  Server: {}", style(&cluster.cluster.server).cyan());
+        if let Some(proxy_url) = &cluster.cluster.proxy_url {
+            println!("  Proxy: {}", style(proxy_url).cyan());
+        }
+    }
+
+    let Some(user) = config.users.iter().find(|u| u.name == context.user) else {
+        return;
+    };
+    println!("  Auth: {}", style(user.user.auth_type()).cyan());
+
+    if let Some(token_file) = &user.user.token_file {
+        println!("  Token file: {}", style(token_file).cyan());
+        if let Err(e) = fs::File::open(token_file) {
+            eprintln!(
+                "{} token file {} can't be read: {}",
+                style("Warning:").yellow().bold(),
+                token_file,
+                e
+            );
+        }
+    }
+
+    if let Some(auth_provider) = &user.user.auth_provider {
+        println!("  Auth provider: {}", style(&auth_provider.name).cyan());
+        for entry in auth_provider.display_config_redacted() {
+            println!("    {}", style(entry).dim());
+        }
+    }
+
+    if let Some(exec) = &user.user.exec {
+        println!("  Exec: {}", style(exec.command_line()).cyan());
+        for var in exec.env.iter().flatten() {
+            println!("    {}", style(var.display_redacted()).dim());
+        }
+    }
+}
+
+/// Copy a context name to the clipboard, printing it instead if that isn't possible
+#[cfg(feature = "clipboard")]
+pub fn copy_context_name(context_name: &str) -> Result<()> {
+    copy_or_print(
+        context_name,
+        crate::utils::copy_to_clipboard,
+        &mut std::io::stdout(),
+    )
+}
+
+#[cfg(feature = "clipboard")]
+fn copy_or_print(
+    context_name: &str,
+    copy: impl FnOnce(&str) -> Result<()>,
+    out: &mut impl std::io::Write,
+) -> Result<()> {
+    if context_name.is_empty() {
+        anyhow::bail!("No current context is set");
+    }
+
+    match copy(context_name) {
+        Ok(()) => crate::commands::output::status!(
+            "{} Copied {} to the clipboard",
+            style("✓").green(),
+            style(context_name).green().bold()
+        ),
+        Err(e) => {
+            eprintln!(
+                "{} could not copy to the clipboard: {:#}; printing it instead",
+                style("Warning:").yellow().bold(),
+                e
+            );
+            writeln!(out, "{}", context_name)?;
+        }
+    }
+    Ok(())
+}
+
+/// Offer to pick a new current context after warning that it is missing
+///
+/// Only asks when a prompt is possible; returns whether a context was picked.
+pub fn offer_current_context_repair() -> Result<bool> {
+    if !can_prompt() || !confirm("Pick a current context now?", true, false)? {
+        return Ok(false);
+    }
+    reset_current_context(None)?;
+    Ok(true)
 }
 
-/// Display details about the currently active context
-pub fn show_current_context(config: &KubeConfig, output: &OutputFormat) {
+/// Display details about a context, normally the currently active one
+///
+/// Inspecting another context doesn't change the current context.
+/// With `wide`, table output also shows the server and how the user authenticates.
+/// With `check`, the context's API server is probed and whether it answered is
+/// shown alongside the details; name output skips the probe.
+pub fn show_current_context(
+    config: &KubeConfig,
+    context_name: &str,
+    output: &OutputFormat,
+    wide: bool,
+    check: bool,
+) {
+    let reachability = || check.then(|| probe_reachability(config, context_name));
+    let info = || {
+        context_info(config, context_name).map(|info| CurrentContextInfo {
+            reachability: reachability(),
+            ..info
+        })
+    };
+
     match output {
         OutputFormat::Table => {
-            println!(
-                "Current context: {}",
-                style(&config.current_context).green().bold()
-            );
+            let label = if context_name == config.current_context {
+                "Current context"
+            } else {
+                "Context"
+            };
+            println!("{}: {}", label, style(context_name).green().bold());
 
-            if let Some(context) = config
-                .contexts
-                .iter()
-                .find(|c| c.name == config.current_context)
-            {
+            if let Some(context) = config.contexts.iter().find(|c| c.name == context_name) {
                 println!("  Cluster: {}", style(&context.context.cluster).cyan());
                 println!("  User: {}", style(&context.context.user).cyan());
 
                 if let Some(namespace) = &context.context.namespace {
                     println!("  Namespace: {}", style(namespace).cyan());
                 }
+
+                if wide {
+                    print_wide_details(config, &context.context);
+                }
+
+                if let Some(reachability) = reachability() {
+                    let summary = reachability.summary();
+                    let summary = if reachability.reachable {
+                        style(summary).green()
+                    } else {
+                        style(summary).red()
+                    };
+                    println!("  Reachable: {}", summary);
+                }
+            }
+        }
+        OutputFormat::Plain => {
+            if let Some(info) = info() {
+                let mut fields = vec![
+                    info.name,
+                    info.cluster,
+                    info.user,
+                    info.namespace.unwrap_or_default(),
+                ];
+                if let Some(reachability) = info.reachability {
+                    fields.push(if reachability.reachable { "yes" } else { "no" }.to_string());
+                }
+                println!("{}", fields.join("\t"));
+            } else {
+                println!("{}", context_name);
             }
         }
         OutputFormat::Name => {
-            println!("{}", config.current_context);
+            println!("{}", context_name);
         }
         OutputFormat::Json => {
-            if let Some(context) = config
-                .contexts
-                .iter()
-                .find(|c| c.name == config.current_context)
-            {
-                let info = CurrentContextInfo {
-                    name: config.current_context.clone(),
-                    cluster: context.context.cluster.clone(),
-                    user: context.context.user.clone(),
-                    namespace: context.context.namespace.clone(),
-                };
+            if let Some(info) = info() {
                 if let Ok(json) = serde_json::to_string_pretty(&info) {
                     println!("{}", json);
                 }
             } else {
-                println!("\"{}\"", config.current_context);
+                println!("\"{}\"", context_name);
+            }
+        }
+        OutputFormat::Yaml => {
+            let yaml = match info() {
+                Some(info) => serde_yaml::to_string(&info),
+                None => serde_yaml::to_string(&context_name),
+            };
+            if let Ok(yaml) = yaml {
+                print!("{}", yaml);
             }
         }
     }
 }
+
+/// Render a context through a template such as `{context}:{namespace}`
+///
+/// A context without a namespace renders as `default`, the namespace kubectl uses.
+/// Unknown placeholders are an error so typos don't go unnoticed.
+pub fn render_template(config: &KubeConfig, context_name: &str, template: &str) -> Result<String> {
+    let info = context_info(config, context_name);
+    let value = |key: &str| -> String {
+        let Some(info) = &info else {
+            return if key == "context" {
+                context_name.to_string()
+            } else {
+                String::new()
+            };
+        };
+        match key {
+            "context" => info.name.clone(),
+            "namespace" => info
+                .namespace
+                .clone()
+                .unwrap_or_else(|| "default".to_string()),
+            "cluster" => info.cluster.clone(),
+            "server" => info.server.clone().unwrap_or_default(),
+            "user" => info.user.clone(),
+            "auth" => info.auth_type.clone().unwrap_or_default(),
+            _ => unreachable!("placeholder was validated"),
+        }
+    };
+
+    let mut rendered = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(len) = rest.find('}') else {
+            break;
+        };
+        let key = &rest[1..len];
+        if !TEMPLATE_PLACEHOLDERS.contains(&key) {
+            anyhow::bail!(
+                "Unknown placeholder '{{{}}}' in template; available: {}",
+                key,
+                TEMPLATE_PLACEHOLDERS
+                    .iter()
+                    .map(|p| format!("{{{}}}", p))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        rendered.push_str(&value(key));
+        rest = &rest[len + 1..];
+    }
+    rendered.push_str(rest);
+
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_template() {
+        let config: KubeConfig = serde_yaml::from_str(
+            r#"
+current-context: dev
+clusters:
+- name: dev-cluster
+  cluster: {server: "https://dev.example.com"}
+contexts:
+- name: dev
+  context: {cluster: dev-cluster, user: alice}
+- name: staging
+  context: {cluster: dev-cluster, user: alice, namespace: staging}
+users:
+- name: alice
+  user: {token: secret}
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            render_template(&config, "dev", "{context}:{namespace}").unwrap(),
+            "dev:default"
+        );
+        assert_eq!(
+            render_template(&config, "dev", "[{cluster}] {server} as {user} ({auth})").unwrap(),
+            "[dev-cluster] https://dev.example.com as alice (token)"
+        );
+        assert_eq!(
+            render_template(&config, "dev", "no {braces").unwrap(),
+            "no {braces"
+        );
+        assert!(render_template(&config, "dev", "{contxt}").is_err());
+
+        // Inspecting another context leaves the active one alone
+        assert_eq!(
+            render_template(&config, "staging", "{context} on {cluster}").unwrap(),
+            "staging on dev-cluster"
+        );
+        assert_eq!(config.current_context, "dev");
+    }
+
+    #[test]
+    fn test_probe_reachability() {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind");
+        let answering = listener.local_addr().expect("No address");
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("Failed to accept");
+            let _ = stream.read(&mut [0; 1024]);
+            let _ = stream.write_all(b"HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\n\r\n");
+        });
+        // Nothing listens once the listener is dropped, so connecting is refused
+        let refusing = TcpListener::bind("127.0.0.1:0")
+            .and_then(|l| l.local_addr())
+            .expect("Failed to bind");
+
+        let config: KubeConfig = serde_yaml::from_str(&format!(
+            r#"
+clusters:
+- name: up
+  cluster: {{server: "http://{}"}}
+- name: down
+  cluster: {{server: "http://{}"}}
+contexts:
+- name: up
+  context: {{cluster: up, user: alice}}
+- name: down
+  context: {{cluster: down, user: alice}}
+- name: orphan
+  context: {{cluster: missing, user: alice}}
+"#,
+            answering, refusing
+        ))
+        .unwrap();
+
+        let up = probe_reachability(&config, "up");
+        assert!(up.reachable);
+        assert_eq!(up.status, Some(401));
+        assert!(up.summary().starts_with("yes (HTTP 401 in "));
+
+        let down = probe_reachability(&config, "down");
+        assert!(!down.reachable);
+        assert!(down.summary().starts_with("no (after "));
+
+        assert!(!probe_reachability(&config, "orphan").reachable);
+    }
+
+    #[cfg(feature = "clipboard")]
+    #[test]
+    fn test_copy_falls_back_to_printing() {
+        let mut out = Vec::new();
+        copy_or_print(
+            "prod",
+            |_| anyhow::bail!("no clipboard available"),
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(out, b"prod\n");
+
+        let mut out = Vec::new();
+        let mut copied = None;
+        copy_or_print(
+            "prod",
+            |name| {
+                copied = Some(name.to_string());
+                Ok(())
+            },
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(copied.as_deref(), Some("prod"));
+        assert!(out.is_empty());
+
+        assert!(copy_or_print("", |_| Ok(()), &mut Vec::new()).is_err());
+    }
+}
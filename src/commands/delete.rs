@@ -1,17 +1,32 @@
 use anyhow::{Context, Result};
 use console::style;
-use dialoguer::{Confirm, Select, theme::ColorfulTheme};
+use dialoguer::{Input, MultiSelect, Select, theme::ColorfulTheme};
 use log::debug;
-use std::collections::HashSet;
 
+use crate::commands::output::{ensure_can_prompt, status};
+use crate::config::analysis::{find_broken_contexts, find_orphans};
 use crate::config::operations::{load_kube_config, save_kube_config};
+use crate::config::usage::{remove_usage_in, update_usage};
+use crate::error::KhelpError;
+use crate::utils::confirm;
 
-/// Delete a Kubernetes context
+/// Delete one or more Kubernetes contexts
 ///
-/// If context_name is provided, deletes that context directly.
-/// Otherwise, presents an interactive menu to select a context.
-/// Always cleans up the associated cluster and user if they become orphaned.
-pub fn delete_context(context_name: Option<String>, force: bool) -> Result<()> {
+/// If context_names is non-empty, deletes those contexts directly.
+/// If current is true, deletes the active context.
+/// If broken is true, deletes every context whose cluster or user is missing.
+/// Otherwise, presents an interactive menu to select contexts.
+/// All targets are confirmed at once and removed in a single save.
+/// Always cleans up the associated clusters and users if they become orphaned.
+/// If switch_to is provided, it becomes the current context when the current one is
+/// deleted, without asking which context to switch to.
+pub fn delete_contexts(
+    context_names: Vec<String>,
+    current: bool,
+    broken: bool,
+    force: bool,
+    switch_to: Option<String>,
+) -> Result<()> {
     let mut config = load_kube_config()?;
     debug!("Loaded kube config with {} contexts", config.contexts.len());
 
@@ -19,173 +34,298 @@ pub fn delete_context(context_name: Option<String>, force: bool) -> Result<()> {
         anyhow::bail!("No contexts available to delete");
     }
 
-    // Select context to delete
-    let selected_context_name = match context_name {
-        Some(name) => {
+    let context_names = if current {
+        if config.current_context.is_empty() {
+            anyhow::bail!("No current context is set");
+        }
+        vec![config.current_context.clone()]
+    } else if broken {
+        let broken_contexts = find_broken_contexts(&config);
+        if broken_contexts.is_empty() {
+            status!("No broken contexts found");
+            return Ok(());
+        }
+        status!("Found broken contexts (missing cluster or user):");
+        for name in &broken_contexts {
+            status!("  - {}", style(name).yellow());
+        }
+        broken_contexts
+    } else {
+        context_names
+    };
+
+    // Select contexts to delete
+    let selected_context_names: Vec<String> = if context_names.is_empty() {
+        debug!("No context name provided, showing selection menu");
+        ensure_can_prompt()?;
+        let selections = MultiSelect::with_theme(&ColorfulTheme::default())
+            .with_prompt("Select contexts to delete (Space to select, Enter to confirm)")
+            .items(&config.contexts.iter().map(|c| &c.name).collect::<Vec<_>>())
+            .interact()
+            .context("Failed to display interactive selection")?;
+
+        if selections.is_empty() {
+            anyhow::bail!("No contexts selected");
+        }
+
+        selections
+            .iter()
+            .map(|&i| config.contexts[i].name.clone())
+            .collect()
+    } else {
+        let mut names = Vec::new();
+        for name in context_names {
             debug!("Context name provided: {}", name);
             if !config.contexts.iter().any(|c| c.name == name) {
-                anyhow::bail!("Context '{}' not found", name);
+                anyhow::bail!(KhelpError::ContextNotFound(name.clone()));
+            }
+            if !names.contains(&name) {
+                names.push(name);
             }
-            name
-        }
-        None => {
-            debug!("No context name provided, showing selection menu");
-            let selection = Select::with_theme(&ColorfulTheme::default())
-                .with_prompt("Select a context to delete")
-                .default(0)
-                .items(&config.contexts.iter().map(|c| &c.name).collect::<Vec<_>>())
-                .interact()
-                .context("Failed to display interactive selection")?;
-
-            config.contexts[selection].name.clone()
         }
+        names
     };
 
-    debug!("Selected context to delete: {}", selected_context_name);
+    debug!("Selected contexts to delete: {:?}", selected_context_names);
 
-    // Check if it's the current context
-    let is_current_context = config.current_context == selected_context_name;
+    if let Some(target) = &switch_to {
+        if !config.contexts.iter().any(|c| c.name == *target) {
+            anyhow::bail!(KhelpError::ContextNotFound(target.clone()));
+        }
+        if selected_context_names.contains(target) {
+            anyhow::bail!("Cannot switch to '{}' because it is being deleted", target);
+        }
+    }
+
+    // Check if the current context is among them
+    let is_current_context = selected_context_names.contains(&config.current_context);
 
     if is_current_context {
-        eprintln!(
+        status!(
             "Context '{}' is currently active",
-            style(&selected_context_name).yellow()
+            style(&config.current_context).yellow()
         );
 
-        // If there are other contexts, offer to switch
-        if config.contexts.len() > 1 {
-            let should_switch = if force {
-                true
-            } else {
-                Confirm::with_theme(&ColorfulTheme::default())
-                    .with_prompt("Switch to another context first?")
-                    .default(true)
-                    .interact()
-                    .context("Failed to get confirmation")?
-            };
+        let other_contexts: Vec<String> = config
+            .contexts
+            .iter()
+            .filter(|c| !selected_context_names.contains(&c.name))
+            .map(|c| c.name.clone())
+            .collect();
 
-            if should_switch {
-                let other_contexts: Vec<_> = config
-                    .contexts
-                    .iter()
-                    .filter(|c| c.name != selected_context_name)
-                    .map(|c| &c.name)
-                    .collect();
-
-                let selection = if force {
-                    0
-                } else {
-                    Select::with_theme(&ColorfulTheme::default())
-                        .with_prompt("Select a context to switch to")
-                        .default(0)
-                        .items(&other_contexts)
-                        .interact()
-                        .context("Failed to display interactive selection")?
-                };
-
-                let new_context = other_contexts[selection].clone();
-                config.current_context = new_context.clone();
-                eprintln!(
-                    "Switched to context: {}",
-                    style(&new_context).green().bold()
-                );
-            } else {
+        // If there are contexts left over, offer to switch
+        if !other_contexts.is_empty() {
+            if !confirm(
+                "Switch to another context first?",
+                true,
+                force || switch_to.is_some(),
+            )? {
                 anyhow::bail!("Cannot delete the current context without switching first");
             }
         } else {
-            // This is the last context, clear current_context
-            debug!("Deleting the last remaining context");
-            config.current_context = String::new();
+            // Every remaining context is being deleted, clear current_context
+            debug!("Deleting the last remaining contexts");
         }
     }
 
-    // Confirmation prompt
-    if !force {
-        let confirmed = Confirm::with_theme(&ColorfulTheme::default())
+    // Confirmation prompt, listing every target at once (broken ones are already listed)
+    if !force && !broken && selected_context_names.len() > 1 {
+        status!("Contexts to delete:");
+        for name in &selected_context_names {
+            status!("  {} {}", style("•").red(), name);
+        }
+    }
+
+    if !force && is_current_context {
+        // Deleting the active context requires typing its name, not just y/n
+        ensure_can_prompt()?;
+        let typed: String = Input::with_theme(&ColorfulTheme::default())
             .with_prompt(format!(
-                "Are you sure you want to delete context '{}'?",
-                selected_context_name
+                "This includes your current context. Type '{}' to confirm deletion",
+                config.current_context
             ))
-            .default(false)
-            .interact()
+            .allow_empty(true)
+            .interact_text()
             .context("Failed to get confirmation")?;
 
-        if !confirmed {
+        if typed.trim() != config.current_context {
+            eprintln!("Deletion cancelled: name did not match");
+            return Ok(());
+        }
+    } else {
+        let prompt = match selected_context_names.as_slice() {
+            [only] => format!("Are you sure you want to delete context '{}'?", only),
+            many => format!(
+                "Are you sure you want to delete these {} contexts?",
+                many.len()
+            ),
+        };
+        if !confirm(&prompt, false, force)? {
             eprintln!("Deletion cancelled");
             return Ok(());
         }
     }
 
-    // Get cluster and user names before deletion for potential cleanup
-    let context_to_delete = config
-        .contexts
-        .iter()
-        .find(|c| c.name == selected_context_name)
-        .ok_or_else(|| anyhow::anyhow!("Context not found"))?;
-
-    let cluster_name = context_to_delete.context.cluster.clone();
-    let user_name = context_to_delete.context.user.clone();
-
-    // Delete the context
-    config.contexts.retain(|c| c.name != selected_context_name);
-    debug!("Removed context: {}", selected_context_name);
+    if is_current_context {
+        let other_contexts: Vec<&String> = config
+            .contexts
+            .iter()
+            .filter(|c| !selected_context_names.contains(&c.name))
+            .map(|c| &c.name)
+            .collect();
 
-    eprintln!(
-        "{} Deleted context: {}",
-        style("✓").green(),
-        style(&selected_context_name).green().bold()
-    );
+        if other_contexts.is_empty() {
+            config.current_context = String::new();
+        } else {
+            let selection = if let Some(target) = &switch_to {
+                other_contexts
+                    .iter()
+                    .position(|name| *name == target)
+                    .expect("switch target was validated")
+            } else if force {
+                0
+            } else {
+                ensure_can_prompt()?;
+                Select::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Select a context to switch to")
+                    .default(0)
+                    .items(&other_contexts)
+                    .interact()
+                    .context("Failed to display interactive selection")?
+            };
 
-    // Clean up associated cluster and user if they become orphaned
-    let mut deleted_clusters = Vec::new();
-    let mut deleted_users = Vec::new();
+            let new_context = other_contexts[selection].clone();
+            config.current_context = new_context.clone();
+            status!(
+                "Switched to context: {}",
+                style(&new_context).green().bold()
+            );
+        }
+    }
 
-    // Find referenced clusters and users
-    let referenced_clusters: HashSet<String> = config
+    // Get cluster and user names before deletion for potential cleanup
+    let mut candidate_clusters = Vec::new();
+    let mut candidate_users = Vec::new();
+    for context in config
         .contexts
         .iter()
-        .map(|c| c.context.cluster.clone())
-        .collect();
+        .filter(|c| selected_context_names.contains(&c.name))
+    {
+        if !candidate_clusters.contains(&context.context.cluster) {
+            candidate_clusters.push(context.context.cluster.clone());
+        }
+        if !candidate_users.contains(&context.context.user) {
+            candidate_users.push(context.context.user.clone());
+        }
+    }
 
-    let referenced_users: HashSet<String> = config
+    // Delete the contexts
+    config
         .contexts
-        .iter()
-        .map(|c| c.context.user.clone())
-        .collect();
+        .retain(|c| !selected_context_names.contains(&c.name));
 
-    // Delete orphaned cluster
-    if !referenced_clusters.contains(&cluster_name) {
-        config.clusters.retain(|c| c.name != cluster_name);
-        deleted_clusters.push(cluster_name);
-        debug!("Removed orphaned cluster");
+    for name in &selected_context_names {
+        debug!("Removed context: {}", name);
+        status!(
+            "{} Deleted context: {}",
+            style("✓").green(),
+            style(name).green().bold()
+        );
     }
 
-    // Delete orphaned user
-    if !referenced_users.contains(&user_name) {
-        config.users.retain(|u| u.name != user_name);
-        deleted_users.push(user_name);
-        debug!("Removed orphaned user");
-    }
+    // Clean up associated clusters and users if they become orphaned
+    let (orphaned_clusters, orphaned_users) = find_orphans(&config);
+    let deleted_clusters: Vec<String> = candidate_clusters
+        .into_iter()
+        .filter(|cluster| orphaned_clusters.contains(cluster))
+        .collect();
+    let deleted_users: Vec<String> = candidate_users
+        .into_iter()
+        .filter(|user| orphaned_users.contains(user))
+        .collect();
+
+    config
+        .clusters
+        .retain(|c| !deleted_clusters.contains(&c.name));
+    config.users.retain(|u| !deleted_users.contains(&u.name));
+    debug!(
+        "Removed {} orphaned cluster(s) and {} orphaned user(s)",
+        deleted_clusters.len(),
+        deleted_users.len()
+    );
 
     // Report cleanup results
-    for cluster in deleted_clusters {
-        eprintln!(
+    for cluster in &deleted_clusters {
+        status!(
             "{} Deleted cluster: {}",
             style("✓").green(),
-            style(&cluster).cyan()
+            style(cluster).cyan()
         );
     }
 
-    for user in deleted_users {
-        eprintln!(
+    for user in &deleted_users {
+        status!(
             "{} Deleted user: {}",
             style("✓").green(),
-            style(&user).cyan()
+            style(user).cyan()
         );
     }
 
+    if selected_context_names.len() > 1 {
+        status!(
+            "Deleted {} context(s), {} cluster(s), and {} user(s)",
+            selected_context_names.len(),
+            deleted_clusters.len(),
+            deleted_users.len()
+        );
+    }
+
+    // A config that was already broken may have a current-context that doesn't exist
+    if !config.current_context.is_empty()
+        && !config
+            .contexts
+            .iter()
+            .any(|c| c.name == config.current_context)
+    {
+        eprintln!(
+            "{} current context '{}' does not exist",
+            style("Warning:").yellow().bold(),
+            config.current_context
+        );
+
+        if force || config.contexts.is_empty() {
+            debug!("Clearing dangling current context");
+            config.current_context = String::new();
+            status!("{} Cleared current context", style("✓").green());
+        } else {
+            let mut items: Vec<&str> = config.contexts.iter().map(|c| c.name.as_str()).collect();
+            items.push("(leave no context selected)");
+
+            ensure_can_prompt()?;
+            let selection = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt("Select a new current context")
+                .default(0)
+                .items(&items)
+                .interact()
+                .context("Failed to display interactive selection")?;
+
+            if selection < config.contexts.len() {
+                config.current_context = config.contexts[selection].name.clone();
+                status!(
+                    "Switched to context: {}",
+                    style(&config.current_context).green().bold()
+                );
+            } else {
+                config.current_context = String::new();
+                status!("{} Cleared current context", style("✓").green());
+            }
+        }
+    }
+
     // Save the config
     save_kube_config(&config)?;
 
+    update_usage(|path| remove_usage_in(path, &selected_context_names));
+
     Ok(())
 }
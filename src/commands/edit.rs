@@ -4,27 +4,86 @@ use dialoguer::{Select, theme::ColorfulTheme};
 use log::debug;
 use std::env;
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use tempfile;
 
-use crate::config::operations::{load_kube_config, save_kube_config};
+use crate::commands::cert_info::parse_certificates;
+use crate::commands::namespace::check_namespace_name;
+use crate::commands::output::{ensure_can_prompt, status, warn_conflicting_fields};
+use crate::config::analysis::{find_empty_fields, find_reference_problems};
+use crate::config::kubernetes::KubeConfig;
+use crate::config::operations::{
+    config_fingerprint, get_kube_config_path, load_kube_config, normalize_line_endings,
+    save_kube_config,
+};
+use crate::error::KhelpError;
+use crate::utils::confirm;
+
+/// Fields `edit` sets directly instead of opening an editor
+#[derive(Debug, Default)]
+pub struct FieldChanges {
+    /// Server URL of the context's cluster
+    pub server: Option<String>,
+    /// Default namespace of the context
+    pub namespace: Option<String>,
+    /// Cluster the context uses
+    pub cluster: Option<String>,
+    /// User the context uses
+    pub user: Option<String>,
+    /// PEM file with the CA of the context's cluster, embedded base64-encoded
+    pub ca_file: Option<PathBuf>,
+}
+
+impl FieldChanges {
+    pub fn is_empty(&self) -> bool {
+        self.server.is_none()
+            && self.namespace.is_none()
+            && self.cluster.is_none()
+            && self.user.is_none()
+            && self.ca_file.is_none()
+    }
+}
 
 /// Edit a specific Kubernetes context
 ///
-/// Opens the selected context in the user's preferred editor.
+/// Opens the selected context in the user's preferred editor, or applies changes
+/// directly without an editor when any are given.
 /// If context_name is provided, edits that context directly.
+/// If current is true, edits the active context.
 /// Otherwise, presents an interactive menu to select a context.
-pub fn edit_context(context_name: Option<String>) -> Result<()> {
+/// A namespace changed to an invalid name is refused unless force is set.
+/// Use edit_full_config to edit the whole file instead.
+pub fn edit_context(
+    context_name: Option<String>,
+    current: bool,
+    force: bool,
+    changes: FieldChanges,
+) -> Result<()> {
+    let config_path = get_kube_config_path()?;
     let config = load_kube_config()?;
+    // Remember what the file looked like so changes made while the editor is open
+    // aren't silently overwritten
+    let fingerprint = config_fingerprint(&config_path)?;
+
+    let context_name = if current {
+        if config.current_context.is_empty() {
+            anyhow::bail!("No current context is set");
+        }
+        Some(config.current_context.clone())
+    } else {
+        context_name
+    };
 
     let selected_context_name = match context_name {
         Some(name) => {
             if !config.contexts.iter().any(|c| c.name == name) {
-                anyhow::bail!("Context '{}' not found", name);
+                anyhow::bail!(KhelpError::ContextNotFound(name.clone()));
             }
             name
         }
         None => {
+            ensure_can_prompt()?;
             let selection = Select::with_theme(&ColorfulTheme::default())
                 .with_prompt("Select a context to edit")
                 .default(0)
@@ -38,55 +97,62 @@ pub fn edit_context(context_name: Option<String>) -> Result<()> {
 
     debug!("Selected context to edit: {}", selected_context_name);
 
+    if !changes.is_empty() {
+        let mut modified_config = config;
+        let (cluster_name, user_name) =
+            apply_field_changes(&mut modified_config, &selected_context_name, changes, force)?;
+        return save_edited_context(
+            &modified_config,
+            &selected_context_name,
+            &cluster_name,
+            &user_name,
+        );
+    }
+
     let context = config
         .contexts
         .iter()
         .find(|c| c.name == selected_context_name)
-        .ok_or_else(|| anyhow::anyhow!("Context not found"))?;
+        .ok_or_else(|| KhelpError::ContextNotFound(selected_context_name.clone()))?;
 
     let cluster_name = &context.context.cluster;
     let _cluster = config
         .clusters
         .iter()
         .find(|c| &c.name == cluster_name)
-        .ok_or_else(|| anyhow::anyhow!("Cluster '{}' not found", cluster_name))?;
+        .ok_or_else(|| KhelpError::ClusterNotFound {
+            cluster: cluster_name.clone(),
+            context: selected_context_name.clone(),
+        })?;
 
     let user_name = &context.context.user;
     let _user = config
         .users
         .iter()
         .find(|u| &u.name == user_name)
-        .ok_or_else(|| anyhow::anyhow!("User '{}' not found", user_name))?;
+        .ok_or_else(|| KhelpError::UserNotFound {
+            user: user_name.clone(),
+            context: selected_context_name.clone(),
+        })?;
 
     debug!(
         "Found related cluster: {} and user: {}",
         cluster_name, user_name
     );
 
-    let clusters_str = config
-        .clusters
-        .iter()
-        .map(|c| c.name.as_str())
-        .collect::<Vec<_>>()
-        .join(", ");
-
-    let users_str = config
-        .users
-        .iter()
-        .map(|u| u.name.as_str())
-        .collect::<Vec<_>>()
-        .join(", ");
+    let clusters_list = comment_list(config.clusters.iter().map(|c| c.name.as_str()));
+    let users_list = comment_list(config.users.iter().map(|u| u.name.as_str()));
 
     let header_comment = format!(
         "# Editing Kubernetes context: {}\n\
          # Make your changes and save the file.\n\
          # The name fields must remain consistent across entries.\n\
-         # Available clusters: {}\n\
-         # Available users: {}\n\
+         # Available clusters:\n{}\
+         # Available users:\n{}\
          #\n\
          # This contains the full context, cluster, and user entries from your ~/.kube/config file.\n\
          # All changes here will be merged back into your config.\n\n",
-        selected_context_name, clusters_str, users_str
+        selected_context_name, clusters_list, users_list
     );
 
     let yaml_config = serde_yaml::to_string(&config).context("Failed to convert config to YAML")?;
@@ -156,65 +222,12 @@ pub fn edit_context(context_name: Option<String>) -> Result<()> {
     let temp_file_path = temp_dir.path().join("kube_context_edit.yaml");
     fs::write(&temp_file_path, combined_yaml)?;
 
-    let editor = env::var("EDITOR")
-        .or_else(|_| env::var("VISUAL"))
-        .unwrap_or_else(|_| {
-            if cfg!(target_os = "windows") {
-                "notepad".to_string()
-            } else {
-                "vi".to_string()
-            }
-        });
-
-    let is_gui_editor = editor.contains("code") || editor.contains("vscode");
-
-    eprintln!(
-        "Opening context configuration in your editor... ({})",
-        editor
-    );
-
-    let status = if is_gui_editor {
-        let mut cmd = Command::new(&editor);
-        cmd.arg(&temp_file_path);
-        let _ = cmd.spawn()?;
-
-        eprintln!("Editor has been launched. Press Enter when you've finished editing.");
-        let mut input = String::new();
-        std::io::stdin().read_line(&mut input)?;
-        None // GUI editors don't return a meaningful exit status
-    } else {
-        Some(
-            Command::new(&editor)
-                .arg(&temp_file_path)
-                .status()
-                .with_context(|| {
-                    format!("Failed to open editor for {}", temp_file_path.display())
-                })?,
-        )
-    };
-
-    if let Some(s) = status
-        && !s.success()
-    {
-        anyhow::bail!("Editor exited with non-zero status code");
-    }
-
-    debug!("Editor process completed successfully");
+    open_in_editor(&temp_file_path, "context configuration")?;
 
     let edited_content = fs::read_to_string(&temp_file_path)
         .with_context(|| format!("Failed to read edited file: {}", temp_file_path.display()))?;
 
-    let content_without_comments = edited_content
-        .lines()
-        .filter(|line| !line.trim_start().starts_with('#'))
-        .collect::<Vec<_>>()
-        .join("\n");
-
-    let entries: Vec<&str> = content_without_comments
-        .split("\n\n")
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .collect();
+    let entries = split_edited_entries(&edited_content);
 
     if entries.is_empty() || entries.len() > 3 {
         anyhow::bail!(
@@ -231,7 +244,7 @@ pub fn edit_context(context_name: Option<String>) -> Result<()> {
 
     for entry in entries {
         let entry_yaml: serde_yaml::Value =
-            serde_yaml::from_str(entry).context("Failed to parse edited YAML entry")?;
+            serde_yaml::from_str(&entry).context("Failed to parse edited YAML entry")?;
 
         if let serde_yaml::Value::Mapping(map) = &entry_yaml {
             if let Some(serde_yaml::Value::Mapping(_context_map)) =
@@ -284,6 +297,19 @@ pub fn edit_context(context_name: Option<String>) -> Result<()> {
     }
 
     debug!("Successfully identified edited entries");
+
+    if config_fingerprint(&config_path)? != fingerprint {
+        eprintln!(
+            "{} {} was changed by another process while the editor was open",
+            style("Warning:").yellow().bold(),
+            config_path.display()
+        );
+        if !confirm("Apply your edits on top of the changed file?", false, false)? {
+            status!("Edit cancelled, your changes were not saved");
+            return Ok(());
+        }
+    }
+
     let mut modified_config = load_kube_config()?;
 
     if let Some(edited_context) = edited_context_value
@@ -294,6 +320,11 @@ pub fn edit_context(context_name: Option<String>) -> Result<()> {
             .iter()
             .position(|c| c.name == selected_context_name)
     {
+        if let Some(namespace) = &edited_context_entry.context.namespace
+            && modified_config.contexts[index].context.namespace.as_ref() != Some(namespace)
+        {
+            check_namespace_name(namespace, force)?;
+        }
         modified_config.contexts[index] = edited_context_entry;
         debug!("Updated context entry in config");
     }
@@ -322,11 +353,485 @@ pub fn edit_context(context_name: Option<String>) -> Result<()> {
         debug!("Updated user entry in config");
     }
 
-    save_kube_config(&modified_config)?;
-    eprintln!(
+    save_edited_context(
+        &modified_config,
+        &selected_context_name,
+        cluster_name,
+        user_name,
+    )
+}
+
+/// Apply `--set-*` changes to a context, returning the cluster and user it then uses
+///
+/// The cluster and user are switched first, so a new server applies to the cluster
+/// the context ends up using. They must already exist in the config.
+fn apply_field_changes(
+    config: &mut KubeConfig,
+    context_name: &str,
+    changes: FieldChanges,
+    force: bool,
+) -> Result<(String, String)> {
+    let FieldChanges {
+        server,
+        namespace,
+        cluster,
+        user,
+        ca_file,
+    } = changes;
+    // Read the CA before touching the config so a bad file changes nothing
+    let ca_data = ca_file.as_deref().map(read_ca_file).transpose()?;
+
+    let index = config
+        .contexts
+        .iter()
+        .position(|c| c.name == context_name)
+        .ok_or_else(|| KhelpError::ContextNotFound(context_name.to_string()))?;
+
+    if let Some(cluster) = cluster {
+        if !config.clusters.iter().any(|c| c.name == cluster) {
+            anyhow::bail!(KhelpError::ClusterNotFound {
+                cluster,
+                context: context_name.to_string(),
+            });
+        }
+        debug!(
+            "Pointing context '{}' at cluster '{}'",
+            context_name, cluster
+        );
+        config.contexts[index].context.cluster = cluster;
+    }
+    if let Some(user) = user {
+        if !config.users.iter().any(|u| u.name == user) {
+            anyhow::bail!(KhelpError::UserNotFound {
+                user,
+                context: context_name.to_string(),
+            });
+        }
+        debug!("Pointing context '{}' at user '{}'", context_name, user);
+        config.contexts[index].context.user = user;
+    }
+    if let Some(namespace) = namespace {
+        if config.contexts[index].context.namespace.as_ref() != Some(&namespace) {
+            check_namespace_name(&namespace, force)?;
+        }
+        config.contexts[index].context.namespace = Some(namespace);
+    }
+
+    let context = &config.contexts[index].context;
+    let (cluster_name, user_name) = (context.cluster.clone(), context.user.clone());
+    if server.is_some() || ca_data.is_some() {
+        let cluster = config
+            .clusters
+            .iter_mut()
+            .find(|c| c.name == cluster_name)
+            .ok_or_else(|| KhelpError::ClusterNotFound {
+                cluster: cluster_name.clone(),
+                context: context_name.to_string(),
+            })?;
+        if let Some(server) = server {
+            debug!("Setting server of cluster '{}' to {}", cluster_name, server);
+            cluster.cluster.server = server;
+        }
+        if let Some(ca_data) = ca_data {
+            debug!("Embedding a new CA in cluster '{}'", cluster_name);
+            cluster.cluster.certificate_authority_data = Some(ca_data);
+            // An embedded CA takes precedence, so a leftover path would only mislead
+            cluster.cluster.certificate_authority = None;
+        }
+    }
+
+    Ok((cluster_name, user_name))
+}
+
+/// Read a PEM CA bundle, returning it base64-encoded for certificate-authority-data
+fn read_ca_file(path: &Path) -> Result<String> {
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD;
+
+    let pem =
+        fs::read(path).with_context(|| format!("Failed to read CA file: {}", path.display()))?;
+    parse_certificates(&pem)
+        .with_context(|| format!("{} is not a PEM certificate", path.display()))?;
+    Ok(STANDARD.encode(pem))
+}
+
+/// Check an edited context, cluster, and user, then save the config
+fn save_edited_context(
+    modified_config: &KubeConfig,
+    context_name: &str,
+    cluster_name: &str,
+    user_name: &str,
+) -> Result<()> {
+    // Only the edited entries are worth checking
+    let edited_entries = KubeConfig {
+        contexts: modified_config
+            .contexts
+            .iter()
+            .filter(|c| c.name == context_name)
+            .cloned()
+            .collect(),
+        clusters: modified_config
+            .clusters
+            .iter()
+            .filter(|c| c.name == cluster_name)
+            .cloned()
+            .collect(),
+        users: modified_config
+            .users
+            .iter()
+            .filter(|u| u.name == user_name)
+            .cloned()
+            .collect(),
+        ..KubeConfig::default()
+    };
+    let empty_fields = find_empty_fields(&edited_entries);
+    if !empty_fields.is_empty() {
+        anyhow::bail!(
+            "Required fields are empty, your changes were not saved:\n  - {}",
+            empty_fields.join("\n  - ")
+        );
+    }
+    warn_conflicting_fields(&edited_entries);
+
+    save_kube_config(modified_config)?;
+    status!(
         "Context '{}' configuration updated successfully",
-        style(&selected_context_name).green().bold()
+        style(context_name).green().bold()
+    );
+
+    Ok(())
+}
+
+/// Edit the whole kubeconfig in the user's preferred editor
+///
+/// The edited file must parse, every reference in it must resolve, and namespaces it
+/// changes must be valid names (unless force is set) before it is saved. A rejected
+/// edit can be reopened to fix it; otherwise the edited file is kept so the changes
+/// aren't lost.
+pub fn edit_full_config(force: bool) -> Result<()> {
+    let config_path = get_kube_config_path()?;
+    let config = load_kube_config()?;
+    let fingerprint = config_fingerprint(&config_path)?;
+
+    let header_comment = format!(
+        "# Editing the full kubeconfig: {}\n\
+         # Make your changes and save the file.\n\
+         # The file must be a valid kubeconfig, and every context must reference\n\
+         # an existing cluster and user.\n\n",
+        config_path.display()
+    );
+    let yaml_config = serde_yaml::to_string(&config).context("Failed to convert config to YAML")?;
+
+    let temp_file = tempfile::Builder::new()
+        .prefix("khelp-config-")
+        .suffix(".yaml")
+        .tempfile()?;
+    fs::write(
+        temp_file.path(),
+        format!("{}{}", header_comment, yaml_config),
+    )?;
+
+    loop {
+        open_in_editor(temp_file.path(), "the full config")?;
+
+        let edited = read_edited_config(temp_file.path()).and_then(|edited_config| {
+            check_changed_namespaces(&config, &edited_config, force)?;
+            Ok(edited_config)
+        });
+        let error = match edited {
+            Ok(edited_config) => {
+                if config_fingerprint(&config_path)? != fingerprint {
+                    eprintln!(
+                        "{} {} was changed by another process while the editor was open",
+                        style("Warning:").yellow().bold(),
+                        config_path.display()
+                    );
+                    if !confirm("Overwrite it with your edited config?", false, false)? {
+                        let (_, kept_path) =
+                            temp_file.keep().context("Failed to keep the edited file")?;
+                        status!(
+                            "Edit cancelled, your changes were kept in {}",
+                            kept_path.display()
+                        );
+                        return Ok(());
+                    }
+                }
+
+                match save_kube_config(&edited_config) {
+                    Ok(()) => {
+                        warn_conflicting_fields(&edited_config);
+                        break;
+                    }
+                    Err(e) => anyhow::Error::from(e),
+                }
+            }
+            Err(e) => e,
+        };
+
+        eprintln!(
+            "{} the edited config was rejected: {:#}",
+            style("Warning:").yellow().bold(),
+            error
+        );
+        // Never throw the edits away just because we can't ask
+        if !confirm("Reopen the editor to fix it?", true, false).unwrap_or(false) {
+            let (_, kept_path) = temp_file.keep().context("Failed to keep the edited file")?;
+            anyhow::bail!(
+                "Config was not saved, your changes were kept in {}",
+                kept_path.display()
+            );
+        }
+    }
+
+    status!(
+        "{} Saved {}",
+        style("✓").green(),
+        style(config_path.display()).green().bold()
     );
 
     Ok(())
 }
+
+/// Check the namespaces of contexts whose namespace the edit added or changed
+fn check_changed_namespaces(original: &KubeConfig, edited: &KubeConfig, force: bool) -> Result<()> {
+    for context in &edited.contexts {
+        let Some(namespace) = &context.context.namespace else {
+            continue;
+        };
+        let unchanged = original
+            .contexts
+            .iter()
+            .any(|c| c.name == context.name && c.context.namespace.as_ref() == Some(namespace));
+        if !unchanged {
+            check_namespace_name(namespace, force)
+                .with_context(|| format!("Context '{}' has an invalid namespace", context.name))?;
+        }
+    }
+    Ok(())
+}
+
+/// Render names as comment lines, one `#   - name` bullet per line
+///
+/// Names like EKS ARNs are long and full of punctuation, so a comma-joined list is
+/// hard to read.
+fn comment_list<'a>(names: impl Iterator<Item = &'a str>) -> String {
+    names.map(|name| format!("#   - {}\n", name)).collect()
+}
+
+/// Split the edited context file into its blank-line separated entries, without comments
+fn split_edited_entries(content: &str) -> Vec<String> {
+    let content_without_comments = normalize_line_endings(content)
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    content_without_comments
+        .split("\n\n")
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// Parse an edited kubeconfig, rejecting it if any of its references don't resolve
+/// or a required field is empty
+fn read_edited_config(path: &Path) -> Result<KubeConfig> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read edited file: {}", path.display()))?;
+    let config: KubeConfig = serde_yaml::from_str(&normalize_line_endings(&content))
+        .context("File is not a valid kubeconfig")?;
+
+    let problems = find_reference_problems(&config);
+    if !problems.is_empty() {
+        anyhow::bail!("Unresolved references:\n  - {}", problems.join("\n  - "));
+    }
+    let empty_fields = find_empty_fields(&config);
+    if !empty_fields.is_empty() {
+        anyhow::bail!(
+            "Required fields are empty:\n  - {}",
+            empty_fields.join("\n  - ")
+        );
+    }
+
+    Ok(config)
+}
+
+/// Open a file in the user's preferred editor and wait until they are done
+fn open_in_editor(path: &Path, what: &str) -> Result<()> {
+    let editor = env::var("EDITOR")
+        .or_else(|_| env::var("VISUAL"))
+        .unwrap_or_else(|_| {
+            if cfg!(target_os = "windows") {
+                "notepad".to_string()
+            } else {
+                "vi".to_string()
+            }
+        });
+
+    let is_gui_editor = editor.contains("code") || editor.contains("vscode");
+
+    status!("Opening {} in your editor... ({})", what, editor);
+
+    let status = if is_gui_editor {
+        let mut cmd = Command::new(&editor);
+        cmd.arg(path);
+        let _ = cmd.spawn()?;
+
+        eprintln!("Editor has been launched. Press Enter when you've finished editing.");
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input)?;
+        None // GUI editors don't return a meaningful exit status
+    } else {
+        Some(
+            Command::new(&editor)
+                .arg(path)
+                .status()
+                .with_context(|| format!("Failed to open editor for {}", path.display()))?,
+        )
+    };
+
+    if let Some(s) = status
+        && !s.success()
+    {
+        anyhow::bail!("Editor exited with non-zero status code");
+    }
+
+    debug!("Editor process completed successfully");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_comment_list_puts_each_name_on_its_own_line() {
+        let list =
+            comment_list(["arn:aws:eks:us-east-1:123456789012:cluster/prod", "a, b"].into_iter());
+        assert_eq!(
+            list,
+            "#   - arn:aws:eks:us-east-1:123456789012:cluster/prod\n#   - a, b\n"
+        );
+        // The list stays a comment, so it never ends up in the parsed entries
+        assert!(split_edited_entries(&list).is_empty());
+    }
+
+    #[test]
+    fn test_split_edited_entries_handles_crlf() {
+        let edited = "# Editing Kubernetes context: dev\n\n\
+                      # Context entry\nname: dev\ncontext:\n  cluster: c\n  user: u\n\n\n\
+                      # Cluster entry\nname: c\ncluster:\n  server: https://c.example.com\n";
+        let crlf = edited.replace('\n', "\r\n");
+
+        let entries = split_edited_entries(&crlf);
+        assert_eq!(entries, split_edited_entries(edited));
+        assert_eq!(entries.len(), 2);
+
+        let context: crate::config::kubernetes::ContextEntry =
+            serde_yaml::from_str(&entries[0]).expect("Failed to parse context entry");
+        assert_eq!(context.name, "dev");
+        assert_eq!(context.context.user, "u");
+    }
+
+    #[test]
+    fn test_apply_field_changes() {
+        let mut config: KubeConfig = serde_yaml::from_str(
+            r#"
+clusters:
+- name: old
+  cluster: {server: "https://old.example.com"}
+- name: new
+  cluster: {server: "https://new.example.com"}
+users:
+- name: alice
+  user: {token: a}
+- name: bob
+  user: {token: b}
+contexts:
+- name: dev
+  context: {cluster: old, user: alice}
+"#,
+        )
+        .expect("Failed to parse test config");
+
+        let changes = FieldChanges {
+            server: Some("https://moved.example.com".to_string()),
+            namespace: Some("apps".to_string()),
+            cluster: Some("new".to_string()),
+            user: Some("bob".to_string()),
+            ca_file: None,
+        };
+        let (cluster, user) =
+            apply_field_changes(&mut config, "dev", changes, false).expect("Failed to apply");
+        assert_eq!((cluster.as_str(), user.as_str()), ("new", "bob"));
+
+        let context = &config.contexts[0].context;
+        assert_eq!(context.namespace.as_deref(), Some("apps"));
+        // The server change lands on the cluster the context was switched to
+        assert_eq!(config.clusters[0].cluster.server, "https://old.example.com");
+        assert_eq!(
+            config.clusters[1].cluster.server,
+            "https://moved.example.com"
+        );
+
+        let missing = FieldChanges {
+            user: Some("nobody".to_string()),
+            ..FieldChanges::default()
+        };
+        assert!(apply_field_changes(&mut config, "dev", missing, false).is_err());
+    }
+
+    #[test]
+    fn test_apply_field_changes_embeds_ca_file() {
+        use crate::test_support::TEST_CERT;
+
+        let mut config: KubeConfig = serde_yaml::from_str(
+            r#"
+clusters:
+- name: dev
+  cluster: {server: "https://dev.example.com", certificate-authority: /old/ca.crt}
+users:
+- name: alice
+  user: {token: a}
+contexts:
+- name: dev
+  context: {cluster: dev, user: alice}
+"#,
+        )
+        .expect("Failed to parse test config");
+        let temp_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+
+        let not_pem = temp_dir.path().join("ca.txt");
+        fs::write(&not_pem, "not a certificate").expect("Failed to write file");
+        let changes = FieldChanges {
+            ca_file: Some(not_pem),
+            ..FieldChanges::default()
+        };
+        assert!(apply_field_changes(&mut config, "dev", changes, false).is_err());
+        assert!(config.clusters[0].cluster.certificate_authority.is_some());
+
+        let ca_file = temp_dir.path().join("ca.crt");
+        fs::write(&ca_file, TEST_CERT).expect("Failed to write file");
+        let changes = FieldChanges {
+            ca_file: Some(ca_file),
+            ..FieldChanges::default()
+        };
+        apply_field_changes(&mut config, "dev", changes, false).expect("Failed to apply");
+
+        use base64::Engine;
+        let cluster = &config.clusters[0].cluster;
+        assert_eq!(cluster.certificate_authority, None);
+        let data = cluster
+            .certificate_authority_data
+            .as_ref()
+            .expect("No CA data");
+        assert_eq!(
+            base64::engine::general_purpose::STANDARD
+                .decode(data)
+                .expect("Invalid base64"),
+            TEST_CERT.as_bytes()
+        );
+    }
+}
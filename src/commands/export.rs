@@ -1,17 +1,77 @@
 use anyhow::{Context, Result};
+use console::style;
 use dialoguer::{MultiSelect, theme::ColorfulTheme};
+use log::debug;
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 
+use crate::cli::ExportFormat;
+use crate::commands::add::merge_external_config;
+use crate::commands::output::ensure_can_prompt;
+use crate::commands::output::status;
 use crate::config::kubernetes::KubeConfig;
-use crate::config::operations::load_kube_config;
+use crate::config::operations::{
+    load_kube_config, load_kube_config_or_default_from, save_kube_config_to,
+};
+use crate::error::KhelpError;
 
-/// Export one or more Kubernetes contexts to stdout
+/// How `export` shapes the exported config
+pub struct ExportOptions {
+    /// Replace user credentials with a placeholder
+    pub redact: bool,
+    /// Leave out contexts with a missing cluster or user, with a warning, instead of failing
+    pub skip_broken: bool,
+    /// Keep the original current-context when it is exported
+    pub keep_current: bool,
+    /// Leave out fields that only restate defaults
+    pub minify: bool,
+    /// Sort clusters, contexts, and users by name for byte-stable output
+    pub sorted: bool,
+    /// Write each context to its own file in this directory, with itself as the
+    /// current-context
+    pub output_dir: Option<PathBuf>,
+    /// Add the exported entries to this file (created if missing), skipping names it
+    /// already has
+    pub merge_into: Option<PathBuf>,
+    /// Format the config is printed in
+    pub format: ExportFormat,
+}
+
+/// Export one or more Kubernetes contexts to stdout, as YAML or JSON
 ///
 /// If context_names is provided, exports those contexts directly.
+/// If current is true, exports the active context.
 /// Otherwise, presents an interactive menu to select contexts.
 /// The output can be redirected to a file.
-pub fn export_contexts(context_names: Vec<String>) -> Result<()> {
+pub fn export_contexts(
+    context_names: Vec<String>,
+    current: bool,
+    options: ExportOptions,
+) -> Result<()> {
+    let ExportOptions {
+        redact,
+        skip_broken,
+        keep_current,
+        minify,
+        sorted,
+        output_dir,
+        merge_into,
+        format,
+    } = options;
+
     let full_config = load_kube_config()?;
 
+    let context_names = if current {
+        if full_config.current_context.is_empty() {
+            anyhow::bail!("No current context is set");
+        }
+        vec![full_config.current_context.clone()]
+    } else {
+        context_names
+    };
+
     let selected_context_names = if context_names.is_empty() {
         // Interactive selection
         let context_list: Vec<&str> = full_config
@@ -28,6 +88,7 @@ pub fn export_contexts(context_names: Vec<String>) -> Result<()> {
             // Only one context, just select it
             vec![context_list[0].to_string()]
         } else {
+            ensure_can_prompt()?;
             let selections = MultiSelect::with_theme(&ColorfulTheme::default())
                 .with_prompt("Select contexts to export (Space to select, Enter to confirm)")
                 .items(&context_list)
@@ -47,7 +108,7 @@ pub fn export_contexts(context_names: Vec<String>) -> Result<()> {
         // Validate all provided context names exist
         for name in &context_names {
             if !full_config.contexts.iter().any(|c| c.name == *name) {
-                anyhow::bail!("Context '{}' not found", name);
+                anyhow::bail!(KhelpError::ContextNotFound(name.clone()));
             }
         }
         context_names
@@ -63,33 +124,43 @@ pub fn export_contexts(context_names: Vec<String>) -> Result<()> {
             .contexts
             .iter()
             .find(|c| c.name == *context_name)
-            .ok_or_else(|| anyhow::anyhow!("Context '{}' not found", context_name))?;
+            .ok_or_else(|| KhelpError::ContextNotFound(context_name.clone()))?;
 
-        let cluster_name = &context.context.cluster;
         let cluster = full_config
             .clusters
             .iter()
-            .find(|c| c.name == *cluster_name)
-            .ok_or_else(|| {
-                anyhow::anyhow!(
-                    "Cluster '{}' not found for context '{}'",
-                    cluster_name,
-                    context_name
-                )
-            })?;
-
-        let user_name = &context.context.user;
+            .find(|c| c.name == context.context.cluster);
         let user = full_config
             .users
             .iter()
-            .find(|u| u.name == *user_name)
-            .ok_or_else(|| {
-                anyhow::anyhow!(
-                    "User '{}' not found for context '{}'",
-                    user_name,
-                    context_name
-                )
-            })?;
+            .find(|u| u.name == context.context.user);
+
+        let (cluster, user) = match (cluster, user) {
+            (Some(cluster), Some(user)) => (cluster, user),
+            (cluster, _) => {
+                let problem = if cluster.is_none() {
+                    KhelpError::ClusterNotFound {
+                        cluster: context.context.cluster.clone(),
+                        context: context_name.clone(),
+                    }
+                } else {
+                    KhelpError::UserNotFound {
+                        user: context.context.user.clone(),
+                        context: context_name.clone(),
+                    }
+                };
+
+                if !skip_broken {
+                    anyhow::bail!(problem);
+                }
+                eprintln!(
+                    "{} {}, skipping it",
+                    style("Warning:").yellow().bold(),
+                    problem
+                );
+                continue;
+            }
+        };
 
         // Add if not already present (contexts might share clusters/users)
         if !contexts
@@ -112,10 +183,48 @@ pub fn export_contexts(context_names: Vec<String>) -> Result<()> {
         }
     }
 
-    // Use the first selected context as the current-context
-    let current_context = selected_context_names.first().cloned().unwrap_or_default();
+    if contexts.is_empty() {
+        anyhow::bail!("None of the selected contexts can be exported");
+    }
+
+    if sorted {
+        // Selection order depends on the command line, so sort for reproducible output
+        contexts.sort_by(|a, b| a.name.cmp(&b.name));
+        clusters.sort_by(|a, b| a.name.cmp(&b.name));
+        users.sort_by(|a, b| a.name.cmp(&b.name));
+    }
 
-    let config = KubeConfig {
+    if redact {
+        debug!("Redacting credentials for {} user(s)", users.len());
+        for user in &mut users {
+            user.user.redact();
+        }
+    }
+
+    // Use the first exported context as the current-context, unless the original
+    // current-context should be kept and is part of the export
+    let current_context = if keep_current
+        && contexts
+            .iter()
+            .any(|c| c.name == full_config.current_context)
+    {
+        full_config.current_context.clone()
+    } else {
+        contexts[0].name.clone()
+    };
+
+    if let Some(output_dir) = output_dir {
+        return export_to_dir(
+            &full_config,
+            contexts,
+            &clusters,
+            &users,
+            minify,
+            &output_dir,
+        );
+    }
+
+    let mut config = KubeConfig {
         api_version: full_config.api_version.clone(),
         clusters,
         contexts,
@@ -125,9 +234,224 @@ pub fn export_contexts(context_names: Vec<String>) -> Result<()> {
         users,
     };
 
-    let yaml = serde_yaml::to_string(&config).context("Failed to serialize config to YAML")?;
+    if minify {
+        debug!("Minifying exported config");
+        config.minify();
+    }
+
+    if let Some(path) = merge_into {
+        return merge_into_file(config, &path);
+    }
+
+    match format {
+        ExportFormat::Yaml => {
+            let yaml =
+                serde_yaml::to_string(&config).context("Failed to serialize config to YAML")?;
+            write_stdout(&yaml)
+        }
+        ExportFormat::Json => {
+            let json = serde_json::to_string_pretty(&config)
+                .context("Failed to serialize config to JSON")?;
+            write_stdout(&json)
+        }
+    }
+}
+
+/// Write the exported config to stdout and flush it, so a pipe such as
+/// `khelp export | khelp add -` always receives the whole document
+///
+/// A reader that stops early (like `head`) isn't an error.
+pub fn write_stdout(text: &str) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    match writeln!(stdout, "{}", text).and_then(|()| stdout.flush()) {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => {
+            Err(e).context("Failed to write the exported config to stdout")
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Add an exported config's entries to another kubeconfig file, like `add` does
+///
+/// Entries whose names the file already has are skipped. The file's current-context is
+/// kept, or set to the exported one if it has none.
+fn merge_into_file(exported: KubeConfig, path: &Path) -> Result<()> {
+    let mut target = load_kube_config_or_default_from(path)?;
+    debug!(
+        "Merging export into {} with {} contexts",
+        path.display(),
+        target.contexts.len()
+    );
 
-    println!("{}", yaml);
+    let current_context = exported.current_context.clone();
+    let (summary, _) = merge_external_config(&mut target, exported, false, false);
+    if !summary.has_changes() {
+        summary.print_summary();
+        status!(
+            "\nNothing to add, {} already has every exported entry",
+            style(path.display()).cyan()
+        );
+        return Ok(());
+    }
+
+    if target.current_context.is_empty() {
+        target.current_context = current_context;
+    }
+    save_kube_config_to(&target, path)?;
+
+    summary.print_summary();
+    status!("\nWrote merged config to {}", style(path.display()).cyan());
 
     Ok(())
 }
+
+/// Write each context, with its cluster and user, to `<dir>/<context-name>.yaml`
+fn export_to_dir(
+    full_config: &KubeConfig,
+    contexts: Vec<crate::config::kubernetes::ContextEntry>,
+    clusters: &[crate::config::kubernetes::ClusterEntry],
+    users: &[crate::config::kubernetes::UserEntry],
+    minify: bool,
+    output_dir: &Path,
+) -> Result<()> {
+    fs::create_dir_all(output_dir)
+        .with_context(|| format!("Failed to create directory: {}", output_dir.display()))?;
+
+    let mut taken_file_names = HashSet::new();
+    for context in contexts {
+        let file_name = unique_file_name(&context.name, &mut taken_file_names);
+        let path = output_dir.join(&file_name);
+
+        let mut config = KubeConfig {
+            api_version: full_config.api_version.clone(),
+            clusters: clusters
+                .iter()
+                .filter(|c| c.name == context.context.cluster)
+                .cloned()
+                .collect(),
+            current_context: context.name.clone(),
+            kind: full_config.kind.clone(),
+            preferences: full_config.preferences.clone(),
+            users: users
+                .iter()
+                .filter(|u| u.name == context.context.user)
+                .cloned()
+                .collect(),
+            contexts: vec![context],
+        };
+
+        if minify {
+            config.minify();
+        }
+
+        debug!(
+            "Exporting context '{}' to {}",
+            config.current_context,
+            path.display()
+        );
+        save_kube_config_to(&config, &path)?;
+        status!(
+            "{} Exported context {} to {}",
+            style("✓").green(),
+            style(&config.current_context).green().bold(),
+            path.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// A `.yaml` file name for a context, unique among the names already taken
+///
+/// Characters that aren't safe in file names on every platform, like the `/` and
+/// `:` in EKS ARNs, are replaced with `_`.
+fn unique_file_name(context_name: &str, taken: &mut HashSet<String>) -> String {
+    let mut stem: String = context_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    // Avoid hidden files and the `.`/`..` directory entries
+    if stem.is_empty() || stem.starts_with('.') {
+        stem.insert(0, '_');
+    }
+
+    let mut file_name = format!("{}.yaml", stem);
+    let mut suffix = 2;
+    while !taken.insert(file_name.to_lowercase()) {
+        file_name = format!("{}-{}.yaml", stem, suffix);
+        suffix += 1;
+    }
+    file_name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unique_file_name() {
+        let mut taken = HashSet::new();
+
+        assert_eq!(
+            unique_file_name(
+                "arn:aws:eks:us-east-1:123456789012:cluster/prod",
+                &mut taken
+            ),
+            "arn_aws_eks_us-east-1_123456789012_cluster_prod.yaml"
+        );
+        assert_eq!(unique_file_name("dev", &mut taken), "dev.yaml");
+        assert_eq!(unique_file_name("..", &mut taken), "_...yaml");
+        // Names that sanitize to the same file (case-insensitively) get a suffix
+        assert_eq!(unique_file_name("team/a", &mut taken), "team_a.yaml");
+        assert_eq!(unique_file_name("team:a", &mut taken), "team_a-2.yaml");
+        assert_eq!(unique_file_name("Team_A", &mut taken), "Team_A-3.yaml");
+    }
+
+    #[test]
+    fn test_merge_into_file_keeps_existing_entries() {
+        let exported = |context: &str, server: &str| -> KubeConfig {
+            serde_yaml::from_str(&format!(
+                r#"
+apiVersion: v1
+kind: Config
+current-context: {context}
+clusters:
+- name: {context}-cluster
+  cluster:
+    server: {server}
+contexts:
+- name: {context}
+  context:
+    cluster: {context}-cluster
+    user: {context}-user
+users:
+- name: {context}-user
+  user:
+    token: secret
+"#
+            ))
+            .expect("Failed to parse test config")
+        };
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = dir.path().join("shared.yaml");
+
+        merge_into_file(exported("dev", "https://dev:6443"), &path).expect("first merge");
+        merge_into_file(exported("prod", "https://prod:6443"), &path).expect("second merge");
+        // An entry the file already has is skipped, not overwritten
+        merge_into_file(exported("dev", "https://changed:6443"), &path).expect("third merge");
+
+        let merged: KubeConfig =
+            serde_yaml::from_str(&fs::read_to_string(&path).expect("Failed to read merged file"))
+                .expect("Failed to parse merged file");
+        let names: Vec<&str> = merged.contexts.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["dev", "prod"]);
+        assert_eq!(merged.current_context, "dev");
+        assert_eq!(merged.clusters[0].cluster.server, "https://dev:6443");
+    }
+}
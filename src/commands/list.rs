@@ -1,7 +1,14 @@
-use crate::cli::OutputFormat;
-use crate::config::kubernetes::KubeConfig;
+use crate::cli::{GroupKey, OutputFormat, SortKey};
+use crate::commands::cert_info::{CredentialHealth, credential_health};
+use crate::commands::output::warn_missing_current_context;
+use crate::config::analysis::{find_duplicate_contexts, find_namespace_deviations};
+use crate::config::kubernetes::{ContextEntry, KubeConfig};
+use crate::config::usage::{load_usage_from, usage_path};
 use console::style;
 use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use x509_parser::time::ASN1Time;
 
 #[derive(Serialize)]
 struct ContextInfo {
@@ -11,39 +18,388 @@ struct ContextInfo {
     #[serde(skip_serializing_if = "Option::is_none")]
     namespace: Option<String>,
     current: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    credentials: Option<&'static str>,
+}
+
+/// Credential health of each user the contexts refer to, keyed by user name
+///
+/// Users shared by several contexts are only decoded once.
+type HealthByUser = BTreeMap<String, CredentialHealth>;
+
+/// Check the client certificate of every user referenced by the given contexts
+fn credential_health_by_user(config: &KubeConfig, contexts: &[&ContextEntry]) -> HealthByUser {
+    let now = ASN1Time::now().timestamp();
+    let mut health = HealthByUser::new();
+    for context in contexts {
+        let user_name = &context.context.user;
+        if !health.contains_key(user_name) {
+            let user = config.users.iter().find(|u| &u.name == user_name);
+            health.insert(
+                user_name.clone(),
+                credential_health(user.map(|u| &u.user), now),
+            );
+        }
+    }
+    health
+}
+
+/// Look up the credential health of a context's user, when it was computed
+fn health_of(health: Option<&HealthByUser>, context: &ContextEntry) -> Option<CredentialHealth> {
+    health?.get(&context.context.user).copied()
+}
+
+/// Warn about contexts that only differ by name, suggesting which ones to remove
+fn warn_duplicate_contexts(config: &KubeConfig) {
+    for names in find_duplicate_contexts(config) {
+        let quoted: Vec<String> = names.iter().map(|name| format!("'{}'", name)).collect();
+        eprintln!(
+            "{} contexts {} use the same cluster, user, and namespace",
+            style("Warning:").yellow().bold(),
+            quoted.join(", ")
+        );
+        eprintln!(
+            "  Consider keeping one of them: khelp delete {}",
+            names[1..].join(" ")
+        );
+    }
+}
+
+/// Load when each context was last switched to, warning instead of failing
+fn load_last_used() -> BTreeMap<String, u64> {
+    let Some(path) = usage_path() else {
+        return BTreeMap::new();
+    };
+    load_usage_from(&path).unwrap_or_else(|e| {
+        eprintln!(
+            "{} could not read context usage log: {:#}",
+            style("Warning:").yellow().bold(),
+            e
+        );
+        BTreeMap::new()
+    })
+}
+
+/// Order contexts for display, falling back to file order when no sort key is given
+///
+/// last_used maps context names to when they were last switched to, for `SortKey::Recent`.
+fn sorted_contexts<'a>(
+    config: &'a KubeConfig,
+    sort: Option<&SortKey>,
+    last_used: &BTreeMap<String, u64>,
+) -> Vec<&'a ContextEntry> {
+    let mut contexts: Vec<&ContextEntry> = config.contexts.iter().collect();
+
+    let Some(sort) = sort else {
+        return contexts;
+    };
+
+    if let SortKey::Recent = sort {
+        // None sorts before Some, so reversing puts never-used contexts last
+        contexts.sort_by_cached_key(|c| {
+            (
+                Reverse(last_used.get(&c.name).copied()),
+                c.name.to_lowercase(),
+            )
+        });
+        return contexts;
+    }
+
+    contexts.sort_by_cached_key(|c| {
+        let primary = match sort {
+            SortKey::Name | SortKey::Recent => String::new(),
+            SortKey::Cluster => c.context.cluster.to_lowercase(),
+            SortKey::Namespace => c
+                .context
+                .namespace
+                .as_deref()
+                .unwrap_or_default()
+                .to_lowercase(),
+        };
+        (primary, c.name.to_lowercase())
+    });
+
+    contexts
+}
+
+/// Print contexts with the name, namespace, and cluster padded into columns
+///
+/// With health, a CERT column shows each context's credential health.
+fn print_aligned_table(
+    config: &KubeConfig,
+    contexts: &[&ContextEntry],
+    health: Option<&HealthByUser>,
+) {
+    let namespaces: Vec<&str> = contexts
+        .iter()
+        .map(|c| c.context.namespace.as_deref().unwrap_or("-"))
+        .collect();
+
+    let name_width = contexts
+        .iter()
+        .map(|c| c.name.chars().count())
+        .chain(std::iter::once("NAME".len()))
+        .max()
+        .unwrap_or_default();
+    let namespace_width = namespaces
+        .iter()
+        .map(|n| n.chars().count())
+        .chain(std::iter::once("NAMESPACE".len()))
+        .max()
+        .unwrap_or_default();
+    let cluster_width = contexts
+        .iter()
+        .map(|c| c.context.cluster.chars().count())
+        .chain(std::iter::once("CLUSTER".len()))
+        .max()
+        .unwrap_or_default();
+
+    if health.is_some() {
+        println!(
+            "  {}  {}  {}  {}",
+            style(format!("{:<name_width$}", "NAME")).bold(),
+            style(format!("{:<namespace_width$}", "NAMESPACE")).bold(),
+            style(format!("{:<cluster_width$}", "CLUSTER")).bold(),
+            style("CERT").bold()
+        );
+    } else {
+        println!(
+            "  {}  {}  {}",
+            style(format!("{:<name_width$}", "NAME")).bold(),
+            style(format!("{:<namespace_width$}", "NAMESPACE")).bold(),
+            style("CLUSTER").bold()
+        );
+    }
+
+    for (context, namespace) in contexts.iter().zip(namespaces) {
+        let is_current = context.name == config.current_context;
+        let marker = if is_current {
+            style("*").green().bold()
+        } else {
+            style(" ").dim()
+        };
+        let name = style(format!("{:<name_width$}", context.name));
+        let name = if is_current {
+            name.green().bold()
+        } else {
+            name
+        };
+        let namespace = style(format!("{:<namespace_width$}", namespace)).cyan();
+
+        match health_of(health, context) {
+            Some(health) => println!(
+                "{} {}  {}  {:<cluster_width$}  {}",
+                marker,
+                name,
+                namespace,
+                context.context.cluster,
+                health.symbol()
+            ),
+            None => println!(
+                "{} {}  {}  {}",
+                marker, name, namespace, context.context.cluster
+            ),
+        }
+    }
+}
+
+/// Print a context in the compact listing, with its marker and namespace
+///
+/// A credential health marker goes before the name when health is given.
+fn print_compact_line(
+    config: &KubeConfig,
+    context: &ContextEntry,
+    indent: &str,
+    health: Option<CredentialHealth>,
+) {
+    let marker = if context.name == config.current_context {
+        style("*").green().bold()
+    } else {
+        style(" ").dim()
+    };
+
+    let namespace_info = if let Some(namespace) = &context.context.namespace {
+        format!(" (namespace: {})", style(namespace).cyan())
+    } else {
+        String::new()
+    };
+
+    let health_info = match health {
+        Some(health) => format!("{} ", health.symbol()),
+        None => String::new(),
+    };
+
+    println!(
+        "{}{} {}{}{}",
+        indent, marker, health_info, context.name, namespace_info
+    );
+}
+
+/// Print contexts in the porcelain format, which stays the same across versions
+///
+/// Each line is `<current>\t<name>\t<cluster>\t<user>\t<namespace>`, where `<current>`
+/// is `*` for the current context and empty otherwise. There is no header and no color.
+fn print_porcelain(config: &KubeConfig, contexts: &[&ContextEntry]) {
+    for context in contexts {
+        println!("{}", porcelain_line(config, context));
+    }
+}
+
+fn porcelain_line(config: &KubeConfig, context: &ContextEntry) -> String {
+    format!(
+        "{}\t{}\t{}\t{}\t{}",
+        if context.name == config.current_context {
+            "*"
+        } else {
+            ""
+        },
+        context.name,
+        context.context.cluster,
+        context.context.user,
+        context.context.namespace.as_deref().unwrap_or_default()
+    )
+}
+
+/// Print contexts nested under their cluster, clusters in order of first appearance
+fn print_grouped_by_cluster(
+    config: &KubeConfig,
+    contexts: &[&ContextEntry],
+    health: Option<&HealthByUser>,
+) {
+    let mut clusters: Vec<&str> = Vec::new();
+    for context in contexts {
+        if !clusters.contains(&context.context.cluster.as_str()) {
+            clusters.push(&context.context.cluster);
+        }
+    }
+
+    for cluster in clusters {
+        println!("{}", style(cluster).cyan().bold());
+        for context in contexts.iter().filter(|c| c.context.cluster == cluster) {
+            print_compact_line(config, context, "  ", health_of(health, context));
+        }
+    }
+}
+
+/// How `list` selects, orders, and prints contexts
+pub struct ListOptions<'a> {
+    /// Output format; the compact listing when unset
+    pub output: Option<OutputFormat>,
+    /// Sort key; file order when unset
+    pub sort: Option<SortKey>,
+    /// Nest the compact listing under group headers
+    pub group_by: Option<GroupKey>,
+    /// Only print the current context's line
+    pub current_only: bool,
+    /// Also show the health of each context's client certificate
+    pub wide: bool,
+    /// Use the stable script format from `print_porcelain` instead of `output`
+    pub porcelain: bool,
+    /// Namespaces contexts are expected to use, keyed by context name glob
+    pub namespace_rules: &'a BTreeMap<String, String>,
+}
+
+/// Warn about contexts whose namespace differs from the one the settings' rules expect
+fn warn_namespace_deviations(config: &KubeConfig, rules: &BTreeMap<String, String>) {
+    let deviations = find_namespace_deviations(config, rules);
+    for deviation in &deviations {
+        eprintln!(
+            "{} context '{}' uses namespace '{}', but namespace_rules expect '{}'",
+            style("Warning:").yellow().bold(),
+            deviation.context,
+            deviation.actual.as_deref().unwrap_or("default"),
+            deviation.expected
+        );
+    }
+    if !deviations.is_empty() {
+        eprintln!("  Apply the rules with: khelp namespace --apply-rules");
+    }
 }
 
 /// List all available Kubernetes contexts, highlighting the current one
-pub fn list_contexts(config: &KubeConfig, output: &OutputFormat) {
+///
+/// Returns whether the current context is set and resolves to an existing context.
+pub fn list_contexts(config: &KubeConfig, options: ListOptions) -> bool {
+    let ListOptions {
+        output,
+        sort,
+        group_by,
+        current_only,
+        wide,
+        porcelain,
+        namespace_rules,
+    } = options;
+    let (output, sort, group_by) = (output.as_ref(), sort.as_ref(), group_by.as_ref());
+
+    let has_current = config
+        .contexts
+        .iter()
+        .any(|c| c.name == config.current_context);
+
+    let last_used = match sort {
+        Some(SortKey::Recent) => load_last_used(),
+        _ => BTreeMap::new(),
+    };
+    let mut contexts = sorted_contexts(config, sort, &last_used);
+    if current_only {
+        contexts.retain(|c| c.name == config.current_context);
+
+        if config.current_context.is_empty() {
+            eprintln!("No current context is set");
+        } else {
+            warn_missing_current_context(config);
+        }
+    }
+
+    if porcelain {
+        print_porcelain(config, &contexts);
+        return has_current;
+    }
+
+    let health = wide.then(|| credential_health_by_user(config, &contexts));
+    let health = health.as_ref();
+
     match output {
-        OutputFormat::Table => {
-            println!("{} available contexts:", style("Kubernetes").green().bold());
-            println!("------------------------");
-
-            for context in &config.contexts {
-                let marker = if context.name == config.current_context {
-                    style("*").green().bold()
-                } else {
-                    style(" ").dim()
-                };
-
-                let namespace_info = if let Some(namespace) = &context.context.namespace {
-                    format!(" (namespace: {})", style(namespace).cyan())
-                } else {
-                    String::new()
-                };
-
-                println!("{} {}{}", marker, context.name, namespace_info);
+        None => {
+            if !current_only {
+                println!("{} available contexts:", style("Kubernetes").green().bold());
+                println!("------------------------");
+            }
+
+            match group_by {
+                Some(GroupKey::Cluster) => print_grouped_by_cluster(config, &contexts, health),
+                None => {
+                    for context in &contexts {
+                        print_compact_line(config, context, "", health_of(health, context));
+                    }
+                }
+            }
+
+            if !current_only {
+                warn_missing_current_context(config);
+                warn_duplicate_contexts(config);
+                warn_namespace_deviations(config, namespace_rules);
+            }
+        }
+        Some(OutputFormat::Table) => print_aligned_table(config, &contexts, health),
+        Some(OutputFormat::Plain) => {
+            for context in &contexts {
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    context.name,
+                    context.context.cluster,
+                    context.context.user,
+                    context.context.namespace.as_deref().unwrap_or_default()
+                );
             }
         }
-        OutputFormat::Name => {
-            for context in &config.contexts {
+        Some(OutputFormat::Name) => {
+            for context in &contexts {
                 println!("{}", context.name);
             }
         }
-        OutputFormat::Json => {
-            let contexts: Vec<ContextInfo> = config
-                .contexts
+        Some(output @ (OutputFormat::Json | OutputFormat::Yaml)) => {
+            let contexts: Vec<ContextInfo> = contexts
                 .iter()
                 .map(|c| ContextInfo {
                     name: c.name.clone(),
@@ -51,11 +407,79 @@ pub fn list_contexts(config: &KubeConfig, output: &OutputFormat) {
                     user: c.context.user.clone(),
                     namespace: c.context.namespace.clone(),
                     current: c.name == config.current_context,
+                    credentials: health_of(health, c).map(CredentialHealth::label),
                 })
                 .collect();
-            if let Ok(json) = serde_json::to_string_pretty(&contexts) {
+            if matches!(output, OutputFormat::Yaml) {
+                if let Ok(yaml) = serde_yaml::to_string(&contexts) {
+                    print!("{}", yaml);
+                }
+            } else if let Ok(json) = serde_json::to_string_pretty(&contexts) {
                 println!("{}", json);
             }
         }
     }
+
+    has_current
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::kubernetes::ContextData;
+
+    #[test]
+    fn test_sort_recent_puts_unused_last() {
+        let config = KubeConfig {
+            contexts: ["old", "never-b", "new", "never-a"]
+                .iter()
+                .map(|name| ContextEntry {
+                    name: name.to_string(),
+                    context: ContextData {
+                        cluster: "cluster".to_string(),
+                        user: "user".to_string(),
+                        namespace: None,
+                    },
+                })
+                .collect(),
+            ..KubeConfig::default()
+        };
+        let last_used = BTreeMap::from([
+            ("old".to_string(), 100),
+            ("new".to_string(), 200),
+            ("deleted".to_string(), 300),
+        ]);
+
+        let names: Vec<&str> = sorted_contexts(&config, Some(&SortKey::Recent), &last_used)
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["new", "old", "never-a", "never-b"]);
+    }
+
+    #[test]
+    fn test_porcelain_line() {
+        let context = |name: &str, namespace: Option<&str>| ContextEntry {
+            name: name.to_string(),
+            context: ContextData {
+                cluster: "cluster".to_string(),
+                user: "user".to_string(),
+                namespace: namespace.map(str::to_string),
+            },
+        };
+        let config = KubeConfig {
+            contexts: vec![context("dev", Some("apps")), context("prod", None)],
+            current_context: "dev".to_string(),
+            ..KubeConfig::default()
+        };
+
+        assert_eq!(
+            porcelain_line(&config, &config.contexts[0]),
+            "*\tdev\tcluster\tuser\tapps"
+        );
+        assert_eq!(
+            porcelain_line(&config, &config.contexts[1]),
+            "\tprod\tcluster\tuser\t"
+        );
+    }
 }
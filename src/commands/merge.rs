@@ -0,0 +1,72 @@
+use anyhow::Result;
+use console::style;
+use log::debug;
+
+use crate::commands::namespace::check_namespace_name;
+use crate::commands::output::status;
+use crate::config::kubernetes::{ContextData, ContextEntry};
+use crate::config::operations::{load_kube_config, save_kube_config};
+use crate::error::KhelpError;
+
+/// Create a new context from the pieces of existing ones
+///
+/// The new context uses the cluster of cluster_from and the user of user_from,
+/// with an optional namespace, which is refused if it isn't a valid name unless force is set.
+pub fn merge_contexts(
+    name: String,
+    cluster_from: String,
+    user_from: String,
+    namespace: Option<String>,
+    force: bool,
+) -> Result<()> {
+    if let Some(namespace) = &namespace {
+        check_namespace_name(namespace, force)?;
+    }
+
+    let mut config = load_kube_config()?;
+    debug!("Loaded kube config with {} contexts", config.contexts.len());
+
+    if config.contexts.iter().any(|c| c.name == name) {
+        anyhow::bail!(KhelpError::ContextExists(name.clone()));
+    }
+
+    let cluster = config
+        .contexts
+        .iter()
+        .find(|c| c.name == cluster_from)
+        .map(|c| c.context.cluster.clone())
+        .ok_or_else(|| KhelpError::ContextNotFound(cluster_from.clone()))?;
+
+    let user = config
+        .contexts
+        .iter()
+        .find(|c| c.name == user_from)
+        .map(|c| c.context.user.clone())
+        .ok_or_else(|| KhelpError::ContextNotFound(user_from.clone()))?;
+
+    debug!(
+        "Creating context '{}' with cluster '{}' and user '{}'",
+        name, cluster, user
+    );
+
+    config.contexts.push(ContextEntry {
+        name: name.clone(),
+        context: ContextData {
+            cluster: cluster.clone(),
+            user: user.clone(),
+            namespace,
+        },
+    });
+
+    save_kube_config(&config)?;
+
+    status!(
+        "{} Created context {} (cluster: {}, user: {})",
+        style("✓").green(),
+        style(&name).green().bold(),
+        style(&cluster).cyan(),
+        style(&user).cyan()
+    );
+
+    Ok(())
+}
@@ -1,11 +1,24 @@
 pub mod add;
+pub mod alias;
+pub mod api;
+pub mod cert_info;
+pub mod check;
 pub mod cleanup;
 pub mod completions;
+pub mod config_path;
 pub mod current;
 pub mod delete;
 pub mod edit;
 pub mod export;
 pub mod list;
+pub mod merge;
+pub mod namespace;
+pub mod output;
+pub mod prompt;
 pub mod rename;
+pub mod reset_current;
+pub mod resolve;
 pub mod switch;
+#[cfg(feature = "tui")]
+pub mod tui;
 pub mod update;
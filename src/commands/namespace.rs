@@ -0,0 +1,367 @@
+use anyhow::{Context, Result};
+use console::style;
+use dialoguer::{Select, theme::ColorfulTheme};
+use log::debug;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::commands::api::{Credentials, build_agent, get};
+use crate::commands::output::ensure_can_prompt;
+use crate::commands::output::status;
+use crate::config::analysis::{find_namespace_deviations, glob_match, invalid_namespace_reason};
+use crate::config::operations::{load_kube_config, save_kube_config};
+use crate::error::KhelpError;
+
+/// How long to wait for the API server before giving up
+const API_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Which contexts a namespace change applies to
+pub enum NamespaceTarget {
+    /// A context name, or a glob pattern using `*` and `?`
+    Pattern(String),
+    /// The active context
+    Current,
+    /// Every context in the config
+    All,
+    /// Pick a context from an interactive menu
+    Interactive,
+}
+
+/// Refuse a namespace name that kubectl would reject, or only warn about it with force
+pub fn check_namespace_name(namespace: &str, force: bool) -> Result<()> {
+    let Some(reason) = invalid_namespace_reason(namespace) else {
+        return Ok(());
+    };
+    if !force {
+        anyhow::bail!(
+            "'{}' is not a valid namespace name: it {}; use --force to set it anyway",
+            namespace,
+            reason
+        );
+    }
+    eprintln!(
+        "{} '{}' is not a valid namespace name: it {}",
+        style("Warning:").yellow().bold(),
+        namespace,
+        reason
+    );
+    Ok(())
+}
+
+/// Set the default namespace on one or more Kubernetes contexts
+///
+/// Matching contexts are updated in a single save.
+/// Namespace names that Kubernetes would reject are refused unless force is set.
+pub fn set_namespace(target: NamespaceTarget, namespace: String, force: bool) -> Result<()> {
+    check_namespace_name(&namespace, force)?;
+
+    let mut config = load_kube_config()?;
+    debug!("Loaded kube config with {} contexts", config.contexts.len());
+
+    if config.contexts.is_empty() {
+        anyhow::bail!("No contexts available");
+    }
+
+    let selected: Vec<String> = match target {
+        NamespaceTarget::Pattern(pattern) => {
+            let is_glob = pattern.contains(['*', '?']);
+            let matches: Vec<String> = config
+                .contexts
+                .iter()
+                .filter(|c| {
+                    if is_glob {
+                        glob_match(&pattern, &c.name)
+                    } else {
+                        c.name == pattern
+                    }
+                })
+                .map(|c| c.name.clone())
+                .collect();
+
+            if matches.is_empty() {
+                if is_glob {
+                    anyhow::bail!("No contexts match '{}'", pattern);
+                }
+                anyhow::bail!(KhelpError::ContextNotFound(pattern.clone()));
+            }
+            matches
+        }
+        NamespaceTarget::Current => {
+            if config.current_context.is_empty() {
+                anyhow::bail!("No current context is set");
+            }
+            if !config
+                .contexts
+                .iter()
+                .any(|c| c.name == config.current_context)
+            {
+                anyhow::bail!(KhelpError::ContextNotFound(config.current_context.clone()));
+            }
+            vec![config.current_context.clone()]
+        }
+        NamespaceTarget::All => config.contexts.iter().map(|c| c.name.clone()).collect(),
+        NamespaceTarget::Interactive => {
+            debug!("No context name provided, showing selection menu");
+            let default_idx = config
+                .contexts
+                .iter()
+                .position(|c| c.name == config.current_context)
+                .unwrap_or(0);
+
+            ensure_can_prompt()?;
+            let selection = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt(format!(
+                    "Select a context to set namespace '{}' on",
+                    namespace
+                ))
+                .default(default_idx)
+                .items(&config.contexts.iter().map(|c| &c.name).collect::<Vec<_>>())
+                .interact()
+                .context("Failed to display interactive selection")?;
+
+            vec![config.contexts[selection].name.clone()]
+        }
+    };
+
+    let mut changed = Vec::new();
+    for context in config
+        .contexts
+        .iter_mut()
+        .filter(|c| selected.contains(&c.name))
+    {
+        if context.context.namespace.as_deref() != Some(namespace.as_str()) {
+            debug!(
+                "Setting namespace of '{}' from {:?} to '{}'",
+                context.name, context.context.namespace, namespace
+            );
+            context.context.namespace = Some(namespace.clone());
+            changed.push(context.name.clone());
+        }
+    }
+
+    if changed.is_empty() {
+        status!(
+            "All {} matching context(s) already use namespace {}",
+            selected.len(),
+            style(&namespace).cyan()
+        );
+        return Ok(());
+    }
+
+    save_kube_config(&config)?;
+
+    for name in &changed {
+        status!(
+            "{} Set namespace of {} to {}",
+            style("✓").green(),
+            style(name).green().bold(),
+            style(&namespace).cyan()
+        );
+    }
+    status!("Updated {} context(s)", changed.len());
+
+    Ok(())
+}
+
+/// Set each context's namespace to the one the settings' `namespace_rules` expect
+///
+/// rules maps context name globs to namespaces, the longest matching pattern winning.
+/// Contexts no rule matches are left alone. Invalid namespace names are refused
+/// unless force is set.
+pub fn apply_namespace_rules(rules: &BTreeMap<String, String>, force: bool) -> Result<()> {
+    if rules.is_empty() {
+        anyhow::bail!(
+            "No namespace_rules are configured; add a [namespace_rules] table to khelp's settings file"
+        );
+    }
+
+    let mut config = load_kube_config()?;
+    let deviations = find_namespace_deviations(&config, rules);
+    if deviations.is_empty() {
+        status!("All contexts already follow the namespace rules");
+        return Ok(());
+    }
+
+    for deviation in &deviations {
+        check_namespace_name(&deviation.expected, force)?;
+    }
+
+    for deviation in &deviations {
+        if let Some(context) = config
+            .contexts
+            .iter_mut()
+            .find(|c| c.name == deviation.context)
+        {
+            context.context.namespace = Some(deviation.expected.clone());
+        }
+    }
+
+    save_kube_config(&config)?;
+
+    for deviation in &deviations {
+        status!(
+            "{} Set namespace of {} from {} to {}",
+            style("✓").green(),
+            style(&deviation.context).green().bold(),
+            style(deviation.actual.as_deref().unwrap_or("default")).yellow(),
+            style(&deviation.expected).cyan()
+        );
+    }
+    status!("Updated {} context(s)", deviations.len());
+
+    Ok(())
+}
+
+/// Query the current context's API server for namespaces and pick one to use
+///
+/// Only token and client certificate auth are supported for the request.
+pub fn pick_namespace_from_cluster() -> Result<()> {
+    let mut config = load_kube_config()?;
+
+    if config.current_context.is_empty() {
+        anyhow::bail!("No current context is set");
+    }
+    let context_name = config.current_context.clone();
+
+    let context = config
+        .contexts
+        .iter()
+        .find(|c| c.name == context_name)
+        .ok_or_else(|| KhelpError::ContextNotFound(context_name.clone()))?;
+
+    let cluster = config
+        .clusters
+        .iter()
+        .find(|c| c.name == context.context.cluster)
+        .ok_or_else(|| KhelpError::ClusterNotFound {
+            cluster: context.context.cluster.clone(),
+            context: context_name.clone(),
+        })?;
+
+    let user = config
+        .users
+        .iter()
+        .find(|u| u.name == context.context.user)
+        .ok_or_else(|| KhelpError::UserNotFound {
+            user: context.context.user.clone(),
+            context: context_name.clone(),
+        })?;
+
+    let credentials = Credentials::for_user(&user.name, &user.user)?;
+    let agent = build_agent(&cluster.cluster, &credentials, API_TIMEOUT)?;
+
+    status!(
+        "Fetching namespaces from {}",
+        style(&cluster.cluster.server).cyan()
+    );
+    let mut response = get(
+        &agent,
+        &cluster.cluster,
+        "/api/v1/namespaces",
+        &credentials,
+        API_TIMEOUT,
+    )?;
+
+    let code = response.status().as_u16();
+    if code != 200 {
+        anyhow::bail!(
+            "API server responded with HTTP {} when listing namespaces",
+            code
+        );
+    }
+
+    let body = response
+        .body_mut()
+        .read_to_string()
+        .context("Failed to read response from API server")?;
+    let mut namespaces = parse_namespace_list(&body)?;
+    if namespaces.is_empty() {
+        anyhow::bail!("The API server returned no namespaces");
+    }
+    namespaces.sort();
+
+    let default_idx = context
+        .context
+        .namespace
+        .as_ref()
+        .and_then(|ns| namespaces.iter().position(|n| n == ns))
+        .unwrap_or(0);
+
+    ensure_can_prompt()?;
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("Select a namespace for context '{}'", context_name))
+        .default(default_idx)
+        .items(&namespaces)
+        .interact()
+        .context("Failed to display interactive selection")?;
+    let namespace = namespaces.swap_remove(selection);
+
+    let context = config
+        .contexts
+        .iter_mut()
+        .find(|c| c.name == context_name)
+        .expect("context was found above");
+
+    if context.context.namespace.as_deref() == Some(namespace.as_str()) {
+        status!(
+            "Context {} already uses namespace {}",
+            style(&context_name).green().bold(),
+            style(&namespace).cyan()
+        );
+        return Ok(());
+    }
+
+    debug!(
+        "Setting namespace of '{}' from {:?} to '{}'",
+        context_name, context.context.namespace, namespace
+    );
+    context.context.namespace = Some(namespace.clone());
+    save_kube_config(&config)?;
+
+    status!(
+        "{} Set namespace of {} to {}",
+        style("✓").green(),
+        style(&context_name).green().bold(),
+        style(&namespace).cyan()
+    );
+
+    Ok(())
+}
+
+/// Extract namespace names from a `NamespaceList` response body
+fn parse_namespace_list(body: &str) -> Result<Vec<String>> {
+    let list: serde_json::Value =
+        serde_json::from_str(body).context("API server returned invalid JSON")?;
+
+    let items = list
+        .get("items")
+        .and_then(|items| items.as_array())
+        .context("API server response has no namespace items")?;
+
+    Ok(items
+        .iter()
+        .filter_map(|item| item.get("metadata")?.get("name")?.as_str())
+        .map(str::to_string)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_namespace_list() {
+        let body = r#"{"kind":"NamespaceList","items":[
+            {"metadata":{"name":"default"}},
+            {"metadata":{"name":"kube-system"}},
+            {"metadata":{}}
+        ]}"#;
+        assert_eq!(
+            parse_namespace_list(body).unwrap(),
+            vec!["default".to_string(), "kube-system".to_string()]
+        );
+
+        assert!(parse_namespace_list(r#"{"kind":"Status"}"#).is_err());
+        assert!(parse_namespace_list("not json").is_err());
+    }
+}
@@ -0,0 +1,159 @@
+use anyhow::Result;
+use console::style;
+use std::cell::RefCell;
+use std::io::{self, IsTerminal};
+
+use crate::cli::ColorChoice;
+use crate::config::analysis::find_conflicting_fields;
+use crate::config::kubernetes::KubeConfig;
+
+thread_local! {
+    static QUIET: RefCell<bool> = const { RefCell::new(false) };
+}
+
+/// Suppress status messages and interactive prompts
+pub fn set_quiet(quiet: bool) {
+    QUIET.with(|q| {
+        *q.borrow_mut() = quiet;
+    });
+}
+
+/// Whether `--quiet` was given
+pub fn is_quiet() -> bool {
+    QUIET.with(|q| *q.borrow())
+}
+
+/// Whether prompting is possible: not running with `--quiet`, and stdin is a terminal
+pub fn can_prompt() -> bool {
+    !is_quiet() && io::stdin().is_terminal()
+}
+
+/// Fail instead of prompting when running with `--quiet` or without a terminal
+///
+/// Without this, a prompt on piped stdin (e.g. in CI) hangs or errors opaquely.
+pub fn ensure_can_prompt() -> Result<()> {
+    if is_quiet() {
+        anyhow::bail!(
+            "This command needs an interactive prompt, which --quiet disables; \
+             pass the context name or --force instead"
+        );
+    }
+    if !io::stdin().is_terminal() {
+        anyhow::bail!(
+            "Input is required but not running interactively (stdin is not a terminal); \
+             pass the context name or --force instead"
+        );
+    }
+    Ok(())
+}
+
+/// Decide whether to force colors on or off, or None to let console detect a terminal
+///
+/// `--color` wins over the `color` setting, which wins over a non-empty `NO_COLOR`;
+/// `--color auto` skips the setting but still honors `NO_COLOR`.
+pub fn resolve_color(
+    choice: Option<ColorChoice>,
+    setting: Option<bool>,
+    no_color: bool,
+) -> Option<bool> {
+    match choice {
+        Some(ColorChoice::Always) => Some(true),
+        Some(ColorChoice::Never) => Some(false),
+        Some(ColorChoice::Auto) => no_color.then_some(false),
+        None => setting.or(no_color.then_some(false)),
+    }
+}
+
+/// Warn about clusters and users in config that set contradicting fields
+pub fn warn_conflicting_fields(config: &KubeConfig) {
+    for problem in find_conflicting_fields(config) {
+        eprintln!("{} {}", style("Warning:").yellow().bold(), problem);
+    }
+}
+
+/// Warn when current-context names a context the config doesn't have, suggesting
+/// `reset-current`; returns whether it did
+pub fn warn_missing_current_context(config: &KubeConfig) -> bool {
+    let current = &config.current_context;
+    if current.is_empty() || config.contexts.iter().any(|c| c.name == *current) {
+        return false;
+    }
+
+    eprintln!(
+        "{} current-context '{}' is set but not found in contexts; pick another with {}",
+        style("Warning:").yellow().bold(),
+        current,
+        style("khelp reset-current").cyan()
+    );
+    true
+}
+
+/// Render a log record as a single line of JSON for `--log-format json`
+pub fn json_log_line(timestamp: &str, record: &log::Record) -> String {
+    serde_json::json!({
+        "timestamp": timestamp,
+        "level": record.level().as_str(),
+        "target": record.target(),
+        "message": record.args().to_string(),
+    })
+    .to_string()
+}
+
+/// Print a status message to stderr unless `--quiet` was given
+///
+/// Warnings and errors should use `eprintln!` directly so they are always shown.
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if !$crate::commands::output::is_quiet() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+pub(crate) use status;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_color() {
+        assert_eq!(
+            resolve_color(Some(ColorChoice::Always), Some(false), true),
+            Some(true)
+        );
+        assert_eq!(
+            resolve_color(Some(ColorChoice::Never), Some(true), false),
+            Some(false)
+        );
+        assert_eq!(
+            resolve_color(Some(ColorChoice::Auto), Some(true), false),
+            None
+        );
+        assert_eq!(
+            resolve_color(Some(ColorChoice::Auto), None, true),
+            Some(false)
+        );
+        assert_eq!(resolve_color(None, Some(true), true), Some(true));
+        assert_eq!(resolve_color(None, None, true), Some(false));
+        assert_eq!(resolve_color(None, None, false), None);
+    }
+
+    #[test]
+    fn test_json_log_line() {
+        let line = json_log_line(
+            "2026-10-15T12:00:00Z",
+            &log::Record::builder()
+                .level(log::Level::Debug)
+                .target("khelp::config")
+                .args(format_args!("Loaded \"{}\"", "dev"))
+                .build(),
+        );
+
+        let parsed: serde_json::Value = serde_json::from_str(&line).expect("Invalid JSON");
+        assert_eq!(parsed["timestamp"], "2026-10-15T12:00:00Z");
+        assert_eq!(parsed["level"], "DEBUG");
+        assert_eq!(parsed["target"], "khelp::config");
+        assert_eq!(parsed["message"], "Loaded \"dev\"");
+        assert!(!line.contains('\n'));
+    }
+}
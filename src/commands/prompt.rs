@@ -0,0 +1,140 @@
+use anyhow::{Context, Result};
+use console::style;
+use log::debug;
+use regex::Regex;
+
+use crate::cli::PromptShell;
+use crate::commands::current::render_template;
+use crate::config::kubernetes::KubeConfig;
+use crate::config::operations::load_kube_config;
+use crate::error::KhelpError;
+
+/// Print the current context as a short segment for a shell prompt, such as `dev:default`
+///
+/// Prints nothing when there is no kubeconfig or no current context, so the output can
+/// go straight into PS1. Contexts matching prod_pattern are shown in red, others in green.
+/// For bash and zsh the colors are always written, with the escapes wrapped so the shell
+/// doesn't count them towards the prompt's width.
+pub fn print_prompt(format: &str, shell: PromptShell, prod_pattern: Option<&str>) -> Result<()> {
+    let prod_pattern = prod_pattern
+        .map(Regex::new)
+        .transpose()
+        .context("Invalid prod_pattern in settings")?;
+
+    let config = match load_kube_config() {
+        Ok(config) => config,
+        Err(KhelpError::ConfigNotFound(_) | KhelpError::EmptyConfig(_)) => {
+            debug!("No kubeconfig, leaving the prompt empty");
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    if let Some(segment) = prompt_segment(&config, format, shell, prod_pattern.as_ref())? {
+        println!("{}", segment);
+    }
+    Ok(())
+}
+
+/// The rendered prompt segment, or `None` when the current context is unset or missing
+fn prompt_segment(
+    config: &KubeConfig,
+    format: &str,
+    shell: PromptShell,
+    prod_pattern: Option<&Regex>,
+) -> Result<Option<String>> {
+    let context_name = &config.current_context;
+    if !config.contexts.iter().any(|c| c.name == *context_name) {
+        return Ok(None);
+    }
+
+    let segment = render_template(config, context_name, format)?;
+    let is_prod = prod_pattern.is_some_and(|pattern| pattern.is_match(context_name));
+    let styled = if is_prod {
+        style(segment).red().bold()
+    } else {
+        style(segment).green()
+    };
+
+    let (open, close) = match shell {
+        PromptShell::Plain => return Ok(Some(styled.to_string())),
+        // What \[ and \] in PS1 become; bash reads those before running $(...)
+        PromptShell::Bash => ("\x01", "\x02"),
+        PromptShell::Zsh => ("%{", "%}"),
+    };
+    let escape = Regex::new("\x1b\\[[0-9;]*m").expect("Invalid escape pattern");
+    let colored = styled.force_styling(true).to_string();
+    Ok(Some(
+        escape
+            .replace_all(&colored, |caps: &regex::Captures| {
+                format!("{}{}{}", open, &caps[0], close)
+            })
+            .into_owned(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_segment() {
+        let mut config: KubeConfig = serde_yaml::from_str(
+            r#"
+current-context: prod-eu
+clusters:
+- name: eu
+  cluster: {server: "https://eu.example.com"}
+users:
+- name: admin
+  user: {token: a}
+contexts:
+- name: prod-eu
+  context: {cluster: eu, user: admin, namespace: payments}
+"#,
+        )
+        .expect("Failed to parse test config");
+        let prod = Regex::new("^prod-").expect("Failed to compile pattern");
+
+        let segment = prompt_segment(
+            &config,
+            "{context}:{namespace}",
+            PromptShell::Plain,
+            Some(&prod),
+        )
+        .expect("Failed to render prompt");
+        assert_eq!(
+            console::strip_ansi_codes(segment.as_deref().unwrap_or_default()),
+            "prod-eu:payments"
+        );
+        assert!(prompt_segment(&config, "{bogus}", PromptShell::Plain, None).is_err());
+
+        config.current_context.clear();
+        assert_eq!(
+            prompt_segment(&config, "{context}", PromptShell::Plain, None)
+                .expect("Failed to render prompt"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_prompt_segment_wraps_escapes_for_shell() {
+        let config: KubeConfig = serde_yaml::from_str(
+            r#"
+current-context: dev
+contexts:
+- name: dev
+  context: {cluster: eu, user: admin}
+"#,
+        )
+        .expect("Failed to parse test config");
+
+        let bash = prompt_segment(&config, "{context}", PromptShell::Bash, None)
+            .expect("Failed to render prompt");
+        assert_eq!(bash.as_deref(), Some("\x01\x1b[32m\x02dev\x01\x1b[0m\x02"));
+
+        let zsh = prompt_segment(&config, "{context}", PromptShell::Zsh, None)
+            .expect("Failed to render prompt");
+        assert_eq!(zsh.as_deref(), Some("%{\x1b[32m%}dev%{\x1b[0m%}"));
+    }
+}
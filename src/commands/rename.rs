@@ -2,13 +2,37 @@ use anyhow::Result;
 use console::style;
 use log::debug;
 
+use crate::commands::output::status;
 use crate::config::operations::{load_kube_config, save_kube_config};
+use crate::config::usage::{rename_usage_in, update_usage};
+use crate::error::KhelpError;
+use crate::utils::confirm;
+
+/// Swap the old context name at the start of a cluster or user name for the new one
+///
+/// Returns None unless name is old_name or starts with old_name followed by `-`, `_`,
+/// or `.`, so renaming `prod` leaves `prodigy-cluster` alone.
+fn cascaded_name(name: &str, old_name: &str, new_name: &str) -> Option<String> {
+    name.strip_prefix(old_name)
+        .filter(|rest| rest.is_empty() || rest.starts_with(['-', '_', '.']))
+        .map(|rest| format!("{}{}", new_name, rest))
+}
 
 /// Rename a Kubernetes context
 ///
 /// Renames the specified context from old_name to new_name.
 /// If the current context matches old_name, it will be updated to new_name.
-pub fn rename_context(old_name: String, new_name: String) -> Result<()> {
+/// A new name that differs from another context only in case is a warning, or an
+/// error with strict_case; changing just the case of old_name is always allowed.
+/// With cascade, the context's cluster and user are renamed too when their names start
+/// with old_name, after asking unless force is set, and every context using them is updated.
+pub fn rename_context(
+    old_name: String,
+    new_name: String,
+    strict_case: bool,
+    cascade: bool,
+    force: bool,
+) -> Result<()> {
     debug!(
         "Attempting to rename context from '{}' to '{}'",
         old_name, new_name
@@ -20,13 +44,13 @@ pub fn rename_context(old_name: String, new_name: String) -> Result<()> {
     // Validate old context exists
     let old_context_exists = config.contexts.iter().any(|c| c.name == old_name);
     if !old_context_exists {
-        anyhow::bail!("Context '{}' not found", old_name);
+        anyhow::bail!(KhelpError::ContextNotFound(old_name.clone()));
     }
 
     // Validate new context name doesn't already exist
     let new_context_exists = config.contexts.iter().any(|c| c.name == new_name);
     if new_context_exists {
-        anyhow::bail!("Context '{}' already exists", new_name);
+        anyhow::bail!(KhelpError::ContextExists(new_name.clone()));
     }
 
     // Prevent renaming to the same name
@@ -34,6 +58,58 @@ pub fn rename_context(old_name: String, new_name: String) -> Result<()> {
         anyhow::bail!("New name must be different from the current name");
     }
 
+    // Some tools treat context names case-insensitively, so `Prod` and `prod` collide
+    let lowercase_name = new_name.to_lowercase();
+    if let Some(similar) = config
+        .contexts
+        .iter()
+        .find(|c| c.name != old_name && c.name.to_lowercase() == lowercase_name)
+    {
+        if strict_case {
+            anyhow::bail!(
+                "Context '{}' differs from '{}' only in case; choose another name",
+                similar.name,
+                new_name
+            );
+        }
+        eprintln!(
+            "{} context '{}' differs from '{}' only in case, which some tools treat as the same",
+            style("Warning:").yellow().bold(),
+            similar.name,
+            new_name
+        );
+    }
+
+    // Decide on the cluster and user renames before changing anything
+    let mut cluster_rename = None;
+    let mut user_rename = None;
+    if cascade {
+        let context = config
+            .contexts
+            .iter()
+            .find(|c| c.name == old_name)
+            .expect("checked above");
+        let cluster_names: Vec<&str> = config.clusters.iter().map(|c| c.name.as_str()).collect();
+        let user_names: Vec<&str> = config.users.iter().map(|u| u.name.as_str()).collect();
+
+        cluster_rename = cascade_rename(
+            "cluster",
+            &context.context.cluster,
+            &cluster_names,
+            &old_name,
+            &new_name,
+            force,
+        )?;
+        user_rename = cascade_rename(
+            "user",
+            &context.context.user,
+            &user_names,
+            &old_name,
+            &new_name,
+            force,
+        )?;
+    }
+
     // Rename the context
     for context in &mut config.contexts {
         if context.name == old_name {
@@ -43,6 +119,36 @@ pub fn rename_context(old_name: String, new_name: String) -> Result<()> {
         }
     }
 
+    if let Some((old_cluster, new_cluster)) = &cluster_rename {
+        for cluster in config
+            .clusters
+            .iter_mut()
+            .filter(|c| &c.name == old_cluster)
+        {
+            cluster.name = new_cluster.clone();
+        }
+        for context in config
+            .contexts
+            .iter_mut()
+            .filter(|c| &c.context.cluster == old_cluster)
+        {
+            context.context.cluster = new_cluster.clone();
+        }
+    }
+
+    if let Some((old_user, new_user)) = &user_rename {
+        for user in config.users.iter_mut().filter(|u| &u.name == old_user) {
+            user.name = new_user.clone();
+        }
+        for context in config
+            .contexts
+            .iter_mut()
+            .filter(|c| &c.context.user == old_user)
+        {
+            context.context.user = new_user.clone();
+        }
+    }
+
     // Update current-context if it matches the old name
     if config.current_context == old_name {
         debug!(
@@ -55,11 +161,87 @@ pub fn rename_context(old_name: String, new_name: String) -> Result<()> {
     // Save the updated configuration with backup
     save_kube_config(&config)?;
 
-    eprintln!(
+    update_usage(|path| rename_usage_in(path, &old_name, &new_name));
+
+    status!(
         "Renamed context from {} to {}",
         style(&old_name).yellow(),
         style(&new_name).green().bold()
     );
+    for (kind, rename) in [("cluster", &cluster_rename), ("user", &user_rename)] {
+        if let Some((old, new)) = rename {
+            status!(
+                "Renamed {} from {} to {}",
+                kind,
+                style(old).yellow(),
+                style(new).green().bold()
+            );
+        }
+    }
 
     Ok(())
 }
+
+/// Work out whether a context's cluster or user should follow the context's new name
+///
+/// kind is "cluster" or "user" and existing holds the names already in use for that kind.
+/// Returns the old and new name when the entry should be renamed.
+fn cascade_rename(
+    kind: &str,
+    name: &str,
+    existing: &[&str],
+    old_name: &str,
+    new_name: &str,
+    force: bool,
+) -> Result<Option<(String, String)>> {
+    let Some(renamed) = cascaded_name(name, old_name, new_name) else {
+        debug!("{} '{}' doesn't start with '{}'", kind, name, old_name);
+        return Ok(None);
+    };
+
+    if !existing.contains(&name) {
+        return Ok(None);
+    }
+    if existing.contains(&renamed.as_str()) {
+        eprintln!(
+            "{} not renaming {} '{}': a {} named '{}' already exists",
+            style("Warning:").yellow().bold(),
+            kind,
+            name,
+            kind,
+            renamed
+        );
+        return Ok(None);
+    }
+
+    let prompt = format!("Also rename {} '{}' to '{}'?", kind, name, renamed);
+    if !confirm(&prompt, true, force)? {
+        return Ok(None);
+    }
+
+    Ok(Some((name.to_string(), renamed)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cascaded_name() {
+        assert_eq!(
+            cascaded_name("prod-cluster", "prod", "production").as_deref(),
+            Some("production-cluster")
+        );
+        assert_eq!(
+            cascaded_name("prod", "prod", "production").as_deref(),
+            Some("production")
+        );
+        assert_eq!(cascaded_name("staging-cluster", "prod", "production"), None);
+        assert_eq!(cascaded_name("my-prod-user", "prod", "production"), None);
+        assert_eq!(cascaded_name("prodigy-cluster", "prod", "production"), None);
+        assert_eq!(
+            cascaded_name("prod.admin", "prod", "production").as_deref(),
+            Some("production.admin")
+        );
+    }
+}
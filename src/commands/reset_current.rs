@@ -0,0 +1,76 @@
+use anyhow::{Context, Result};
+use console::style;
+use dialoguer::{Select, theme::ColorfulTheme};
+use log::debug;
+
+use crate::commands::output::{ensure_can_prompt, status};
+use crate::config::operations::{load_kube_config, save_kube_config};
+use crate::error::KhelpError;
+
+/// Pick a new current context when the current one is unset or names no context
+///
+/// If context_name is provided, that context becomes current.
+/// Otherwise, presents an interactive menu to select one.
+/// A current context that already resolves is left alone; use `switch` to change it.
+pub fn reset_current_context(context_name: Option<String>) -> Result<()> {
+    let mut config = load_kube_config()?;
+
+    if config
+        .contexts
+        .iter()
+        .any(|c| c.name == config.current_context)
+    {
+        status!(
+            "Current context {} exists, nothing to fix (use {} to change it)",
+            style(&config.current_context).green().bold(),
+            style("khelp switch").cyan()
+        );
+        return Ok(());
+    }
+
+    if config.current_context.is_empty() {
+        status!("No current context is set");
+    } else {
+        status!(
+            "Current context {} does not exist",
+            style(&config.current_context).red()
+        );
+    }
+
+    if config.contexts.is_empty() {
+        anyhow::bail!("There are no contexts to choose from; add one with `khelp add`");
+    }
+
+    let selected_context = match context_name {
+        Some(name) => {
+            if !config.contexts.iter().any(|c| c.name == name) {
+                anyhow::bail!(KhelpError::ContextNotFound(name));
+            }
+            name
+        }
+        None => {
+            debug!("No context name provided, showing selection menu");
+            let names: Vec<&str> = config.contexts.iter().map(|c| c.name.as_str()).collect();
+
+            ensure_can_prompt()?;
+            let selection = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt("Select the context to make current")
+                .default(0)
+                .items(&names)
+                .interact()
+                .context("Failed to display interactive selection")?;
+
+            config.contexts[selection].name.clone()
+        }
+    };
+
+    config.current_context = selected_context.clone();
+    save_kube_config(&config)?;
+
+    status!(
+        "Current context set to {}",
+        style(&selected_context).green().bold()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use anyhow::{Context, Result};
+use log::debug;
+
+use crate::cli::ExportFormat;
+use crate::commands::export::write_stdout;
+use crate::config::kubernetes::KubeConfig;
+use crate::config::operations::load_kube_config;
+use crate::error::KhelpError;
+
+/// Print the single-context config kubectl uses for the current context
+///
+/// Like `export --current`, but with the defaults kubectl assumes for unset
+/// fields written out, so the output shows what kubectl will actually do.
+pub fn resolve_current_context(format: ExportFormat) -> Result<()> {
+    let full_config = load_kube_config()?;
+
+    let config = resolved_config(&full_config)?;
+    debug!("Resolved current context '{}'", config.current_context);
+
+    match format {
+        ExportFormat::Yaml => {
+            let yaml =
+                serde_yaml::to_string(&config).context("Failed to serialize config to YAML")?;
+            write_stdout(&yaml)
+        }
+        ExportFormat::Json => {
+            let json = serde_json::to_string_pretty(&config)
+                .context("Failed to serialize config to JSON")?;
+            write_stdout(&json)
+        }
+    }
+}
+
+/// A config holding only the current context, its cluster, and its user, with defaults filled in
+fn resolved_config(full_config: &KubeConfig) -> Result<KubeConfig> {
+    let current = &full_config.current_context;
+    if current.is_empty() {
+        anyhow::bail!("No current context is set");
+    }
+
+    let context = full_config
+        .contexts
+        .iter()
+        .find(|c| c.name == *current)
+        .ok_or_else(|| KhelpError::ContextNotFound(current.clone()))?;
+    let cluster = full_config
+        .clusters
+        .iter()
+        .find(|c| c.name == context.context.cluster)
+        .ok_or_else(|| KhelpError::ClusterNotFound {
+            cluster: context.context.cluster.clone(),
+            context: current.clone(),
+        })?;
+    let user = full_config
+        .users
+        .iter()
+        .find(|u| u.name == context.context.user)
+        .ok_or_else(|| KhelpError::UserNotFound {
+            user: context.context.user.clone(),
+            context: current.clone(),
+        })?;
+
+    let mut config = KubeConfig {
+        api_version: full_config.api_version.clone(),
+        clusters: vec![cluster.clone()],
+        contexts: vec![context.clone()],
+        current_context: current.clone(),
+        kind: full_config.kind.clone(),
+        preferences: full_config.preferences.clone(),
+        users: vec![user.clone()],
+    };
+    config.fill_defaults();
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolved_config_keeps_only_current_context() {
+        let full_config: KubeConfig = serde_yaml::from_str(
+            r#"
+apiVersion: v1
+current-context: dev
+clusters:
+- name: dev-cluster
+  cluster:
+    server: https://dev:6443
+- name: prod-cluster
+  cluster:
+    server: https://prod:6443
+contexts:
+- name: dev
+  context:
+    cluster: dev-cluster
+    user: dev-user
+- name: prod
+  context:
+    cluster: prod-cluster
+    user: prod-user
+    namespace: payments
+users:
+- name: dev-user
+  user:
+    token: dev-token
+- name: prod-user
+  user:
+    token: prod-token
+"#,
+        )
+        .expect("Failed to parse test config");
+
+        let config = resolved_config(&full_config).expect("Failed to resolve config");
+        assert_eq!(config.current_context, "dev");
+        assert_eq!(config.contexts.len(), 1);
+        assert_eq!(config.clusters[0].name, "dev-cluster");
+        assert_eq!(config.users[0].name, "dev-user");
+        assert_eq!(
+            config.contexts[0].context.namespace.as_deref(),
+            Some("default")
+        );
+        assert_eq!(
+            config.clusters[0].cluster.insecure_skip_tls_verify,
+            Some(false)
+        );
+
+        let mut unset = full_config.clone();
+        unset.current_context.clear();
+        assert!(resolved_config(&unset).is_err());
+    }
+}
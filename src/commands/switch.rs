@@ -2,25 +2,64 @@ use anyhow::{Context, Result};
 use console::style;
 use dialoguer::{Select, theme::ColorfulTheme};
 use log::debug;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
-use crate::config::operations::{load_kube_config, save_kube_config};
+use crate::commands::namespace::check_namespace_name;
+use crate::commands::output::{ensure_can_prompt, status};
+use crate::config::history::{history_path, load_history_from, push_history_to};
+use crate::config::kubernetes::KubeConfig;
+use crate::config::operations::{load_kube_config, save_kube_config, stable_hash};
+use crate::config::settings::settings_path;
+use crate::config::usage::{record_usage_to, update_usage};
+use crate::error::KhelpError;
 
 /// Switch to a different Kubernetes context
 ///
 /// If context_name is provided, switches directly to that context.
+/// A context_name that isn't an exact match may be a unique prefix or substring.
+/// A context_name of `-` switches back to the previously active context.
 /// Otherwise, presents an interactive menu to select a context.
-pub fn switch_context(context_name: Option<String>) -> Result<()> {
+/// If namespace is provided, it becomes the selected context's default namespace; an
+/// invalid namespace name is refused unless force is set.
+/// Selecting the context that is already current, without changing its namespace,
+/// leaves the kubeconfig untouched and returns false so the caller can report it.
+/// With strict, refuses to switch to a cluster that skips TLS verification unless force is set.
+/// With print_export, the kubeconfig isn't changed; the context is copied to a session
+/// file instead and a shell command pointing `KUBECONFIG` at it is printed.
+pub fn switch_context(
+    context_name: Option<String>,
+    namespace: Option<String>,
+    strict: bool,
+    force: bool,
+    print_export: bool,
+) -> Result<bool> {
+    if let Some(namespace) = &namespace {
+        check_namespace_name(namespace, force)?;
+    }
+
     let mut config = load_kube_config()?;
     debug!("Loaded kube config with {} contexts", config.contexts.len());
 
+    let history_path = history_path()?;
+
     let selected_context = match context_name {
+        Some(name) if name == "-" => {
+            debug!("Switching back using history at {}", history_path.display());
+            load_history_from(&history_path)?
+                .into_iter()
+                .rev()
+                .find(|previous| {
+                    *previous != config.current_context
+                        && config.contexts.iter().any(|c| c.name == *previous)
+                })
+                .context("No previous context to switch back to")?
+        }
         Some(name) => {
             debug!("Context name provided: {}", name);
-            if let Some(context) = config.contexts.iter().find(|c| c.name == name) {
-                context.name.clone()
-            } else {
-                anyhow::bail!("Context '{}' not found", name);
-            }
+            resolve_partial_name(&config, &name)?
         }
         None => {
             debug!("No context name provided, showing selection menu");
@@ -45,6 +84,7 @@ pub fn switch_context(context_name: Option<String>) -> Result<()> {
                 .position(|c| c.name == config.current_context)
                 .unwrap_or(0);
 
+            ensure_can_prompt()?;
             let selection = Select::with_theme(&ColorfulTheme::default())
                 .with_prompt("Select a context to switch to")
                 .default(default_idx)
@@ -58,6 +98,42 @@ pub fn switch_context(context_name: Option<String>) -> Result<()> {
 
     debug!("Selected context: {}", selected_context);
 
+    if let Some(cluster_name) = insecure_cluster(&config, &selected_context) {
+        if strict && !force {
+            anyhow::bail!(
+                "Context '{}' uses cluster '{}' with TLS verification disabled; use --force to switch anyway",
+                selected_context,
+                cluster_name
+            );
+        }
+        eprintln!(
+            "{} cluster '{}' has insecure-skip-tls-verify enabled; its certificate is not verified",
+            style("Warning:").yellow().bold(),
+            style(cluster_name).cyan()
+        );
+    }
+
+    if print_export {
+        print_session_export(&config, &selected_context, namespace.as_deref())?;
+        warn_missing_exec_plugin(&config, &selected_context);
+        return Ok(true);
+    }
+
+    let namespace_unchanged = namespace.is_none()
+        || config
+            .contexts
+            .iter()
+            .find(|c| c.name == selected_context)
+            .is_some_and(|c| c.context.namespace == namespace);
+    if selected_context == config.current_context && namespace_unchanged {
+        // Nothing would change, so don't rewrite the file (or back it up)
+        status!(
+            "Already on context: {}",
+            style(&selected_context).green().bold()
+        );
+        return Ok(false);
+    }
+
     let old_context = config.current_context.clone();
     config.current_context = selected_context.clone();
     debug!(
@@ -65,12 +141,339 @@ pub fn switch_context(context_name: Option<String>) -> Result<()> {
         old_context, selected_context
     );
 
+    if let Some(namespace) = &namespace
+        && let Some(context) = config
+            .contexts
+            .iter_mut()
+            .find(|c| c.name == selected_context)
+    {
+        debug!(
+            "Setting namespace of '{}' from {:?} to '{}'",
+            selected_context, context.context.namespace, namespace
+        );
+        context.context.namespace = Some(namespace.clone());
+    }
+
     save_kube_config(&config)?;
 
-    eprintln!(
-        "Switched to context: {}",
-        style(&selected_context).green().bold()
+    if !old_context.is_empty()
+        && old_context != selected_context
+        && let Err(e) = push_history_to(&history_path, &old_context)
+    {
+        eprintln!(
+            "{} could not record switch history: {:#}",
+            style("Warning:").yellow().bold(),
+            e
+        );
+    }
+
+    update_usage(|path| record_usage_to(path, &selected_context));
+
+    match &namespace {
+        Some(namespace) => status!(
+            "Switched to context: {} (namespace: {})",
+            style(&selected_context).green().bold(),
+            style(namespace).cyan()
+        ),
+        None => status!(
+            "Switched to context: {}",
+            style(&selected_context).green().bold()
+        ),
+    }
+
+    warn_missing_exec_plugin(&config, &selected_context);
+
+    Ok(true)
+}
+
+/// Copy a context, with its cluster and user, to a session kubeconfig and print
+/// `export KUBECONFIG=<file>` for the calling shell to eval
+///
+/// See [`session_config_path`] for where the file goes and how long it stays.
+fn print_session_export(
+    config: &KubeConfig,
+    context_name: &str,
+    namespace: Option<&str>,
+) -> Result<()> {
+    let mut context = config
+        .contexts
+        .iter()
+        .find(|c| c.name == context_name)
+        .cloned()
+        .ok_or_else(|| KhelpError::ContextNotFound(context_name.to_string()))?;
+    if let Some(namespace) = namespace {
+        context.context.namespace = Some(namespace.to_string());
+    }
+
+    let session_config = KubeConfig {
+        api_version: config.api_version.clone(),
+        clusters: config
+            .clusters
+            .iter()
+            .filter(|c| c.name == context.context.cluster)
+            .cloned()
+            .collect(),
+        current_context: context_name.to_string(),
+        kind: config.kind.clone(),
+        preferences: config.preferences.clone(),
+        users: config
+            .users
+            .iter()
+            .filter(|u| u.name == context.context.user)
+            .cloned()
+            .collect(),
+        contexts: vec![context],
+    };
+    let yaml =
+        serde_yaml::to_string(&session_config).context("Failed to serialize session config")?;
+
+    let path = session_config_path(context_name)?;
+    let dir = path.parent().expect("session configs live in a directory");
+    create_private_dir(dir)?;
+
+    // Written to a temporary file first, which is readable only by the current user since it
+    // holds credentials, then moved over the previous session config for this context
+    let mut file = tempfile::NamedTempFile::new_in(dir)
+        .with_context(|| format!("Failed to create session config in {}", dir.display()))?;
+    file.write_all(yaml.as_bytes())
+        .with_context(|| format!("Failed to write session config: {}", path.display()))?;
+    file.persist(&path)
+        .with_context(|| format!("Failed to write session config: {}", path.display()))?;
+    debug!("Wrote session config to {}", path.display());
+
+    println!("export KUBECONFIG={}", shell_quote(&path.to_string_lossy()));
+    status!(
+        "Using context {} in this shell only (session config: {})",
+        style(context_name).green().bold(),
+        path.display()
     );
 
     Ok(())
 }
+
+/// Where the session config for a context is written
+///
+/// Each context has one file in the `sessions` directory next to khelp's settings, so
+/// exporting a context again overwrites the copy from last time instead of piling up
+/// credentials. The file stays until the next export of that context or until it is deleted.
+fn session_config_path(context_name: &str) -> Result<PathBuf> {
+    let settings = settings_path().context("Could not determine khelp's config directory")?;
+    let readable: String = context_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    // The hash keeps names that only differ in replaced characters apart
+    let file_name = format!(
+        "{}-{:08x}.yaml",
+        readable,
+        stable_hash(context_name.as_bytes()) as u32
+    );
+    Ok(settings.with_file_name("sessions").join(file_name))
+}
+
+/// Create a directory only the current user can read
+fn create_private_dir(dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create directory: {}", dir.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700))
+            .with_context(|| format!("Failed to set permissions on {}", dir.display()))?;
+    }
+    Ok(())
+}
+
+/// Quote a value for POSIX shells
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Find the context a possibly abbreviated name refers to
+///
+/// An exact match wins, then a unique prefix, then a unique substring.
+fn resolve_partial_name(config: &KubeConfig, name: &str) -> Result<String> {
+    if config.contexts.iter().any(|c| c.name == name) {
+        return Ok(name.to_string());
+    }
+
+    let by_prefix: Vec<&str> = config
+        .contexts
+        .iter()
+        .map(|c| c.name.as_str())
+        .filter(|candidate| candidate.starts_with(name))
+        .collect();
+    let candidates = if by_prefix.is_empty() {
+        config
+            .contexts
+            .iter()
+            .map(|c| c.name.as_str())
+            .filter(|candidate| candidate.contains(name))
+            .collect()
+    } else {
+        by_prefix
+    };
+
+    match candidates.as_slice() {
+        [] => anyhow::bail!(KhelpError::ContextNotFound(name.to_string())),
+        [only] => {
+            debug!("Resolved '{}' to context '{}'", name, only);
+            Ok(only.to_string())
+        }
+        many => anyhow::bail!(
+            "'{}' matches several contexts, be more specific:\n  {}",
+            name,
+            many.join("\n  ")
+        ),
+    }
+}
+
+/// Name of the context's cluster if it skips TLS certificate verification
+fn insecure_cluster<'a>(config: &'a KubeConfig, context_name: &str) -> Option<&'a str> {
+    let context = config.contexts.iter().find(|c| c.name == context_name)?;
+    config
+        .clusters
+        .iter()
+        .find(|c| c.name == context.context.cluster)
+        .filter(|c| c.cluster.insecure_skip_tls_verify == Some(true))
+        .map(|c| c.name.as_str())
+}
+
+/// Warn if the context's user authenticates via an exec plugin that isn't installed
+fn warn_missing_exec_plugin(config: &KubeConfig, context_name: &str) {
+    let Some(exec) = config
+        .contexts
+        .iter()
+        .find(|c| c.name == context_name)
+        .and_then(|c| config.users.iter().find(|u| u.name == c.context.user))
+        .and_then(|u| u.user.exec.as_ref())
+    else {
+        return;
+    };
+
+    if let Some(path) = find_executable(&exec.command) {
+        debug!("Exec credential plugin resolved to: {}", path.display());
+        return;
+    }
+
+    eprintln!(
+        "{} credential plugin '{}' was not found on PATH",
+        style("Warning:").yellow().bold(),
+        style(&exec.command).cyan()
+    );
+    if let Some(hint) = &exec.install_hint {
+        eprintln!("{}", hint.trim_end());
+    }
+}
+
+/// Resolve a command the way a shell would, returning its path if it exists
+fn find_executable(command: &str) -> Option<PathBuf> {
+    let command_path = Path::new(command);
+    if command_path.components().count() > 1 {
+        return command_path.is_file().then(|| command_path.to_path_buf());
+    }
+
+    // Windows resolves bare names using the extensions listed in PATHEXT
+    let extensions: Vec<String> = if cfg!(target_os = "windows") {
+        env::var("PATHEXT")
+            .unwrap_or_else(|_| ".EXE;.CMD;.BAT;.COM".to_string())
+            .split(';')
+            .map(|ext| ext.to_string())
+            .chain(std::iter::once(String::new()))
+            .collect()
+    } else {
+        vec![String::new()]
+    };
+
+    let path_var = env::var_os("PATH")?;
+    env::split_paths(&path_var).find_map(|dir| {
+        extensions.iter().find_map(|ext| {
+            let candidate = dir.join(format!("{}{}", command, ext));
+            candidate.is_file().then_some(candidate)
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::kubernetes::{ContextData, ContextEntry};
+
+    fn config_with(names: &[&str]) -> KubeConfig {
+        KubeConfig {
+            contexts: names
+                .iter()
+                .map(|name| ContextEntry {
+                    name: name.to_string(),
+                    context: ContextData {
+                        cluster: "cluster".to_string(),
+                        user: "user".to_string(),
+                        namespace: None,
+                    },
+                })
+                .collect(),
+            ..KubeConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(
+            shell_quote("/tmp/khelp-session-1.yaml"),
+            "'/tmp/khelp-session-1.yaml'"
+        );
+        assert_eq!(shell_quote("/tmp/it's here"), "'/tmp/it'\\''s here'");
+    }
+
+    #[test]
+    fn test_session_config_path_is_stable_per_context() {
+        let prod = session_config_path("prod-eu").unwrap();
+        assert_eq!(prod, session_config_path("prod-eu").unwrap());
+        assert_eq!(prod.parent().unwrap().file_name().unwrap(), "sessions");
+        assert!(
+            prod.file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with("prod-eu-")
+        );
+
+        let arn = session_config_path("arn:aws:eks:eu-west-1:1234:cluster/prod").unwrap();
+        assert!(
+            arn.file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with("arn_aws_eks_eu-west-1_1234_cluster_prod-")
+        );
+        assert_ne!(
+            arn,
+            session_config_path("arn_aws:eks:eu-west-1:1234:cluster/prod").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_resolve_partial_name() {
+        let config = config_with(&["prod", "prod-eu", "staging-us", "dev-us"]);
+
+        // Exact matches win even when they are also a prefix of others
+        assert_eq!(resolve_partial_name(&config, "prod").unwrap(), "prod");
+        assert_eq!(resolve_partial_name(&config, "prod-").unwrap(), "prod-eu");
+        assert_eq!(resolve_partial_name(&config, "stag").unwrap(), "staging-us");
+        assert_eq!(resolve_partial_name(&config, "ging").unwrap(), "staging-us");
+
+        let ambiguous = resolve_partial_name(&config, "-us").unwrap_err();
+        assert!(ambiguous.to_string().contains("staging-us"));
+        assert!(ambiguous.to_string().contains("dev-us"));
+
+        let missing = resolve_partial_name(&config, "qa").unwrap_err();
+        assert!(matches!(
+            missing.downcast_ref::<KhelpError>(),
+            Some(KhelpError::ContextNotFound(_))
+        ));
+    }
+}
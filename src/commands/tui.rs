@@ -0,0 +1,427 @@
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::io::{self, IsTerminal};
+
+use crate::commands::delete::delete_contexts;
+use crate::commands::edit::{FieldChanges, edit_context};
+use crate::commands::output::ensure_can_prompt;
+use crate::commands::switch::switch_context;
+use crate::config::kubernetes::{ContextEntry, KubeConfig};
+use crate::config::operations::load_kube_config;
+
+const HELP: &str = "↑/↓ move  Enter switch  d delete  e edit  q quit";
+
+/// What to do with the highlighted context once the browser hands control back
+#[derive(Debug, PartialEq)]
+enum Action {
+    Switch(String),
+    Delete(String),
+    Edit(String),
+    Quit,
+}
+
+/// Whether the browser closes after an action, or shows the list again with a message
+#[derive(Debug, PartialEq)]
+enum Step {
+    Exit,
+    Continue(Option<String>),
+}
+
+/// The commands the browser hands the highlighted context to
+trait ContextCommands {
+    fn switch(&mut self, name: String) -> Result<()>;
+    fn delete(&mut self, name: String) -> Result<()>;
+    fn edit(&mut self, name: String) -> Result<()>;
+}
+
+/// The regular `switch`, `delete`, and `edit` commands, confirmation prompts included
+struct RegularCommands;
+
+impl ContextCommands for RegularCommands {
+    fn switch(&mut self, name: String) -> Result<()> {
+        switch_context(Some(name), None, false, false, false).map(|_| ())
+    }
+
+    fn delete(&mut self, name: String) -> Result<()> {
+        delete_contexts(vec![name], false, false, false, None)
+    }
+
+    fn edit(&mut self, name: String) -> Result<()> {
+        edit_context(Some(name), false, false, FieldChanges::default())
+    }
+}
+
+/// Takes over the terminal while the browser is drawn, and hands it back when
+/// dropped, so errors and prompts don't leave the terminal in a bad state
+struct Screen(DefaultTerminal);
+
+impl Screen {
+    fn enter() -> Result<Self> {
+        Ok(Screen(ratatui::try_init()?))
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = ratatui::try_restore();
+    }
+}
+
+/// Browse contexts in a full-screen list, with the highlighted context's details beside it
+///
+/// Enter switches to the highlighted context and exits. `d` and `e` delete or edit it
+/// with the regular commands, including their confirmation prompts, then return to the list.
+pub fn browse_contexts() -> Result<()> {
+    ensure_can_prompt()?;
+    if !io::stdout().is_terminal() {
+        anyhow::bail!("browse needs a terminal on stdout; use `khelp list` in scripts");
+    }
+
+    let mut config = load_kube_config()?;
+    let mut state = ListState::default().with_selected(Some(
+        config
+            .contexts
+            .iter()
+            .position(|c| c.name == config.current_context)
+            .unwrap_or(0),
+    ));
+    let mut message = None;
+
+    loop {
+        if config.contexts.is_empty() {
+            println!("No contexts left to browse");
+            return Ok(());
+        }
+        let last = config.contexts.len() - 1;
+        state.select(Some(state.selected().unwrap_or(0).min(last)));
+
+        let action = {
+            let mut screen = Screen::enter()?;
+            pick_action(&mut screen.0, &config, &mut state, message.take())?
+        };
+
+        match dispatch(action, &mut RegularCommands)? {
+            Step::Exit => return Ok(()),
+            Step::Continue(error) => message = error,
+        }
+
+        config = load_kube_config()?;
+    }
+}
+
+/// Run an action with the given commands
+///
+/// Switching ends the browser, and so does a failed switch, whose error is returned.
+/// A failed delete or edit is shown in the browser instead.
+fn dispatch(action: Action, commands: &mut impl ContextCommands) -> Result<Step> {
+    let result = match action {
+        Action::Quit => return Ok(Step::Exit),
+        Action::Switch(name) => {
+            commands.switch(name)?;
+            return Ok(Step::Exit);
+        }
+        Action::Delete(name) => commands.delete(name),
+        Action::Edit(name) => commands.edit(name),
+    };
+    Ok(Step::Continue(result.err().map(|e| format!("{:#}", e))))
+}
+
+/// Draw the browser and handle keys until one of them asks for an action
+fn pick_action(
+    terminal: &mut DefaultTerminal,
+    config: &KubeConfig,
+    state: &mut ListState,
+    message: Option<String>,
+) -> Result<Action> {
+    loop {
+        terminal.draw(|frame| render(frame, config, state, message.as_deref()))?;
+
+        if let Event::Key(key) = event::read()?
+            && let Some(action) = handle_key(key, config, state)
+        {
+            return Ok(action);
+        }
+    }
+}
+
+/// Move the highlight for navigation keys, or return the action a key asks for
+fn handle_key(key: KeyEvent, config: &KubeConfig, state: &mut ListState) -> Option<Action> {
+    if key.kind != KeyEventKind::Press {
+        return None;
+    }
+
+    let last = config.contexts.len().saturating_sub(1);
+    let selected = state.selected().unwrap_or(0).min(last);
+    let name = || config.contexts[selected].name.clone();
+    match key.code {
+        // Ctrl-C arrives as a key press while the terminal is in raw mode
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            return Some(Action::Quit);
+        }
+        KeyCode::Up | KeyCode::Char('k') => state.select(Some(selected.saturating_sub(1))),
+        KeyCode::Down | KeyCode::Char('j') => state.select(Some((selected + 1).min(last))),
+        KeyCode::Home => state.select(Some(0)),
+        KeyCode::End => state.select(Some(last)),
+        KeyCode::Enter => return Some(Action::Switch(name())),
+        KeyCode::Char('d') => return Some(Action::Delete(name())),
+        KeyCode::Char('e') => return Some(Action::Edit(name())),
+        KeyCode::Char('q') | KeyCode::Esc => return Some(Action::Quit),
+        _ => {}
+    }
+    None
+}
+
+/// Describe a context for the details panel, resolving its cluster and user
+fn details_lines<'a>(config: &'a KubeConfig, context: &'a ContextEntry) -> Vec<Line<'a>> {
+    let value = |text: &'a str| Span::styled(text, Style::default().fg(Color::Cyan));
+    let missing = |text: &'a str| Span::styled(text, Style::default().fg(Color::Red));
+
+    let server = config
+        .clusters
+        .iter()
+        .find(|c| c.name == context.context.cluster)
+        .map(|c| value(&c.cluster.server))
+        .unwrap_or_else(|| missing("(cluster not found)"));
+    let auth = config
+        .users
+        .iter()
+        .find(|u| u.name == context.context.user)
+        .map(|u| value(u.user.auth_type()))
+        .unwrap_or_else(|| missing("(user not found)"));
+
+    vec![
+        Line::styled(
+            context.name.as_str(),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Line::default(),
+        Line::from(vec![
+            Span::raw("Cluster:   "),
+            value(&context.context.cluster),
+        ]),
+        Line::from(vec![Span::raw("Server:    "), server]),
+        Line::from(vec![Span::raw("User:      "), value(&context.context.user)]),
+        Line::from(vec![Span::raw("Auth:      "), auth]),
+        Line::from(vec![
+            Span::raw("Namespace: "),
+            value(context.context.namespace.as_deref().unwrap_or("default")),
+        ]),
+    ]
+}
+
+/// Draw the context list on the left and the selected context's details on the right
+fn render(frame: &mut Frame, config: &KubeConfig, state: &mut ListState, message: Option<&str>) {
+    let [title_area, main_area, footer_area] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(1),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [list_area, details_area] =
+        Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+            .areas(main_area);
+
+    frame.render_widget(
+        Line::from(vec![
+            Span::styled(
+                "Kubernetes",
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(format!(" contexts ({})", config.contexts.len())),
+        ]),
+        title_area,
+    );
+
+    let items: Vec<ListItem> = config
+        .contexts
+        .iter()
+        .map(|context| {
+            let marker = if context.name == config.current_context {
+                "* "
+            } else {
+                "  "
+            };
+            ListItem::new(format!("{}{}", marker, context.name))
+        })
+        .collect();
+    let list = List::new(items)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol(">");
+    frame.render_stateful_widget(list, list_area, state);
+
+    if let Some(context) = state.selected().and_then(|i| config.contexts.get(i)) {
+        frame.render_widget(
+            Paragraph::new(details_lines(config, context))
+                .block(Block::new().borders(Borders::LEFT)),
+            details_area,
+        );
+    }
+
+    let footer = match message {
+        Some(message) => Line::styled(message, Style::default().fg(Color::Red)),
+        None => Line::styled(HELP, Style::default().add_modifier(Modifier::DIM)),
+    };
+    frame.render_widget(footer, footer_area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    fn config() -> KubeConfig {
+        serde_yaml::from_str(
+            r#"
+current-context: dev
+clusters:
+- name: dev
+  cluster: {server: "https://dev.example.com"}
+users:
+- name: alice
+  user: {token: a}
+contexts:
+- name: dev
+  context: {cluster: dev, user: alice}
+- name: staging
+  context: {cluster: dev, user: alice, namespace: staging}
+- name: prod
+  context: {cluster: missing, user: alice}
+"#,
+        )
+        .expect("Failed to parse test config")
+    }
+
+    fn press(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    /// Records which command was called with which context
+    #[derive(Default)]
+    struct RecordingCommands {
+        calls: Vec<String>,
+        fail: bool,
+    }
+
+    impl RecordingCommands {
+        fn record(&mut self, command: &str, name: String) -> Result<()> {
+            self.calls.push(format!("{} {}", command, name));
+            if self.fail {
+                anyhow::bail!("{} failed", command);
+            }
+            Ok(())
+        }
+    }
+
+    impl ContextCommands for RecordingCommands {
+        fn switch(&mut self, name: String) -> Result<()> {
+            self.record("switch", name)
+        }
+
+        fn delete(&mut self, name: String) -> Result<()> {
+            self.record("delete", name)
+        }
+
+        fn edit(&mut self, name: String) -> Result<()> {
+            self.record("edit", name)
+        }
+    }
+
+    #[test]
+    fn test_handle_key_moves_and_picks_actions() {
+        let config = config();
+        let mut state = ListState::default().with_selected(Some(0));
+
+        assert_eq!(handle_key(press(KeyCode::Down), &config, &mut state), None);
+        assert_eq!(
+            handle_key(press(KeyCode::Char('j')), &config, &mut state),
+            None
+        );
+        // Moving past the last context stays on it
+        assert_eq!(handle_key(press(KeyCode::Down), &config, &mut state), None);
+        assert_eq!(state.selected(), Some(2));
+        assert_eq!(handle_key(press(KeyCode::Up), &config, &mut state), None);
+        assert_eq!(state.selected(), Some(1));
+
+        assert_eq!(
+            handle_key(press(KeyCode::Enter), &config, &mut state),
+            Some(Action::Switch("staging".to_string()))
+        );
+        assert_eq!(
+            handle_key(press(KeyCode::Char('d')), &config, &mut state),
+            Some(Action::Delete("staging".to_string()))
+        );
+        assert_eq!(
+            handle_key(press(KeyCode::Char('e')), &config, &mut state),
+            Some(Action::Edit("staging".to_string()))
+        );
+        assert_eq!(
+            handle_key(
+                KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
+                &config,
+                &mut state
+            ),
+            Some(Action::Quit)
+        );
+
+        let mut release = press(KeyCode::Enter);
+        release.kind = KeyEventKind::Release;
+        assert_eq!(handle_key(release, &config, &mut state), None);
+    }
+
+    #[test]
+    fn test_dispatch_hands_context_to_commands() {
+        let mut commands = RecordingCommands::default();
+        let switch = dispatch(Action::Switch("dev".to_string()), &mut commands);
+        assert_eq!(switch.unwrap(), Step::Exit);
+        let delete = dispatch(Action::Delete("staging".to_string()), &mut commands);
+        assert_eq!(delete.unwrap(), Step::Continue(None));
+        let edit = dispatch(Action::Edit("prod".to_string()), &mut commands);
+        assert_eq!(edit.unwrap(), Step::Continue(None));
+        assert_eq!(dispatch(Action::Quit, &mut commands).unwrap(), Step::Exit);
+        assert_eq!(
+            commands.calls,
+            ["switch dev", "delete staging", "edit prod"]
+        );
+
+        // A failed delete or edit is shown in the browser, a failed switch ends it
+        let mut failing = RecordingCommands {
+            fail: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            dispatch(Action::Delete("dev".to_string()), &mut failing).unwrap(),
+            Step::Continue(Some("delete failed".to_string()))
+        );
+        assert!(dispatch(Action::Switch("dev".to_string()), &mut failing).is_err());
+    }
+
+    #[test]
+    fn test_render_shows_contexts_and_details() {
+        let config = config();
+        let mut state = ListState::default().with_selected(Some(2));
+        let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+        terminal
+            .draw(|frame| render(frame, &config, &mut state, None))
+            .unwrap();
+
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("* dev"));
+        assert!(screen.contains("staging"));
+        assert!(screen.contains("(cluster not found)"));
+        assert!(screen.contains("q quit"));
+    }
+}
@@ -1,43 +1,60 @@
+use std::time::Duration;
 #[cfg(feature = "self_update")]
-use {anyhow::Result, console::style, log::debug};
+use {
+    crate::commands::output::status, crate::utils::UpdateError, anyhow::Result, console::style,
+    log::debug,
+};
 
 /// Check for updates to khelp
 ///
 /// Connects to GitHub to check if a new version is available.
 /// If apply is true, automatically downloads and applies the update.
+/// Checking GitHub for a newer release gives up after timeout; the download doesn't.
 #[cfg(feature = "self_update")]
-pub fn handle_update(apply: bool) -> Result<()> {
-    debug!("Running update command with apply: {}", apply);
+pub fn handle_update(apply: bool, timeout: Duration) -> Result<()> {
+    debug!(
+        "Running update command with apply: {}, timeout: {:?}",
+        apply, timeout
+    );
 
     if apply {
-        eprintln!("Checking for and applying updates...");
+        status!("Checking for and applying updates...");
 
-        match crate::utils::update() {
+        match crate::utils::update(timeout) {
             Ok(()) => {
-                eprintln!("{}", style("Update completed successfully!").green().bold());
+                status!("{}", style("Update completed successfully!").green().bold());
                 Ok(())
             }
+            Err(e @ UpdateError::TimedOut(_)) => {
+                anyhow::bail!("Update check timed out: {}; khelp was not changed", e);
+            }
             Err(e) => {
                 anyhow::bail!("Failed to update: {}", e);
             }
         }
     } else {
-        eprintln!("Checking for updates...");
+        status!("Checking for updates...");
 
-        match crate::utils::check_for_updates() {
+        match crate::utils::check_for_updates(timeout) {
             Ok(update_available) => {
                 if update_available {
-                    eprintln!(
+                    status!(
                         "{}",
                         style("A new version of khelp is available!").green().bold()
                     );
-                    eprintln!("Run {} to update", style("khelp update --apply").cyan());
+                    status!("Run {} to update", style("khelp update --apply").cyan());
                     Ok(())
                 } else {
-                    eprintln!("{}", style("Already at the latest version.").green());
+                    status!("{}", style("Already at the latest version.").green());
                     Ok(())
                 }
             }
+            Err(e @ UpdateError::TimedOut(_)) => {
+                anyhow::bail!(
+                    "Update check timed out: {}; it is unknown whether an update is available (try a longer --timeout)",
+                    e
+                );
+            }
             Err(e) => {
                 anyhow::bail!("Failed to check for updates: {}", e);
             }
@@ -48,7 +65,7 @@ pub fn handle_update(apply: bool) -> Result<()> {
 /// Stub function for when self_update feature is not enabled
 #[cfg(not(feature = "self_update"))]
 #[allow(dead_code)]
-pub fn handle_update(_apply: bool) -> anyhow::Result<()> {
+pub fn handle_update(_apply: bool, _timeout: Duration) -> anyhow::Result<()> {
     eprintln!(
         "The update feature is not enabled in this build. Please install khelp with the 'self_update' feature to enable updates."
     );
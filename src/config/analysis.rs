@@ -0,0 +1,647 @@
+use std::collections::{BTreeMap, HashSet};
+
+use super::kubernetes::{ContextEntry, KubeConfig};
+
+/// Finds clusters and users that no context references
+///
+/// Returns the orphaned cluster names and user names, in file order.
+pub fn find_orphans(config: &KubeConfig) -> (Vec<String>, Vec<String>) {
+    let referenced_clusters: HashSet<&str> = config
+        .contexts
+        .iter()
+        .map(|c| c.context.cluster.as_str())
+        .collect();
+    let referenced_users: HashSet<&str> = config
+        .contexts
+        .iter()
+        .map(|c| c.context.user.as_str())
+        .collect();
+
+    let orphaned_clusters = config
+        .clusters
+        .iter()
+        .filter(|c| !referenced_clusters.contains(c.name.as_str()))
+        .map(|c| c.name.clone())
+        .collect();
+    let orphaned_users = config
+        .users
+        .iter()
+        .filter(|u| !referenced_users.contains(u.name.as_str()))
+        .map(|u| u.name.clone())
+        .collect();
+
+    (orphaned_clusters, orphaned_users)
+}
+
+/// Finds contexts that can't be used because their cluster or user is missing
+pub fn find_broken_contexts(config: &KubeConfig) -> Vec<String> {
+    let cluster_names: HashSet<&str> = config.clusters.iter().map(|c| c.name.as_str()).collect();
+    let user_names: HashSet<&str> = config.users.iter().map(|u| u.name.as_str()).collect();
+
+    config
+        .contexts
+        .iter()
+        .filter(|c| {
+            !cluster_names.contains(c.context.cluster.as_str())
+                || !user_names.contains(c.context.user.as_str())
+        })
+        .map(|c| c.name.clone())
+        .collect()
+}
+
+/// Describes every required field that is empty
+///
+/// Covers context, cluster, and user names, the cluster and user a context refers to,
+/// and cluster servers. Unnamed entries are identified by their position, counting from 1.
+pub fn find_empty_fields(config: &KubeConfig) -> Vec<String> {
+    fn describe(kind: &str, index: usize, name: &str) -> String {
+        if name.trim().is_empty() {
+            format!("{} #{}", kind, index + 1)
+        } else {
+            format!("{} '{}'", kind, name)
+        }
+    }
+
+    let mut problems = Vec::new();
+    for (index, context) in config.contexts.iter().enumerate() {
+        let entry = describe("context", index, &context.name);
+        if context.name.trim().is_empty() {
+            problems.push(format!("{} has an empty name", entry));
+        }
+        if context.context.cluster.trim().is_empty() {
+            problems.push(format!("{} has an empty cluster", entry));
+        }
+        if context.context.user.trim().is_empty() {
+            problems.push(format!("{} has an empty user", entry));
+        }
+    }
+    for (index, cluster) in config.clusters.iter().enumerate() {
+        let entry = describe("cluster", index, &cluster.name);
+        if cluster.name.trim().is_empty() {
+            problems.push(format!("{} has an empty name", entry));
+        }
+        if cluster.cluster.server.trim().is_empty() {
+            problems.push(format!("{} has an empty server", entry));
+        }
+    }
+    for (index, user) in config.users.iter().enumerate() {
+        if user.name.trim().is_empty() {
+            problems.push(format!("{} has an empty name", describe("user", index, "")));
+        }
+    }
+    problems
+}
+
+/// Describes each cluster and user that sets contradicting fields
+///
+/// Nothing is changed; kubectl would either reject these entries or silently pick one
+/// of the fields, which is usually not what was intended.
+pub fn find_conflicting_fields(config: &KubeConfig) -> Vec<String> {
+    let clusters = config
+        .clusters
+        .iter()
+        .map(|c| ("cluster", &c.name, c.cluster.conflicting_fields()));
+    let users = config
+        .users
+        .iter()
+        .map(|u| ("user", &u.name, u.user.conflicting_fields()));
+
+    clusters
+        .chain(users)
+        .flat_map(|(kind, name, conflicts)| {
+            conflicts.into_iter().map(move |fields| {
+                format!(
+                    "{} '{}' sets conflicting fields: {}",
+                    kind,
+                    name,
+                    fields.join(", ")
+                )
+            })
+        })
+        .collect()
+}
+
+/// Explains why a namespace name breaks the Kubernetes naming rules, if it does
+///
+/// Namespaces must be RFC 1123 labels: at most 63 lowercase letters, digits, and `-`,
+/// starting and ending with a letter or digit.
+pub fn invalid_namespace_reason(namespace: &str) -> Option<&'static str> {
+    if namespace.is_empty() {
+        Some("is empty")
+    } else if namespace.len() > 63 {
+        Some("is longer than 63 characters")
+    } else if !namespace
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        Some("may only contain lowercase letters, digits, and '-'")
+    } else if namespace.starts_with('-') || namespace.ends_with('-') {
+        Some("must start and end with a letter or digit")
+    } else {
+        None
+    }
+}
+
+/// Finds groups of contexts that point at the same cluster, user, and namespace
+///
+/// A missing namespace counts as `default`. Each group lists its context names in
+/// file order, and groups are ordered by their first context.
+pub fn find_duplicate_contexts(config: &KubeConfig) -> Vec<Vec<String>> {
+    fn target(context: &ContextEntry) -> (&str, &str, &str) {
+        (
+            context.context.cluster.as_str(),
+            context.context.user.as_str(),
+            context.context.namespace.as_deref().unwrap_or("default"),
+        )
+    }
+
+    let mut groups: Vec<Vec<&ContextEntry>> = Vec::new();
+    for context in &config.contexts {
+        match groups
+            .iter_mut()
+            .find(|group| target(group[0]) == target(context))
+        {
+            Some(group) => group.push(context),
+            None => groups.push(vec![context]),
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter(|group| group.len() > 1)
+        .map(|group| group.iter().map(|c| c.name.clone()).collect())
+        .collect()
+}
+
+/// Match text against a simple glob where `*` matches any run and `?` any single character
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` seen and where in the text it started matching
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            // Let the last `*` swallow one more character and retry
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// A context whose namespace differs from what the namespace rules expect
+#[derive(Debug, PartialEq)]
+pub struct NamespaceDeviation {
+    pub context: String,
+    pub actual: Option<String>,
+    pub expected: String,
+}
+
+/// Finds the namespace a context should use according to glob rules
+///
+/// rules maps context name patterns to namespaces. When several patterns match, the
+/// longest one wins, so `dev-eu-*` can override `dev-*`.
+pub fn expected_namespace<'a>(
+    rules: &'a BTreeMap<String, String>,
+    context: &str,
+) -> Option<&'a str> {
+    rules
+        .iter()
+        .filter(|(pattern, _)| glob_match(pattern, context))
+        .max_by_key(|(pattern, _)| pattern.len())
+        .map(|(_, namespace)| namespace.as_str())
+}
+
+/// Finds contexts whose namespace doesn't match the one their rule expects
+///
+/// Contexts that no rule matches are left out. A context without a namespace only
+/// deviates if the rule expects something other than `default`.
+pub fn find_namespace_deviations(
+    config: &KubeConfig,
+    rules: &BTreeMap<String, String>,
+) -> Vec<NamespaceDeviation> {
+    config
+        .contexts
+        .iter()
+        .filter_map(|context| {
+            let expected = expected_namespace(rules, &context.name)?;
+            let actual = context.context.namespace.as_deref().unwrap_or("default");
+            (actual != expected).then(|| NamespaceDeviation {
+                context: context.name.clone(),
+                actual: context.context.namespace.clone(),
+                expected: expected.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Describes every reference in the config that doesn't resolve
+///
+/// Covers duplicate entry names, contexts whose cluster or user is missing, and a
+/// current-context that names no context. A consistent config yields no problems.
+pub fn find_reference_problems(config: &KubeConfig) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    let mut seen = HashSet::new();
+    for name in config.contexts.iter().map(|c| &c.name) {
+        if !seen.insert(name) {
+            problems.push(format!("duplicate context '{}'", name));
+        }
+    }
+    let mut seen = HashSet::new();
+    for name in config.clusters.iter().map(|c| &c.name) {
+        if !seen.insert(name) {
+            problems.push(format!("duplicate cluster '{}'", name));
+        }
+    }
+    let mut seen = HashSet::new();
+    for name in config.users.iter().map(|u| &u.name) {
+        if !seen.insert(name) {
+            problems.push(format!("duplicate user '{}'", name));
+        }
+    }
+
+    for context in &config.contexts {
+        if !config
+            .clusters
+            .iter()
+            .any(|c| c.name == context.context.cluster)
+        {
+            problems.push(format!(
+                "context '{}' references missing cluster '{}'",
+                context.name, context.context.cluster
+            ));
+        }
+        if !config.users.iter().any(|u| u.name == context.context.user) {
+            problems.push(format!(
+                "context '{}' references missing user '{}'",
+                context.name, context.context.user
+            ));
+        }
+    }
+
+    if !config.current_context.is_empty()
+        && !config
+            .contexts
+            .iter()
+            .any(|c| c.name == config.current_context)
+    {
+        problems.push(format!(
+            "current-context '{}' does not exist",
+            config.current_context
+        ));
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(yaml: &str) -> KubeConfig {
+        serde_yaml::from_str(yaml).expect("Failed to parse test config")
+    }
+
+    #[test]
+    fn test_shared_references_are_not_orphans() {
+        let config = parse(
+            r#"
+clusters:
+- name: shared
+  cluster: {server: "https://shared.example.com"}
+users:
+- name: admin
+  user: {token: a}
+contexts:
+- name: dev
+  context: {cluster: shared, user: admin}
+- name: staging
+  context: {cluster: shared, user: admin, namespace: staging}
+"#,
+        );
+
+        let (clusters, users) = find_orphans(&config);
+        assert!(clusters.is_empty());
+        assert!(users.is_empty());
+    }
+
+    #[test]
+    fn test_unreferenced_entries_are_orphans() {
+        let config = parse(
+            r#"
+clusters:
+- name: used
+  cluster: {server: "https://used.example.com"}
+- name: stale
+  cluster: {server: "https://stale.example.com"}
+users:
+- name: alice
+  user: {token: a}
+- name: bob
+  user: {token: b}
+contexts:
+- name: dev
+  context: {cluster: used, user: alice}
+"#,
+        );
+
+        let (clusters, users) = find_orphans(&config);
+        assert_eq!(clusters, vec!["stale".to_string()]);
+        assert_eq!(users, vec!["bob".to_string()]);
+    }
+
+    #[test]
+    fn test_everything_is_orphaned_without_contexts() {
+        let config = parse(
+            r#"
+clusters:
+- name: one
+  cluster: {server: "https://one.example.com"}
+- name: two
+  cluster: {server: "https://two.example.com"}
+users:
+- name: alice
+  user: {token: a}
+"#,
+        );
+
+        let (clusters, users) = find_orphans(&config);
+        assert_eq!(clusters, vec!["one".to_string(), "two".to_string()]);
+        assert_eq!(users, vec!["alice".to_string()]);
+    }
+
+    #[test]
+    fn test_find_broken_contexts() {
+        let config = parse(
+            r#"
+clusters:
+- name: real
+  cluster: {server: "https://real.example.com"}
+users:
+- name: alice
+  user: {token: a}
+contexts:
+- name: good
+  context: {cluster: real, user: alice}
+- name: no-cluster
+  context: {cluster: missing, user: alice}
+- name: no-user
+  context: {cluster: real, user: missing}
+"#,
+        );
+
+        assert_eq!(
+            find_broken_contexts(&config),
+            vec!["no-cluster".to_string(), "no-user".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_find_reference_problems() {
+        let config = parse(
+            r#"
+current-context: gone
+clusters:
+- name: real
+  cluster: {server: "https://real.example.com"}
+- name: real
+  cluster: {server: "https://copy.example.com"}
+users:
+- name: alice
+  user: {token: a}
+contexts:
+- name: good
+  context: {cluster: real, user: alice}
+- name: dangling
+  context: {cluster: missing, user: nobody}
+"#,
+        );
+
+        assert_eq!(
+            find_reference_problems(&config),
+            vec![
+                "duplicate cluster 'real'".to_string(),
+                "context 'dangling' references missing cluster 'missing'".to_string(),
+                "context 'dangling' references missing user 'nobody'".to_string(),
+                "current-context 'gone' does not exist".to_string(),
+            ]
+        );
+
+        let consistent = parse(
+            r#"
+current-context: good
+clusters:
+- name: real
+  cluster: {server: "https://real.example.com"}
+users:
+- name: alice
+  user: {token: a}
+contexts:
+- name: good
+  context: {cluster: real, user: alice}
+"#,
+        );
+        assert!(find_reference_problems(&consistent).is_empty());
+    }
+
+    #[test]
+    fn test_find_duplicate_contexts() {
+        let config = parse(
+            r#"
+contexts:
+- name: prod
+  context: {cluster: prod, user: admin}
+- name: dev
+  context: {cluster: dev, user: admin}
+- name: prod-copy
+  context: {cluster: prod, user: admin, namespace: default}
+- name: prod-monitoring
+  context: {cluster: prod, user: admin, namespace: monitoring}
+- name: dev-imported
+  context: {cluster: dev, user: admin}
+"#,
+        );
+
+        assert_eq!(
+            find_duplicate_contexts(&config),
+            vec![
+                vec!["prod".to_string(), "prod-copy".to_string()],
+                vec!["dev".to_string(), "dev-imported".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_namespace_deviations() {
+        let config = parse(
+            r#"
+apiVersion: v1
+kind: Config
+current-context: ""
+clusters: []
+contexts:
+- name: dev-a
+  context: {cluster: c, user: u, namespace: sandbox}
+- name: dev-b
+  context: {cluster: c, user: u, namespace: default}
+- name: dev-eu-a
+  context: {cluster: c, user: u, namespace: sandbox}
+- name: prod
+  context: {cluster: c, user: u}
+- name: staging
+  context: {cluster: c, user: u}
+users: []
+"#,
+        );
+        let rules = BTreeMap::from([
+            ("dev-*".to_string(), "sandbox".to_string()),
+            ("dev-eu-*".to_string(), "sandbox-eu".to_string()),
+            ("prod".to_string(), "default".to_string()),
+        ]);
+
+        assert_eq!(expected_namespace(&rules, "dev-eu-a"), Some("sandbox-eu"));
+        assert_eq!(expected_namespace(&rules, "staging"), None);
+        assert_eq!(
+            find_namespace_deviations(&config, &rules),
+            vec![
+                NamespaceDeviation {
+                    context: "dev-b".to_string(),
+                    actual: Some("default".to_string()),
+                    expected: "sandbox".to_string(),
+                },
+                NamespaceDeviation {
+                    context: "dev-eu-a".to_string(),
+                    actual: Some("sandbox".to_string()),
+                    expected: "sandbox-eu".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_empty_fields() {
+        let config = parse(
+            r#"
+apiVersion: v1
+kind: Config
+current-context: ""
+clusters:
+- name: c1
+  cluster:
+    server: ""
+- name: ""
+  cluster:
+    server: https://c2:6443
+contexts:
+- name: ""
+  context:
+    cluster: c1
+    user: u1
+- name: ctx
+  context:
+    cluster: " "
+    user: ""
+users:
+- name: u1
+  user: {}
+- name: ""
+  user: {}
+"#,
+        );
+
+        assert_eq!(
+            find_empty_fields(&config),
+            vec![
+                "context #1 has an empty name",
+                "context 'ctx' has an empty cluster",
+                "context 'ctx' has an empty user",
+                "cluster 'c1' has an empty server",
+                "cluster #2 has an empty name",
+                "user #2 has an empty name",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_conflicting_fields() {
+        let config = parse(
+            r#"
+apiVersion: v1
+kind: Config
+current-context: ""
+clusters:
+- name: c1
+  cluster:
+    server: https://c1:6443
+    certificate-authority: /etc/ca.crt
+    certificate-authority-data: Y2E=
+- name: c2
+  cluster:
+    server: https://c2:6443
+contexts: []
+users:
+- name: u1
+  user:
+    token: secret
+    exec:
+      apiVersion: client.authentication.k8s.io/v1
+      command: aws
+"#,
+        );
+
+        assert_eq!(
+            find_conflicting_fields(&config),
+            vec![
+                "cluster 'c1' sets conflicting fields: certificate-authority, certificate-authority-data",
+                "user 'u1' sets conflicting fields: token, exec",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_namespace_reason() {
+        for valid in ["default", "kube-system", "team-1", "a", &"a".repeat(63)] {
+            assert_eq!(invalid_namespace_reason(valid), None, "{}", valid);
+        }
+        for invalid in [
+            "",
+            "My_NS",
+            "Prod",
+            "dev.team",
+            "-dev",
+            "dev-",
+            &"a".repeat(64),
+        ] {
+            assert!(invalid_namespace_reason(invalid).is_some(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("team-*", "team-a"));
+        assert!(glob_match("team-*", "team-"));
+        assert!(glob_match("*-prod", "eu-west-prod"));
+        assert!(glob_match("team-?-dev", "team-a-dev"));
+        assert!(glob_match("*a*b*", "xxaxxbxx"));
+        assert!(glob_match("*", ""));
+
+        assert!(!glob_match("team-*", "other-team-a"));
+        assert!(!glob_match("team-?-dev", "team-ab-dev"));
+        assert!(!glob_match("*-prod", "prod-eu"));
+        assert!(!glob_match("exact", "exactly"));
+    }
+}
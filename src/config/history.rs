@@ -0,0 +1,112 @@
+use anyhow::{Context, Result};
+use log::debug;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::operations::configured_kube_config_path;
+
+/// Maximum number of previous contexts kept in the history file
+pub const MAX_HISTORY: usize = 20;
+
+/// Name of the history file, stored next to the kubeconfig it belongs to
+const HISTORY_FILE_NAME: &str = ".khelp_history";
+
+/// Gets the path to the switch history file for the kubeconfig in use
+pub fn history_path() -> Result<PathBuf> {
+    let config_path = configured_kube_config_path()?;
+    let dir = config_path
+        .parent()
+        .context("Could not determine kubeconfig directory")?;
+    Ok(dir.join(HISTORY_FILE_NAME))
+}
+
+/// Loads previously active contexts from a history file, oldest first
+///
+/// A missing file is treated as an empty history.
+pub fn load_history_from(path: &Path) -> Result<Vec<String>> {
+    if !path.exists() {
+        debug!("No history file at {}", path.display());
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read history file: {}", path.display()))?;
+
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Appends a context to a history file, keeping at most `MAX_HISTORY` entries
+///
+/// # Arguments
+///
+/// * `path` - Path to the history file
+/// * `context_name` - The context that was active before switching
+pub fn push_history_to(path: &Path, context_name: &str) -> Result<()> {
+    let mut history = load_history_from(path)?;
+
+    // Consecutive switches to the same context don't need repeated entries
+    if history.last().map(String::as_str) != Some(context_name) {
+        history.push(context_name.to_string());
+    }
+    if history.len() > MAX_HISTORY {
+        history.drain(..history.len() - MAX_HISTORY);
+    }
+
+    let mut content = history.join("\n");
+    content.push('\n');
+    fs::write(path, content)
+        .with_context(|| format!("Failed to write history file: {}", path.display()))?;
+
+    debug!("Recorded '{}' in {}", context_name, path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_missing_history_is_empty() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let history = load_history_from(&temp_dir.path().join(HISTORY_FILE_NAME))
+            .expect("Failed to load history");
+        assert!(history.is_empty());
+    }
+
+    #[test]
+    fn test_push_history_skips_repeats() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join(HISTORY_FILE_NAME);
+
+        push_history_to(&path, "dev").expect("Failed to push");
+        push_history_to(&path, "dev").expect("Failed to push");
+        push_history_to(&path, "prod").expect("Failed to push");
+
+        let history = load_history_from(&path).expect("Failed to load history");
+        assert_eq!(history, vec!["dev", "prod"]);
+    }
+
+    #[test]
+    fn test_push_history_is_capped() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join(HISTORY_FILE_NAME);
+
+        for i in 0..MAX_HISTORY + 5 {
+            push_history_to(&path, &format!("ctx-{}", i)).expect("Failed to push");
+        }
+
+        let history = load_history_from(&path).expect("Failed to load history");
+        assert_eq!(history.len(), MAX_HISTORY);
+        assert_eq!(history.first().map(String::as_str), Some("ctx-5"));
+        assert_eq!(
+            history.last().cloned(),
+            Some(format!("ctx-{}", MAX_HISTORY + 4))
+        );
+    }
+}
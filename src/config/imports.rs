@@ -0,0 +1,196 @@
+use anyhow::{Context, Result};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::kubernetes::KubeConfig;
+use super::operations::stable_hash;
+
+/// Suffix of the file recording what `add` imported, stored next to the kubeconfig
+/// it describes as `.<file name>.khelp_imports`
+const IMPORTS_FILE_SUFFIX: &str = "khelp_imports";
+
+/// The entries a kubeconfig holds from one import source, by name, with a
+/// fingerprint of each entry's data
+///
+/// Fingerprints are a stable hash of the entry's JSON, so they can be compared across
+/// khelp builds; one that stops matching makes `add --prune` leave the entry alone.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportRecord {
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub clusters: BTreeMap<String, u64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub contexts: BTreeMap<String, u64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub users: BTreeMap<String, u64>,
+}
+
+impl ImportRecord {
+    /// Fingerprints of every cluster, context, and user in a config
+    pub fn of(config: &KubeConfig) -> Self {
+        Self {
+            clusters: config
+                .clusters
+                .iter()
+                .map(|c| (c.name.clone(), fingerprint(&c.cluster)))
+                .collect(),
+            contexts: config
+                .contexts
+                .iter()
+                .map(|c| (c.name.clone(), fingerprint(&c.context)))
+                .collect(),
+            users: config
+                .users
+                .iter()
+                .map(|u| (u.name.clone(), fingerprint(&u.user)))
+                .collect(),
+        }
+    }
+
+    /// The entries of this record that a config holds under the same name with the same data
+    pub fn retained_in(&self, config: &KubeConfig) -> Self {
+        let current = Self::of(config);
+        let keep = |recorded: &BTreeMap<String, u64>, current: &BTreeMap<String, u64>| {
+            recorded
+                .iter()
+                .filter(|(name, print)| current.get(*name) == Some(print))
+                .map(|(name, print)| (name.clone(), *print))
+                .collect()
+        };
+
+        Self {
+            clusters: keep(&self.clusters, &current.clusters),
+            contexts: keep(&self.contexts, &current.contexts),
+            users: keep(&self.users, &current.users),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clusters.is_empty() && self.contexts.is_empty() && self.users.is_empty()
+    }
+}
+
+/// Fingerprints an entry's data so a later edit to it can be detected
+fn fingerprint<T: Serialize>(data: &T) -> u64 {
+    stable_hash(&serde_json::to_vec(data).unwrap_or_default())
+}
+
+/// Gets the path of the import record for a kubeconfig file
+pub fn imports_path(config_path: &Path) -> Result<PathBuf> {
+    let file_name = config_path
+        .file_name()
+        .context("Could not determine kubeconfig file name")?;
+    Ok(config_path.with_file_name(format!(
+        ".{}.{}",
+        file_name.to_string_lossy(),
+        IMPORTS_FILE_SUFFIX
+    )))
+}
+
+/// Loads what was imported from each source, keyed by source
+///
+/// A missing file is treated as no imports.
+pub fn load_imports_from(path: &Path) -> Result<BTreeMap<String, ImportRecord>> {
+    if !path.exists() {
+        debug!("No import record at {}", path.display());
+        return Ok(BTreeMap::new());
+    }
+
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read import record: {}", path.display()))?;
+    serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse import record: {}", path.display()))
+}
+
+/// Replaces the record of what was imported from a source, dropping it when empty
+pub fn save_import_record_to(path: &Path, source: &str, record: ImportRecord) -> Result<()> {
+    let mut imports = load_imports_from(path)?;
+    if record.is_empty() {
+        if imports.remove(source).is_none() {
+            return Ok(());
+        }
+    } else {
+        imports.insert(source.to_string(), record);
+    }
+
+    let content = serde_yaml::to_string(&imports).context("Failed to serialize import record")?;
+    fs::write(path, content)
+        .with_context(|| format!("Failed to write import record: {}", path.display()))?;
+
+    debug!("Recorded imports from '{}' in {}", source, path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn parse(yaml: &str) -> KubeConfig {
+        serde_yaml::from_str(yaml).expect("Failed to parse test config")
+    }
+
+    #[test]
+    fn test_retained_in_drops_renamed_and_edited_entries() {
+        let source = parse(
+            r#"
+clusters:
+- name: dev
+  cluster: {server: "https://dev.example.com"}
+- name: prod
+  cluster: {server: "https://prod.example.com"}
+users:
+- name: admin
+  user: {token: a}
+"#,
+        );
+        let config = parse(
+            r#"
+clusters:
+- name: dev
+  cluster: {server: "https://dev.example.com"}
+- name: prod
+  cluster: {server: "https://edited.example.com"}
+users:
+- name: admin-imported
+  user: {token: a}
+"#,
+        );
+
+        let record = ImportRecord::of(&source).retained_in(&config);
+        assert_eq!(record.clusters.keys().collect::<Vec<_>>(), vec!["dev"]);
+        assert!(record.users.is_empty());
+    }
+
+    #[test]
+    fn test_fingerprint_is_stable() {
+        // Stored in import records, so it must not change between builds
+        let data = BTreeMap::from([("server", "https://a")]);
+        assert_eq!(fingerprint(&data), 0xf432f0f4cf5cabf6);
+    }
+
+    #[test]
+    fn test_import_records_round_trip() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = imports_path(&temp_dir.path().join("config")).expect("Failed to get path");
+        assert!(path.ends_with(".config.khelp_imports"));
+        assert!(load_imports_from(&path).expect("Failed to load").is_empty());
+
+        let record = ImportRecord::of(&parse(
+            r#"
+contexts:
+- name: dev
+  context: {cluster: dev, user: admin}
+"#,
+        ));
+        save_import_record_to(&path, "/tmp/team.yaml", record.clone()).expect("Failed to save");
+        let imports = load_imports_from(&path).expect("Failed to load");
+        assert_eq!(imports.get("/tmp/team.yaml"), Some(&record));
+
+        save_import_record_to(&path, "/tmp/team.yaml", ImportRecord::default())
+            .expect("Failed to save");
+        assert!(load_imports_from(&path).expect("Failed to load").is_empty());
+    }
+}
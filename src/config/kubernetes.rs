@@ -34,12 +34,131 @@ impl Default for KubeConfig {
             contexts: Vec::new(),
             current_context: String::new(),
             kind: "Config".to_string(),
-            preferences: Some(Preferences {}),
+            preferences: Some(Preferences::default()),
             users: Vec::new(),
         }
     }
 }
 
+impl KubeConfig {
+    /// Drop fields that only restate kubectl's defaults, for a more portable file
+    ///
+    /// Removes empty preferences, `namespace: default`, `false` flags, and empty
+    /// values. The result is still a valid kubeconfig.
+    pub fn minify(&mut self) {
+        if self
+            .preferences
+            .as_ref()
+            .is_some_and(|preferences| preferences.entries.is_empty())
+        {
+            self.preferences = None;
+        }
+
+        for cluster in &mut self.clusters {
+            let cluster = &mut cluster.cluster;
+            for field in [
+                &mut cluster.certificate_authority_data,
+                &mut cluster.certificate_authority,
+                &mut cluster.tls_server_name,
+                &mut cluster.proxy_url,
+            ] {
+                drop_empty(field);
+            }
+            for flag in [
+                &mut cluster.insecure_skip_tls_verify,
+                &mut cluster.disable_compression,
+            ] {
+                if *flag == Some(false) {
+                    *flag = None;
+                }
+            }
+        }
+
+        for context in &mut self.contexts {
+            let namespace = &mut context.context.namespace;
+            if namespace.as_deref() == Some("default") {
+                *namespace = None;
+            }
+            drop_empty(namespace);
+        }
+
+        for user in &mut self.users {
+            let user = &mut user.user;
+            for field in [
+                &mut user.client_certificate_data,
+                &mut user.client_certificate,
+                &mut user.client_key_data,
+                &mut user.client_key,
+                &mut user.token,
+                &mut user.token_file,
+                &mut user.impersonate,
+                &mut user.impersonate_uid,
+                &mut user.username,
+                &mut user.password,
+            ] {
+                drop_empty(field);
+            }
+            if user.impersonate_groups.as_ref().is_some_and(Vec::is_empty) {
+                user.impersonate_groups = None;
+            }
+
+            if let Some(exec) = &mut user.exec {
+                if exec.args.as_ref().is_some_and(Vec::is_empty) {
+                    exec.args = None;
+                }
+                if exec.env.as_ref().is_some_and(Vec::is_empty) {
+                    exec.env = None;
+                }
+                if exec.provide_cluster_info == Some(false) {
+                    exec.provide_cluster_info = None;
+                }
+                drop_empty(&mut exec.install_hint);
+                drop_empty(&mut exec.interactive_mode);
+            }
+        }
+    }
+
+    /// Write out the defaults kubectl assumes for unset fields, the opposite of `minify`
+    ///
+    /// Sets `namespace: default`, `false` TLS and compression flags, empty
+    /// preferences, and the exec plugin's `provideClusterInfo` and `interactiveMode`.
+    pub fn fill_defaults(&mut self) {
+        self.preferences.get_or_insert_with(Preferences::default);
+
+        for cluster in &mut self.clusters {
+            let cluster = &mut cluster.cluster;
+            for flag in [
+                &mut cluster.insecure_skip_tls_verify,
+                &mut cluster.disable_compression,
+            ] {
+                flag.get_or_insert(false);
+            }
+        }
+
+        for context in &mut self.contexts {
+            let namespace = &mut context.context.namespace;
+            drop_empty(namespace);
+            namespace.get_or_insert_with(|| "default".to_string());
+        }
+
+        for user in &mut self.users {
+            if let Some(exec) = &mut user.user.exec {
+                exec.provide_cluster_info.get_or_insert(false);
+                drop_empty(&mut exec.interactive_mode);
+                exec.interactive_mode
+                    .get_or_insert_with(|| "IfAvailable".to_string());
+            }
+        }
+    }
+}
+
+/// Treat an empty string the same as an absent value
+fn drop_empty(field: &mut Option<String>) {
+    if field.as_ref().is_some_and(String::is_empty) {
+        *field = None;
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ClusterEntry {
     pub cluster: ClusterData,
@@ -75,6 +194,17 @@ pub struct ClusterData {
     pub disable_compression: Option<bool>,
 }
 
+impl ClusterData {
+    /// Groups of fields that are set together but contradict each other
+    pub fn conflicting_fields(&self) -> Vec<Vec<&'static str>> {
+        if self.certificate_authority.is_some() && self.certificate_authority_data.is_some() {
+            vec![vec!["certificate-authority", "certificate-authority-data"]]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ContextEntry {
     pub context: ContextData,
@@ -128,11 +258,110 @@ pub struct UserData {
     pub exec: Option<ExecConfig>,
 }
 
+/// Placeholder written in place of credential values when redacting
+pub const REDACTED: &str = "REDACTED";
+
+/// Fragments of key and variable names whose values are credentials
+const SECRET_MARKERS: [&str; 3] = ["token", "secret", "password"];
+
+/// Whether a config key or environment variable name looks like it holds a credential
+pub fn looks_secret(name: &str) -> bool {
+    let name = name.to_lowercase();
+    SECRET_MARKERS.iter().any(|marker| name.contains(marker))
+}
+
+impl UserData {
+    /// Short label for the authentication mechanism this user is configured with
+    pub fn auth_type(&self) -> &'static str {
+        if self.exec.is_some() {
+            "exec"
+        } else if self.auth_provider.is_some() {
+            "auth-provider"
+        } else if self.token.is_some() || self.token_file.is_some() {
+            "token"
+        } else if self.client_certificate_data.is_some() || self.client_certificate.is_some() {
+            "client-certificate"
+        } else if self.username.is_some() || self.password.is_some() {
+            "basic"
+        } else {
+            "none"
+        }
+    }
+
+    /// Groups of fields that are set together but contradict each other
+    ///
+    /// A file and inline data for the same certificate or key conflict, and so does
+    /// configuring more than one way to authenticate.
+    pub fn conflicting_fields(&self) -> Vec<Vec<&'static str>> {
+        let mut conflicts = Vec::new();
+        if self.client_certificate.is_some() && self.client_certificate_data.is_some() {
+            conflicts.push(vec!["client-certificate", "client-certificate-data"]);
+        }
+        if self.client_key.is_some() && self.client_key_data.is_some() {
+            conflicts.push(vec!["client-key", "client-key-data"]);
+        }
+
+        let methods: Vec<&'static str> = [
+            (self.token.is_some(), "token"),
+            (
+                self.token.is_none() && self.token_file.is_some(),
+                "tokenFile",
+            ),
+            (
+                self.client_certificate_data.is_some(),
+                "client-certificate-data",
+            ),
+            (
+                self.client_certificate_data.is_none() && self.client_certificate.is_some(),
+                "client-certificate",
+            ),
+            (
+                self.username.is_some() || self.password.is_some(),
+                "username/password",
+            ),
+            (self.auth_provider.is_some(), "auth-provider"),
+            (self.exec.is_some(), "exec"),
+        ]
+        .into_iter()
+        .filter_map(|(set, field)| set.then_some(field))
+        .collect();
+        if methods.len() > 1 {
+            conflicts.push(methods);
+        }
+
+        conflicts
+    }
+
+    /// Replace credential values with a placeholder so the entry can be shared safely
+    pub fn redact(&mut self) {
+        for field in [
+            &mut self.token,
+            &mut self.token_file,
+            &mut self.client_key_data,
+            &mut self.client_certificate_data,
+            &mut self.password,
+        ] {
+            if field.is_some() {
+                *field = Some(REDACTED.to_string());
+            }
+        }
+
+        if let Some(auth_provider) = &mut self.auth_provider {
+            for (key, value) in auth_provider.config.iter_mut() {
+                if looks_secret(key) {
+                    *value = REDACTED.to_string();
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AuthProviderConfig {
     pub name: String,
-    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
-    pub config: std::collections::HashMap<String, String>,
+    // Ordered so configs serialize the same way every time
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub config: std::collections::BTreeMap<String, String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -152,14 +381,60 @@ pub struct ExecConfig {
     pub interactive_mode: Option<String>,
 }
 
+impl ExecConfig {
+    /// The command line the plugin runs, with arguments containing whitespace quoted
+    pub fn command_line(&self) -> String {
+        std::iter::once(&self.command)
+            .chain(self.args.iter().flatten())
+            .map(|part| {
+                if part.is_empty() || part.contains(char::is_whitespace) {
+                    format!("'{}'", part)
+                } else {
+                    part.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct EnvVar {
     pub name: String,
     pub value: String,
 }
 
+impl AuthProviderConfig {
+    /// `key=value` for each config entry, sorted by key, with credentials redacted
+    pub fn display_config_redacted(&self) -> Vec<String> {
+        self.config
+            .iter()
+            .map(|(key, value)| {
+                let value = if looks_secret(key) { REDACTED } else { value };
+                format!("{}={}", key, value)
+            })
+            .collect()
+    }
+}
+
+impl EnvVar {
+    /// `NAME=value`, or `NAME=REDACTED` when the variable looks like a credential
+    pub fn display_redacted(&self) -> String {
+        let value = if looks_secret(&self.name) {
+            REDACTED
+        } else {
+            &self.value
+        };
+        format!("{}={}", self.name, value)
+    }
+}
+
+/// The `preferences` block, kept verbatim since khelp doesn't interpret it
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
-pub struct Preferences {}
+pub struct Preferences {
+    #[serde(flatten)]
+    pub entries: serde_yaml::Mapping,
+}
 
 #[cfg(test)]
 mod tests {
@@ -329,6 +604,122 @@ users:
         assert!(yaml.contains("password: secret"));
     }
 
+    #[test]
+    fn test_user_auth_type() {
+        let exec_user = UserData {
+            token: Some("ignored".to_string()),
+            exec: Some(ExecConfig::default()),
+            ..Default::default()
+        };
+        assert_eq!(exec_user.auth_type(), "exec");
+
+        let cert_user = UserData {
+            client_certificate: Some("/path/to/cert".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(cert_user.auth_type(), "client-certificate");
+
+        assert_eq!(UserData::default().auth_type(), "none");
+    }
+
+    #[test]
+    fn test_conflicting_fields() {
+        let cluster = ClusterData {
+            certificate_authority: Some("/path/to/ca".to_string()),
+            certificate_authority_data: Some("Y2E=".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            cluster.conflicting_fields(),
+            vec![vec!["certificate-authority", "certificate-authority-data"]]
+        );
+
+        let user = UserData {
+            token: Some("token".to_string()),
+            token_file: Some("/path/to/token".to_string()),
+            client_certificate_data: Some("Y2VydA==".to_string()),
+            client_key: Some("/path/to/key".to_string()),
+            client_key_data: Some("a2V5".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            user.conflicting_fields(),
+            vec![
+                vec!["client-key", "client-key-data"],
+                vec!["token", "client-certificate-data"],
+            ]
+        );
+
+        // A certificate with its key is one way to authenticate, not two
+        let cert_user = UserData {
+            client_certificate: Some("/path/to/cert".to_string()),
+            client_key: Some("/path/to/key".to_string()),
+            ..Default::default()
+        };
+        assert!(cert_user.conflicting_fields().is_empty());
+    }
+
+    #[test]
+    fn test_user_redaction() {
+        let mut user = UserData {
+            token: Some("bearer-token-here".to_string()),
+            client_key_data: Some("key-data".to_string()),
+            username: Some("admin".to_string()),
+            auth_provider: Some(AuthProviderConfig {
+                name: "oidc".to_string(),
+                config: [
+                    ("client-id".to_string(), "my-client".to_string()),
+                    ("id-token".to_string(), "secret-id-token".to_string()),
+                    ("client-secret".to_string(), "shh".to_string()),
+                ]
+                .into_iter()
+                .collect(),
+            }),
+            ..Default::default()
+        };
+
+        user.redact();
+
+        assert_eq!(user.token.as_deref(), Some(REDACTED));
+        assert_eq!(user.client_key_data.as_deref(), Some(REDACTED));
+        assert_eq!(user.password, None, "Unset fields should stay unset");
+        assert_eq!(user.username.as_deref(), Some("admin"));
+
+        let config = &user.auth_provider.unwrap().config;
+        assert_eq!(config["client-id"], "my-client");
+        assert_eq!(config["id-token"], REDACTED);
+        assert_eq!(config["client-secret"], REDACTED);
+    }
+
+    #[test]
+    fn test_exec_command_line_and_env_display() {
+        let exec = ExecConfig {
+            command: "aws".to_string(),
+            args: Some(vec![
+                "eks".to_string(),
+                "get-token".to_string(),
+                "--cluster-name".to_string(),
+                "my cluster".to_string(),
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(
+            exec.command_line(),
+            "aws eks get-token --cluster-name 'my cluster'"
+        );
+
+        let plain = EnvVar {
+            name: "AWS_PROFILE".to_string(),
+            value: "prod".to_string(),
+        };
+        let secret = EnvVar {
+            name: "AWS_SESSION_TOKEN".to_string(),
+            value: "s3cr3t".to_string(),
+        };
+        assert_eq!(plain.display_redacted(), "AWS_PROFILE=prod");
+        assert_eq!(secret.display_redacted(), "AWS_SESSION_TOKEN=REDACTED");
+    }
+
     #[test]
     fn test_round_trip_serialization() {
         let original_yaml = r#"
@@ -364,4 +755,126 @@ users:
         assert_eq!(config.contexts.len(), config2.contexts.len());
         assert_eq!(config.users.len(), config2.users.len());
     }
+
+    #[test]
+    fn test_minify_drops_default_fields() {
+        let yaml = r#"
+apiVersion: v1
+clusters:
+- cluster:
+    server: https://127.0.0.1:6443
+    insecure-skip-tls-verify: false
+    proxy-url: ""
+  name: test-cluster
+contexts:
+- context:
+    cluster: test-cluster
+    user: test-user
+    namespace: default
+  name: test-context
+- context:
+    cluster: test-cluster
+    user: test-user
+    namespace: dev
+  name: dev-context
+current-context: test-context
+kind: Config
+preferences: {}
+users:
+- name: test-user
+  user:
+    token: test-token
+    as-groups: []
+"#;
+
+        let mut config: KubeConfig = serde_yaml::from_str(yaml).expect("Failed to deserialize");
+        config.minify();
+        let serialized = serde_yaml::to_string(&config).expect("Failed to serialize");
+
+        assert!(!serialized.contains("preferences"));
+        assert!(!serialized.contains("insecure-skip-tls-verify"));
+        assert!(!serialized.contains("proxy-url"));
+        assert!(!serialized.contains("as-groups"));
+        assert!(!serialized.contains("namespace: default"));
+        assert!(serialized.contains("namespace: dev"));
+        assert!(serialized.contains("token: test-token"));
+
+        let reparsed: KubeConfig =
+            serde_yaml::from_str(&serialized).expect("Minified config should still parse");
+        assert_eq!(reparsed.contexts.len(), 2);
+    }
+
+    #[test]
+    fn test_fill_defaults_materializes_unset_fields() {
+        let yaml = r#"
+apiVersion: v1
+clusters:
+- cluster:
+    server: https://127.0.0.1:6443
+    insecure-skip-tls-verify: true
+  name: test-cluster
+contexts:
+- context:
+    cluster: test-cluster
+    user: test-user
+  name: test-context
+- context:
+    cluster: test-cluster
+    user: test-user
+    namespace: dev
+  name: dev-context
+current-context: test-context
+kind: Config
+users:
+- name: test-user
+  user:
+    exec:
+      apiVersion: client.authentication.k8s.io/v1beta1
+      command: aws
+"#;
+
+        let mut config: KubeConfig = serde_yaml::from_str(yaml).expect("Failed to deserialize");
+        config.fill_defaults();
+
+        assert!(config.preferences.is_some());
+        let cluster = &config.clusters[0].cluster;
+        assert_eq!(cluster.insecure_skip_tls_verify, Some(true));
+        assert_eq!(cluster.disable_compression, Some(false));
+        assert_eq!(
+            config.contexts[0].context.namespace.as_deref(),
+            Some("default")
+        );
+        assert_eq!(config.contexts[1].context.namespace.as_deref(), Some("dev"));
+        let exec = config.users[0].user.exec.as_ref().expect("exec config");
+        assert_eq!(exec.provide_cluster_info, Some(false));
+        assert_eq!(exec.interactive_mode.as_deref(), Some("IfAvailable"));
+    }
+
+    #[test]
+    fn test_auth_provider_display_redacts_secrets() {
+        let auth_provider = AuthProviderConfig {
+            name: "oidc".to_string(),
+            config: [
+                ("idp-issuer-url", "https://accounts.example.com"),
+                ("client-id", "kubernetes"),
+                ("client-secret", "shh"),
+                ("id-token", "eyJhbGciOi"),
+                ("refresh-token", "r3fr3sh"),
+            ]
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect(),
+        };
+
+        assert_eq!(
+            auth_provider.display_config_redacted(),
+            vec![
+                "client-id=kubernetes",
+                "client-secret=REDACTED",
+                "id-token=REDACTED",
+                "idp-issuer-url=https://accounts.example.com",
+                "refresh-token=REDACTED",
+            ]
+        );
+    }
 }
@@ -1,2 +1,7 @@
+pub mod analysis;
+pub mod history;
+pub mod imports;
 pub mod kubernetes;
 pub mod operations;
+pub mod settings;
+pub mod usage;
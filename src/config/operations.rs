@@ -1,14 +1,24 @@
-use anyhow::{Context, Result};
 use dirs::home_dir;
 use log::debug;
+use std::borrow::Cow;
 use std::cell::RefCell;
+use std::ffi::OsString;
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use super::kubernetes::KubeConfig;
+use crate::error::{KhelpError, Result};
+
+/// How many backups of each config are kept in a backup directory
+const MAX_BACKUPS_PER_CONFIG: usize = 5;
 
 thread_local! {
     static KUBECONFIG_PATH_OVERRIDE: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+    static BACKUP_ENABLED: RefCell<bool> = const { RefCell::new(false) };
+    static BACKUP_DIR: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+    static ALLOW_INVALID_SERVERS: RefCell<bool> = const { RefCell::new(false) };
 }
 
 /// Set a custom kubeconfig path to use instead of the default
@@ -18,26 +28,101 @@ pub fn set_kubeconfig_path(path: PathBuf) {
     });
 }
 
-/// Gets the path to the Kubernetes config file
-pub fn get_kube_config_path() -> Result<PathBuf> {
-    // Check for override first
+/// Enable or disable backing up the existing config before each save
+pub fn set_backup_enabled(enabled: bool) {
+    BACKUP_ENABLED.with(|b| {
+        *b.borrow_mut() = enabled;
+    });
+}
+
+/// Write backups into a directory instead of next to the config, or next to it for None
+pub fn set_backup_dir(dir: Option<PathBuf>) {
+    BACKUP_DIR.with(|d| {
+        *d.borrow_mut() = dir;
+    });
+}
+
+/// Allow saving configs whose cluster server URLs fail validation
+pub fn set_allow_invalid_servers(allow: bool) {
+    ALLOW_INVALID_SERVERS.with(|a| {
+        *a.borrow_mut() = allow;
+    });
+}
+
+/// Finds the user's home directory
+///
+/// Falls back to `$HOME` and `$USERPROFILE` when the platform lookup fails, which
+/// happens for some service accounts and minimal containers.
+pub fn home_directory() -> Option<PathBuf> {
+    home_directory_from(
+        home_dir(),
+        std::env::var_os("HOME"),
+        std::env::var_os("USERPROFILE"),
+    )
+}
+
+fn home_directory_from(
+    platform_home: Option<PathBuf>,
+    home: Option<OsString>,
+    user_profile: Option<OsString>,
+) -> Option<PathBuf> {
+    platform_home.or_else(|| {
+        [home, user_profile]
+            .into_iter()
+            .flatten()
+            .map(PathBuf::from)
+            .find(|path| path.is_absolute())
+    })
+}
+
+/// Finds a kubeconfig path set in the environment, with the variable it came from
+///
+/// `KHELP_KUBECONFIG` wins over `KUBECONFIG`, so khelp can manage a different file
+/// than the one kubectl reads. Empty variables are ignored.
+pub fn kubeconfig_from_env() -> Option<(PathBuf, &'static str)> {
+    kubeconfig_from_vars(
+        std::env::var_os("KHELP_KUBECONFIG"),
+        std::env::var_os("KUBECONFIG"),
+    )
+}
+
+fn kubeconfig_from_vars(
+    khelp_kubeconfig: Option<OsString>,
+    kubeconfig: Option<OsString>,
+) -> Option<(PathBuf, &'static str)> {
+    [
+        (khelp_kubeconfig, "KHELP_KUBECONFIG"),
+        (kubeconfig, "KUBECONFIG"),
+    ]
+    .into_iter()
+    .find_map(|(value, name)| {
+        value
+            .filter(|value| !value.is_empty())
+            .map(|value| (PathBuf::from(value), name))
+    })
+}
+
+/// Gets the path khelp is configured to use, without checking that it exists
+///
+/// This is the `--kubeconfig`/`KHELP_KUBECONFIG`/`KUBECONFIG` override if set,
+/// otherwise `~/.kube/config`.
+pub fn configured_kube_config_path() -> Result<PathBuf> {
     let override_path = KUBECONFIG_PATH_OVERRIDE.with(|p| p.borrow().clone());
     if let Some(path) = override_path {
-        if !path.exists() {
-            anyhow::bail!("Kubernetes config file not found at: {}", path.display());
-        }
         debug!("Using overridden kubeconfig path: {}", path.display());
         return Ok(path);
     }
 
-    let home = home_dir().context("Could not find home directory")?;
-    let kube_config_path = home.join(".kube").join("config");
+    let home = home_directory().ok_or(KhelpError::NoHomeDirectory)?;
+    Ok(home.join(".kube").join("config"))
+}
+
+/// Gets the path to the Kubernetes config file
+pub fn get_kube_config_path() -> Result<PathBuf> {
+    let kube_config_path = configured_kube_config_path()?;
 
     if !kube_config_path.exists() {
-        anyhow::bail!(
-            "Kubernetes config file not found at: {}",
-            kube_config_path.display()
-        );
+        return Err(KhelpError::ConfigNotFound(kube_config_path));
     }
 
     debug!(
@@ -61,39 +146,30 @@ pub fn load_kube_config() -> Result<KubeConfig> {
 pub fn load_kube_config_from(path: &Path) -> Result<KubeConfig> {
     debug!("Loading Kubernetes config from: {}", path.display());
 
-    let config_content = fs::read_to_string(path)
-        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+    let config_content = fs::read_to_string(path).map_err(KhelpError::io(format!(
+        "Failed to read config file: {}",
+        path.display()
+    )))?;
+    let config_content = normalize_line_endings(&config_content);
 
     // Check for empty or whitespace-only content
     let trimmed = config_content.trim();
     if trimmed.is_empty() {
-        anyhow::bail!(
-            "Config file is empty: {}\n\nA valid kubeconfig file must contain at least:\n  apiVersion: v1\n  kind: Config\n  clusters: []\n  contexts: []\n  users: []\n  current-context: \"\"",
-            path.display()
-        );
+        return Err(KhelpError::EmptyConfig(path.to_path_buf()));
     }
 
-    // Provide more helpful error messages for common issues
-    let config: KubeConfig = serde_yaml::from_str(&config_content).map_err(|e| {
-        let error_msg = e.to_string();
-        if error_msg.contains("missing field `apiVersion`") || error_msg.contains("missing field `kind`") {
-            anyhow::anyhow!(
-                "Invalid kubeconfig file: {}\n\nThe file appears to be missing required fields. A valid kubeconfig must include:\n  - apiVersion: v1\n  - kind: Config\n  - clusters, contexts, users arrays\n  - current-context\n\nOriginal error: {}",
-                path.display(),
-                error_msg
-            )
-        } else if error_msg.contains("missing field") {
-            anyhow::anyhow!(
-                "Invalid kubeconfig file: {}\n\n{}\n\nPlease check that your kubeconfig file has all required fields.",
-                path.display(),
-                error_msg
-            )
-        } else {
-            anyhow::anyhow!(
-                "Failed to parse kubeconfig file: {}\n\n{}",
-                path.display(),
-                error_msg
-            )
+    let config: KubeConfig = serde_yaml::from_str(&config_content).map_err(|error| {
+        let error_msg = error.to_string();
+        let line = error
+            .location()
+            .and_then(|location| config_content.lines().nth(location.line().checked_sub(1)?))
+            .map(str::to_string);
+        KhelpError::ParseError {
+            path: path.to_path_buf(),
+            missing_required: error_msg.contains("missing field `apiVersion`")
+                || error_msg.contains("missing field `kind`"),
+            error,
+            line,
         }
     })?;
 
@@ -104,25 +180,58 @@ pub fn load_kube_config_from(path: &Path) -> Result<KubeConfig> {
     Ok(config)
 }
 
+/// Converts CRLF (and lone CR) line endings to LF
+///
+/// Configs edited on Windows often use CRLF. khelp always writes LF, so normalizing
+/// on read keeps content-based logic and diffs consistent across platforms.
+pub fn normalize_line_endings(content: &str) -> Cow<'_, str> {
+    if content.contains('\r') {
+        Cow::Owned(content.replace("\r\n", "\n").replace('\r', "\n"))
+    } else {
+        Cow::Borrowed(content)
+    }
+}
+
 /// Loads the Kubernetes config from the default location, or returns an empty config
 /// if the file is empty or missing. This is useful for commands that need to initialize
 /// a new config (like `add`).
 pub fn load_kube_config_or_default() -> Result<KubeConfig> {
-    match load_kube_config() {
+    default_if_missing(load_kube_config())
+}
+
+/// Loads the Kubernetes config from a custom path, or returns an empty config if the
+/// file is empty or missing
+///
+/// # Arguments
+///
+/// * `path` - Path to the kubeconfig file, which need not exist yet
+pub fn load_kube_config_or_default_from(path: &Path) -> Result<KubeConfig> {
+    if !path.exists() {
+        debug!(
+            "{} does not exist, using empty default config",
+            path.display()
+        );
+        return Ok(KubeConfig::default());
+    }
+    default_if_missing(load_kube_config_from(path))
+}
+
+fn default_if_missing(result: Result<KubeConfig>) -> Result<KubeConfig> {
+    match result {
         Ok(config) => Ok(config),
-        Err(e) => {
-            let error_msg = e.to_string();
-            // If config is empty or missing required fields, return a default empty config
-            if error_msg.contains("Config file is empty")
-                || error_msg.contains("missing required fields")
-                || error_msg.contains("Kubernetes config file not found")
-            {
-                debug!("Main config is empty or not found, using empty default config");
-                Ok(KubeConfig::default())
-            } else {
-                Err(e)
-            }
+        // If config is empty, missing, or missing required fields, return a default empty config
+        Err(
+            KhelpError::EmptyConfig(_)
+            | KhelpError::ConfigNotFound(_)
+            | KhelpError::ParseError {
+                missing_required: true,
+                ..
+            },
+        ) => {
+            debug!("Config is empty or not found, using empty default config");
+            Ok(KubeConfig::default())
         }
+        Err(e) => Err(e),
     }
 }
 
@@ -145,19 +254,23 @@ pub fn get_kube_config_path_or_create() -> Result<PathBuf> {
         if let Some(parent) = path.parent()
             && !parent.exists()
         {
-            std::fs::create_dir_all(parent)
-                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+            std::fs::create_dir_all(parent).map_err(KhelpError::io(format!(
+                "Failed to create directory: {}",
+                parent.display()
+            )))?;
         }
         return Ok(path);
     }
 
-    let home = home_dir().context("Could not find home directory")?;
+    let home = home_directory().ok_or(KhelpError::NoHomeDirectory)?;
     let kube_dir = home.join(".kube");
 
     // Create the .kube directory if it doesn't exist
     if !kube_dir.exists() {
-        std::fs::create_dir_all(&kube_dir)
-            .with_context(|| format!("Failed to create directory: {}", kube_dir.display()))?;
+        std::fs::create_dir_all(&kube_dir).map_err(KhelpError::io(format!(
+            "Failed to create directory: {}",
+            kube_dir.display()
+        )))?;
         debug!("Created .kube directory: {}", kube_dir.display());
     }
 
@@ -171,18 +284,281 @@ pub fn get_kube_config_path_or_create() -> Result<PathBuf> {
 /// * `config` - The Kubernetes configuration to save
 /// * `path` - Path where the config should be saved
 pub fn save_kube_config_to(config: &KubeConfig, path: &Path) -> Result<()> {
+    let path = &resolve_config_path(path)?;
     debug!("Saving Kubernetes config to: {}", path.display());
 
-    let config_yaml =
-        serde_yaml::to_string(config).context("Failed to serialize Kubernetes config to YAML")?;
+    if !ALLOW_INVALID_SERVERS.with(|a| *a.borrow()) {
+        validate_server_urls(config)?;
+    }
 
-    fs::write(path, config_yaml)
-        .with_context(|| format!("Failed to write config file: {}", path.display()))?;
+    let config_yaml = serde_yaml::to_string(config).map_err(KhelpError::Serialize)?;
+
+    if BACKUP_ENABLED.with(|b| *b.borrow()) && path.exists() {
+        let backup_path = get_backup_path(path);
+        if let Some(parent) = backup_path.parent()
+            && !parent.as_os_str().is_empty()
+            && !parent.exists()
+        {
+            fs::create_dir_all(parent).map_err(KhelpError::io(format!(
+                "Failed to create backup directory: {}",
+                parent.display()
+            )))?;
+        }
+        fs::copy(path, &backup_path).map_err(KhelpError::io(format!(
+            "Failed to back up config to: {}",
+            backup_path.display()
+        )))?;
+        debug!("Backed up config to: {}", backup_path.display());
+        if let Some(dir) = BACKUP_DIR.with(|d| d.borrow().clone()) {
+            prune_backups(&dir, path)?;
+        }
+    }
+
+    write_atomically(path, config_yaml.as_bytes()).map_err(KhelpError::io(format!(
+        "Failed to write config file: {}",
+        path.display()
+    )))?;
 
     debug!("Config updated successfully");
     Ok(())
 }
 
+/// Checks that every cluster's server is an absolute http or https URL
+///
+/// # Arguments
+///
+/// * `config` - The Kubernetes configuration to validate
+pub fn validate_server_urls(config: &KubeConfig) -> Result<()> {
+    let problems: Vec<String> = config
+        .clusters
+        .iter()
+        .filter_map(|cluster| {
+            let server = &cluster.cluster.server;
+            let problem = match url::Url::parse(server) {
+                Ok(url) if url.scheme() != "http" && url.scheme() != "https" => {
+                    format!("unsupported scheme '{}'", url.scheme())
+                }
+                Ok(url) if !url.has_host() => "missing host".to_string(),
+                Ok(_) => return None,
+                Err(e) => e.to_string(),
+            };
+            Some(format!(
+                "  - cluster '{}': '{}' ({})",
+                cluster.name, server, problem
+            ))
+        })
+        .collect();
+
+    if !problems.is_empty() {
+        return Err(KhelpError::InvalidServerUrls(problems));
+    }
+
+    Ok(())
+}
+
+/// Fingerprints a config file's contents so a later change by another process can be
+/// detected; returns `None` if the file doesn't exist
+pub fn config_fingerprint(path: &Path) -> Result<Option<u64>> {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+
+    let content = match fs::read(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(KhelpError::io(format!(
+                "Failed to read config file: {}",
+                path.display()
+            ))(e));
+        }
+    };
+
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    Ok(Some(hasher.finish()))
+}
+
+/// Gets the path a config backup is written to (the config path with `.bak` appended)
+///
+/// With a backup directory set, each save gets its own timestamped backup there,
+/// named after the config's file name and a hash of its canonical path so configs
+/// that share a file name don't overwrite each other's backups.
+pub fn get_backup_path(path: &Path) -> PathBuf {
+    let backup_dir = BACKUP_DIR.with(|d| d.borrow().clone());
+    match backup_dir {
+        Some(dir) => {
+            let prefix = backup_prefix(path);
+            let mut millis = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_millis())
+                .unwrap_or_default();
+            // Two saves within a millisecond still get separate backups
+            while dir.join(format!("{}{:013}.bak", prefix, millis)).exists() {
+                millis += 1;
+            }
+            dir.join(format!("{}{:013}.bak", prefix, millis))
+        }
+        None => {
+            let mut backup = path.as_os_str().to_owned();
+            backup.push(".bak");
+            PathBuf::from(backup)
+        }
+    }
+}
+
+/// The start of the names of a config's backups in a backup directory
+fn backup_prefix(path: &Path) -> String {
+    let source = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "config".to_string());
+    format!(
+        "{}-{:016x}-",
+        file_name,
+        stable_hash(source.as_os_str().as_encoded_bytes())
+    )
+}
+
+/// Removes all but the newest MAX_BACKUPS_PER_CONFIG backups of a config from a backup directory
+fn prune_backups(dir: &Path, path: &Path) -> Result<()> {
+    let prefix = backup_prefix(path);
+    let mut backups: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(KhelpError::io(format!(
+            "Failed to read backup directory: {}",
+            dir.display()
+        )))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|backup| {
+            backup
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(".bak"))
+        })
+        .collect();
+    // The zero-padded timestamps sort oldest first
+    backups.sort();
+
+    let excess = backups.len().saturating_sub(MAX_BACKUPS_PER_CONFIG);
+    for backup in &backups[..excess] {
+        debug!("Removing old backup: {}", backup.display());
+        fs::remove_file(backup).map_err(KhelpError::io(format!(
+            "Failed to remove old backup: {}",
+            backup.display()
+        )))?;
+    }
+    Ok(())
+}
+
+/// Hashes bytes with 64-bit FNV-1a, which unlike DefaultHasher gives the same
+/// result in every build, so hashes can be stored and compared later
+pub(crate) fn stable_hash(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+    })
+}
+
+/// Writes content to a temp file next to the target, syncs it, then renames it over
+/// the target so readers only ever see the old or the new file, never a partial one
+fn write_atomically(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    // The temp file must live on the same filesystem for the rename to be atomic
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    let mut temp_file = tempfile::Builder::new()
+        .prefix(".khelp-")
+        .suffix(".tmp")
+        .tempfile_in(dir)?;
+    temp_file.write_all(content)?;
+    temp_file.as_file().sync_all()?;
+
+    // Keep the existing file's mode and owner; a new kubeconfig holds credentials,
+    // so it is only readable by its owner
+    match fs::metadata(path) {
+        Ok(metadata) => {
+            temp_file
+                .as_file()
+                .set_permissions(metadata.permissions())?;
+            #[cfg(unix)]
+            preserve_owner(temp_file.as_file(), &metadata);
+        }
+        Err(_) => {
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                temp_file
+                    .as_file()
+                    .set_permissions(fs::Permissions::from_mode(0o600))?;
+            }
+        }
+    }
+
+    temp_file.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+/// Give the replacement file the original's owner and group, such as when root edits
+/// another user's kubeconfig
+///
+/// Only privileged users can change ownership; otherwise the file keeps the writer's
+/// owner, which is already the original's in the common case.
+#[cfg(unix)]
+fn preserve_owner(file: &fs::File, original: &fs::Metadata) {
+    use std::os::unix::fs::MetadataExt;
+
+    let Ok(current) = file.metadata() else {
+        return;
+    };
+    if (current.uid(), current.gid()) == (original.uid(), original.gid()) {
+        return;
+    }
+    if let Err(e) = std::os::unix::fs::fchown(file, Some(original.uid()), Some(original.gid())) {
+        debug!("Could not preserve the kubeconfig's owner: {}", e);
+    }
+}
+
+/// Resolves symlinks so that writes update the real file behind a linked config
+/// instead of replacing the link itself
+///
+/// # Arguments
+///
+/// * `path` - Path to the kubeconfig file, which may be a symlink
+pub fn resolve_config_path(path: &Path) -> Result<PathBuf> {
+    let is_symlink = fs::symlink_metadata(path)
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(false);
+
+    if !is_symlink {
+        return Ok(path.to_path_buf());
+    }
+
+    let resolved = match fs::canonicalize(path) {
+        Ok(resolved) => resolved,
+        Err(_) => {
+            // Dangling link: write to wherever it points so the link becomes valid
+            let target = fs::read_link(path).map_err(KhelpError::io(format!(
+                "Failed to read symlink: {}",
+                path.display()
+            )))?;
+            match path.parent() {
+                Some(parent) if target.is_relative() => parent.join(target),
+                _ => target,
+            }
+        }
+    };
+
+    debug!(
+        "Resolved symlinked config {} -> {}",
+        path.display(),
+        resolved.display()
+    );
+    Ok(resolved)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -283,6 +659,46 @@ users:
         );
     }
 
+    #[test]
+    fn test_load_errors_are_matchable() {
+        let temp_file = NamedTempFile::new().expect("Failed to create temp file");
+        std::fs::write(temp_file.path(), "").expect("Failed to write to temp file");
+        assert!(matches!(
+            load_kube_config_from(temp_file.path()),
+            Err(KhelpError::EmptyConfig(path)) if path == temp_file.path()
+        ));
+
+        std::fs::write(temp_file.path(), "clusters: [").expect("Failed to write to temp file");
+        assert!(matches!(
+            load_kube_config_from(temp_file.path()),
+            Err(KhelpError::ParseError {
+                missing_required: false,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_error_points_at_line() {
+        let temp_file = NamedTempFile::new().expect("Failed to create temp file");
+        let yaml = "apiVersion: v1\nkind: Config\nclusters:\n- name: c1\n  cluster:\n    server: https://a.example\n   insecure-skip-tls-verify: true\ncontexts: []\nusers: []\n";
+        std::fs::write(temp_file.path(), yaml).expect("Failed to write to temp file");
+
+        let error_msg = load_kube_config_from(temp_file.path())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error_msg.contains("At line 7, column"),
+            "Error should give the line and column: {}",
+            error_msg
+        );
+        assert!(
+            error_msg.contains("   insecure-skip-tls-verify: true"),
+            "Error should quote the offending line: {}",
+            error_msg
+        );
+    }
+
     #[test]
     fn test_load_kube_config_from_whitespace_only() {
         let temp_file = NamedTempFile::new().expect("Failed to create temp file");
@@ -338,6 +754,71 @@ preferences: {}
         assert!(content.contains("test-user"));
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_save_keeps_mode_and_defaults_new_files_to_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let config: KubeConfig =
+            serde_yaml::from_str(&sample_kubeconfig_yaml()).expect("Failed to parse config");
+        let mode = |path: &Path| {
+            std::fs::metadata(path)
+                .expect("Failed to stat config")
+                .permissions()
+                .mode()
+                & 0o777
+        };
+
+        let new_path = temp_dir.path().join("new-config");
+        save_kube_config_to(&config, &new_path).expect("Failed to save new config");
+        assert_eq!(mode(&new_path), 0o600);
+
+        let shared_path = temp_dir.path().join("shared-config");
+        std::fs::write(&shared_path, sample_kubeconfig_yaml()).expect("Failed to write config");
+        std::fs::set_permissions(&shared_path, std::fs::Permissions::from_mode(0o640))
+            .expect("Failed to set permissions");
+        save_kube_config_to(&config, &shared_path).expect("Failed to save config");
+        assert_eq!(mode(&shared_path), 0o640);
+    }
+
+    #[test]
+    fn test_config_fingerprint_detects_changes() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("config");
+
+        assert_eq!(config_fingerprint(&path).unwrap(), None);
+
+        std::fs::write(&path, sample_kubeconfig_yaml()).expect("Failed to write config");
+        let before = config_fingerprint(&path).unwrap();
+        assert!(before.is_some());
+        assert_eq!(config_fingerprint(&path).unwrap(), before);
+
+        std::fs::write(
+            &path,
+            sample_kubeconfig_yaml().replace("test-context", "other"),
+        )
+        .expect("Failed to rewrite config");
+        assert_ne!(config_fingerprint(&path).unwrap(), before);
+    }
+
+    #[test]
+    fn test_load_or_default_from_missing_and_empty_files() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("scratch.yaml");
+
+        let config = load_kube_config_or_default_from(&path).expect("Missing file should default");
+        assert!(config.contexts.is_empty());
+
+        std::fs::write(&path, "  \n").expect("Failed to write empty config");
+        let config = load_kube_config_or_default_from(&path).expect("Empty file should default");
+        assert!(config.contexts.is_empty());
+
+        std::fs::write(&path, sample_kubeconfig_yaml()).expect("Failed to write config");
+        let config = load_kube_config_or_default_from(&path).expect("Failed to load config");
+        assert_eq!(config.contexts.len(), 1);
+    }
+
     #[test]
     fn test_save_kube_config_with_backup() {
         // Create a temporary directory for this test
@@ -359,6 +840,115 @@ preferences: {}
         assert_eq!(saved_config.current_context, "modified-context");
     }
 
+    #[test]
+    fn test_save_writes_backup_when_enabled() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let config_path = temp_dir.path().join("config");
+        std::fs::write(&config_path, sample_kubeconfig_yaml())
+            .expect("Failed to write initial config");
+
+        let mut config = load_kube_config_from(&config_path).expect("Failed to load config");
+        config.current_context = "modified-context".to_string();
+
+        set_backup_enabled(true);
+        let result = save_kube_config_to(&config, &config_path);
+        set_backup_enabled(false);
+        result.expect("Failed to save config");
+
+        let backup_path = get_backup_path(&config_path);
+        assert_eq!(backup_path, temp_dir.path().join("config.bak"));
+
+        // The backup holds the previous contents, the config holds the new ones
+        let backup = load_kube_config_from(&backup_path).expect("Failed to load backup");
+        assert_eq!(backup.current_context, "test-context");
+        let saved = load_kube_config_from(&config_path).expect("Failed to reload config");
+        assert_eq!(saved.current_context, "modified-context");
+    }
+
+    /// Backups of a config in a backup directory, oldest first
+    fn backups_in(dir: &Path, config_path: &Path) -> Vec<PathBuf> {
+        let prefix = backup_prefix(config_path);
+        let mut backups: Vec<PathBuf> = std::fs::read_dir(dir)
+            .expect("Failed to read backup dir")
+            .map(|entry| entry.expect("Failed to read entry").path())
+            .filter(|p| {
+                p.file_name()
+                    .unwrap()
+                    .to_str()
+                    .unwrap()
+                    .starts_with(&prefix)
+            })
+            .collect();
+        backups.sort();
+        backups
+    }
+
+    #[test]
+    fn test_backup_dir_is_created_and_used() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let config_path = temp_dir.path().join("config");
+        let backup_dir = temp_dir.path().join("backups").join("kube");
+        std::fs::write(&config_path, sample_kubeconfig_yaml())
+            .expect("Failed to write initial config");
+
+        let mut config = load_kube_config_from(&config_path).expect("Failed to load config");
+        config.current_context = "modified-context".to_string();
+
+        set_backup_enabled(true);
+        set_backup_dir(Some(backup_dir.clone()));
+        let result = save_kube_config_to(&config, &config_path);
+        set_backup_dir(None);
+        set_backup_enabled(false);
+        result.expect("Failed to save config");
+
+        assert!(!temp_dir.path().join("config.bak").exists());
+        let backups = backups_in(&backup_dir, &config_path);
+        assert_eq!(backups.len(), 1);
+        let backup = load_kube_config_from(&backups[0]).expect("Failed to load backup");
+        assert_eq!(backup.current_context, "test-context");
+    }
+
+    #[test]
+    fn test_backup_dir_keeps_newest_backups_per_config() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let backup_dir = temp_dir.path().join("backups");
+        // Two configs sharing a file name, like ~/.kube/config and ~/work/config
+        let home_config = temp_dir.path().join("home").join("config");
+        let work_config = temp_dir.path().join("work").join("config");
+        for path in [&home_config, &work_config] {
+            std::fs::create_dir_all(path.parent().unwrap()).expect("Failed to create dir");
+            std::fs::write(path, sample_kubeconfig_yaml()).expect("Failed to write config");
+        }
+
+        set_backup_enabled(true);
+        set_backup_dir(Some(backup_dir.clone()));
+        let mut result = Ok(());
+        for round in 0..MAX_BACKUPS_PER_CONFIG + 2 {
+            for path in [&home_config, &work_config] {
+                let mut config = load_kube_config_from(path).expect("Failed to load config");
+                config.current_context = format!("round-{}", round);
+                result = result.and(save_kube_config_to(&config, path));
+            }
+        }
+        set_backup_dir(None);
+        set_backup_enabled(false);
+        result.expect("Failed to save config");
+
+        let home_backups = backups_in(&backup_dir, &home_config);
+        let work_backups = backups_in(&backup_dir, &work_config);
+        assert_eq!(home_backups.len(), MAX_BACKUPS_PER_CONFIG);
+        assert_eq!(work_backups.len(), MAX_BACKUPS_PER_CONFIG);
+        assert!(home_backups.iter().all(|b| !work_backups.contains(b)));
+
+        // The newest backup holds the contents from before the last save
+        let newest =
+            load_kube_config_from(home_backups.last().unwrap()).expect("Failed to load backup");
+        assert_eq!(
+            newest.current_context,
+            format!("round-{}", MAX_BACKUPS_PER_CONFIG)
+        );
+    }
+
     #[test]
     fn test_round_trip_save_and_load() {
         let temp_file = NamedTempFile::new().expect("Failed to create temp file");
@@ -392,6 +982,90 @@ preferences: {}
         assert_eq!(reloaded_config.users.len(), modified_config.users.len());
     }
 
+    #[test]
+    fn test_home_directory_falls_back_to_env() {
+        let platform = std::env::temp_dir().join("platform-home");
+        let env_home = std::env::temp_dir().join("env-home");
+
+        assert_eq!(
+            home_directory_from(Some(platform.clone()), Some(env_home.clone().into()), None),
+            Some(platform)
+        );
+        assert_eq!(
+            home_directory_from(None, None, Some(env_home.clone().into())),
+            Some(env_home)
+        );
+        // Relative or empty values are not usable as a home directory
+        assert_eq!(
+            home_directory_from(None, Some(OsString::new()), Some(OsString::from("rel"))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_khelp_kubeconfig_takes_precedence() {
+        assert_eq!(
+            kubeconfig_from_vars(Some("staging".into()), Some("kubectl".into())),
+            Some((PathBuf::from("staging"), "KHELP_KUBECONFIG"))
+        );
+        assert_eq!(
+            kubeconfig_from_vars(Some(OsString::new()), Some("kubectl".into())),
+            Some((PathBuf::from("kubectl"), "KUBECONFIG"))
+        );
+        assert_eq!(kubeconfig_from_vars(None, Some(OsString::new())), None);
+    }
+
+    #[test]
+    fn test_load_and_save_crlf_config() {
+        let temp_file = NamedTempFile::new().expect("Failed to create temp file");
+        let crlf_yaml = sample_kubeconfig_yaml().replace('\n', "\r\n");
+        std::fs::write(temp_file.path(), &crlf_yaml).expect("Failed to write config");
+
+        let config = load_kube_config_from(temp_file.path()).expect("Failed to load CRLF config");
+        assert_eq!(config.current_context, "test-context");
+
+        save_kube_config_to(&config, temp_file.path()).expect("Failed to save config");
+        let saved = std::fs::read_to_string(temp_file.path()).expect("Failed to read config");
+        assert!(
+            !saved.contains('\r'),
+            "saved config should use LF line endings"
+        );
+    }
+
+    #[test]
+    fn test_normalize_line_endings() {
+        assert_eq!(normalize_line_endings("a\r\nb\rc\n"), "a\nb\nc\n");
+        assert!(matches!(normalize_line_endings("a\nb"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_round_trip_preserves_preferences() {
+        let temp_file = NamedTempFile::new().expect("Failed to create temp file");
+        let yaml = sample_kubeconfig_yaml().replace(
+            "preferences: {}",
+            "preferences:\n  colors: true\n  extensions:\n  - name: tool\n    extension: {mode: fast}",
+        );
+        assert!(
+            yaml.contains("colors: true"),
+            "sample should have preferences"
+        );
+        std::fs::write(temp_file.path(), yaml).expect("Failed to write initial config");
+
+        let config = load_kube_config_from(temp_file.path()).expect("Failed to load config");
+        save_kube_config_to(&config, temp_file.path()).expect("Failed to save config");
+
+        let reloaded = load_kube_config_from(temp_file.path()).expect("Failed to reload config");
+        let entries = &reloaded
+            .preferences
+            .expect("preferences were dropped")
+            .entries;
+        assert_eq!(entries.get("colors"), Some(&serde_yaml::Value::Bool(true)));
+        assert_eq!(
+            entries["extensions"][0]["extension"]["mode"],
+            serde_yaml::Value::String("fast".to_string())
+        );
+    }
+
     #[test]
     fn test_save_to_invalid_path() {
         let temp_file = NamedTempFile::new().expect("Failed to create temp file");
@@ -413,6 +1087,108 @@ preferences: {}
         );
     }
 
+    #[test]
+    fn test_save_is_atomic_for_concurrent_readers() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let config_path = temp_dir.path().join("config");
+        std::fs::write(&config_path, sample_kubeconfig_yaml())
+            .expect("Failed to write initial config");
+
+        let config = load_kube_config_from(&config_path).expect("Failed to load config");
+        let expected = serde_yaml::to_string(&config).expect("Failed to serialize config");
+
+        let stop = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let reader = {
+            let stop = stop.clone();
+            let config_path = config_path.clone();
+            let expected = expected.clone();
+            std::thread::spawn(move || {
+                while !stop.load(std::sync::atomic::Ordering::Relaxed) {
+                    let content =
+                        std::fs::read_to_string(&config_path).expect("Config should always exist");
+                    assert!(
+                        content == expected || content == sample_kubeconfig_yaml(),
+                        "Observed a partially written config: {:?}",
+                        content
+                    );
+                }
+            })
+        };
+
+        for _ in 0..200 {
+            save_kube_config_to(&config, &config_path).expect("Failed to save config");
+        }
+
+        stop.store(true, std::sync::atomic::Ordering::Relaxed);
+        reader.join().expect("Reader observed a partial write");
+
+        // No temp files should be left behind next to the config
+        let leftovers: Vec<_> = std::fs::read_dir(temp_dir.path())
+            .expect("Failed to read temp dir")
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name() != "config")
+            .collect();
+        assert!(
+            leftovers.is_empty(),
+            "Temp files left behind: {:?}",
+            leftovers
+        );
+    }
+
+    #[test]
+    fn test_validate_server_urls_collects_all_problems() {
+        let mut config: KubeConfig =
+            serde_yaml::from_str(&sample_kubeconfig_yaml()).expect("Failed to parse config");
+        assert!(validate_server_urls(&config).is_ok());
+
+        let mut bad_scheme = config.clusters[0].clone();
+        bad_scheme.name = "bad-scheme".to_string();
+        bad_scheme.cluster.server = "htps://host:6443".to_string();
+        let mut empty = config.clusters[0].clone();
+        empty.name = "empty".to_string();
+        empty.cluster.server = String::new();
+        config.clusters.push(bad_scheme);
+        config.clusters.push(empty);
+
+        let error_msg = validate_server_urls(&config).unwrap_err().to_string();
+        assert!(error_msg.contains("bad-scheme"), "{}", error_msg);
+        assert!(
+            error_msg.contains("unsupported scheme 'htps'"),
+            "{}",
+            error_msg
+        );
+        assert!(error_msg.contains("empty"), "{}", error_msg);
+        assert!(!error_msg.contains("'test-cluster'"), "{}", error_msg);
+    }
+
+    #[test]
+    fn test_save_rejects_invalid_server_url() {
+        let temp_file = NamedTempFile::new().expect("Failed to create temp file");
+        std::fs::write(temp_file.path(), sample_kubeconfig_yaml())
+            .expect("Failed to write initial config");
+
+        let mut config = load_kube_config_from(temp_file.path()).expect("Failed to load config");
+        config.clusters[0].cluster.server = "not a url".to_string();
+
+        let result = save_kube_config_to(&config, temp_file.path());
+        assert!(
+            matches!(result, Err(KhelpError::InvalidServerUrls(ref problems)) if problems.len() == 1),
+            "Should refuse to save an invalid server URL"
+        );
+
+        // The file on disk must be untouched
+        let content = std::fs::read_to_string(temp_file.path()).expect("Failed to read config");
+        assert_eq!(content, sample_kubeconfig_yaml());
+
+        set_allow_invalid_servers(true);
+        let result = save_kube_config_to(&config, temp_file.path());
+        set_allow_invalid_servers(false);
+        assert!(
+            result.is_ok(),
+            "Should save when invalid servers are allowed"
+        );
+    }
+
     #[test]
     fn test_kubeconfig_default() {
         let config = KubeConfig::default();
@@ -0,0 +1,261 @@
+use anyhow::{Context, Result};
+use log::debug;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item, Table, value};
+
+use super::operations::home_directory;
+
+/// khelp's own persistent settings, read from `config.toml`
+///
+/// Every field is optional in the file; CLI flags take precedence over these values.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    /// Back up the kubeconfig before every save
+    pub backup: bool,
+    /// Directory backups are written to (next to the kubeconfig when unset)
+    pub backup_dir: Option<PathBuf>,
+    /// Force colored output on or off (auto-detected when unset)
+    pub color: Option<bool>,
+    /// Sort key used by `list` when `--sort` isn't given
+    pub default_sort: Option<String>,
+    /// Short names that resolve to full context names
+    pub aliases: BTreeMap<String, String>,
+    /// Namespaces contexts are expected to use, keyed by context name glob
+    pub namespace_rules: BTreeMap<String, String>,
+    /// Regex matching the contexts `prompt` highlights as production
+    pub prod_pattern: Option<String>,
+}
+
+impl Settings {
+    /// Loads settings from the default location, falling back to defaults if the
+    /// file doesn't exist
+    pub fn load() -> Result<Self> {
+        match settings_path() {
+            Some(path) if path.exists() => Self::load_from(&path),
+            _ => {
+                debug!("No settings file found, using defaults");
+                Ok(Self::default())
+            }
+        }
+    }
+
+    /// Loads settings from a custom path
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the settings file
+    pub fn load_from(path: &Path) -> Result<Self> {
+        debug!("Loading settings from: {}", path.display());
+
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read settings file: {}", path.display()))?;
+
+        toml::from_str(&content)
+            .with_context(|| format!("Failed to parse settings file: {}", path.display()))
+    }
+
+    /// Resolves an alias to its context name, or returns the input unchanged
+    pub fn resolve_alias(&self, name: &str) -> String {
+        match self.aliases.get(name) {
+            Some(context) => {
+                debug!("Resolved alias '{}' to context '{}'", name, context);
+                context.clone()
+            }
+            None => name.to_string(),
+        }
+    }
+}
+
+/// Adds or replaces an alias in a settings file, keeping the rest of the file intact
+///
+/// # Arguments
+///
+/// * `path` - Path to the settings file, created if it doesn't exist
+/// * `alias` - The short name
+/// * `context` - The context name the alias resolves to
+pub fn set_alias_in(path: &Path, alias: &str, context: &str) -> Result<()> {
+    let content = if path.exists() {
+        fs::read_to_string(path)
+            .with_context(|| format!("Failed to read settings file: {}", path.display()))?
+    } else {
+        String::new()
+    };
+
+    let mut document: DocumentMut = content
+        .parse()
+        .with_context(|| format!("Failed to parse settings file: {}", path.display()))?;
+
+    let aliases = document
+        .entry("aliases")
+        .or_insert_with(|| Item::Table(Table::new()))
+        .as_table_like_mut()
+        .context("'aliases' in the settings file must be a table")?;
+    aliases.insert(alias, value(context));
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    fs::write(path, document.to_string())
+        .with_context(|| format!("Failed to write settings file: {}", path.display()))?;
+
+    debug!(
+        "Set alias '{}' -> '{}' in {}",
+        alias,
+        context,
+        path.display()
+    );
+    Ok(())
+}
+
+/// Gets the path to khelp's settings file, honoring `XDG_CONFIG_HOME`
+pub fn settings_path() -> Option<PathBuf> {
+    settings_path_from(env::var_os("XDG_CONFIG_HOME"), home_directory())
+}
+
+fn settings_path_from(xdg_config_home: Option<OsString>, home: Option<PathBuf>) -> Option<PathBuf> {
+    // Per the XDG spec, an empty or relative value is ignored
+    let config_dir = xdg_config_home
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| home.map(|h| h.join(".config")))?;
+
+    Some(config_dir.join("khelp").join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_settings_path_prefers_xdg_config_home() {
+        let path = settings_path_from(
+            Some(OsString::from("/xdg/config")),
+            Some(PathBuf::from("/home/user")),
+        );
+        assert_eq!(path, Some(PathBuf::from("/xdg/config/khelp/config.toml")));
+    }
+
+    #[test]
+    fn test_settings_path_falls_back_to_home() {
+        let path = settings_path_from(None, Some(PathBuf::from("/home/user")));
+        assert_eq!(
+            path,
+            Some(PathBuf::from("/home/user/.config/khelp/config.toml"))
+        );
+
+        // Relative XDG_CONFIG_HOME values must be ignored
+        let path = settings_path_from(
+            Some(OsString::from("relative")),
+            Some(PathBuf::from("/home/user")),
+        );
+        assert_eq!(
+            path,
+            Some(PathBuf::from("/home/user/.config/khelp/config.toml"))
+        );
+    }
+
+    #[test]
+    fn test_load_settings_from_file() {
+        let temp_file = NamedTempFile::new().expect("Failed to create temp file");
+        std::fs::write(
+            temp_file.path(),
+            "backup = true\nbackup_dir = \"/var/backups/kube\"\ncolor = false\ndefault_sort = \"cluster\"\nprod_pattern = \"^prod\"\n",
+        )
+        .expect("Failed to write settings");
+
+        let settings = Settings::load_from(temp_file.path()).expect("Failed to load settings");
+        assert!(settings.backup);
+        assert_eq!(
+            settings.backup_dir,
+            Some(PathBuf::from("/var/backups/kube"))
+        );
+        assert_eq!(settings.color, Some(false));
+        assert_eq!(settings.default_sort.as_deref(), Some("cluster"));
+        assert_eq!(settings.prod_pattern.as_deref(), Some("^prod"));
+    }
+
+    #[test]
+    fn test_load_namespace_rules() {
+        let temp_file = NamedTempFile::new().expect("Failed to create temp file");
+        std::fs::write(
+            temp_file.path(),
+            "[namespace_rules]\n\"dev-*\" = \"sandbox\"\nprod = \"apps\"\n",
+        )
+        .expect("Failed to write settings");
+
+        let settings = Settings::load_from(temp_file.path()).expect("Failed to load settings");
+        assert_eq!(
+            settings.namespace_rules,
+            BTreeMap::from([
+                ("dev-*".to_string(), "sandbox".to_string()),
+                ("prod".to_string(), "apps".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_load_partial_settings_uses_defaults() {
+        let temp_file = NamedTempFile::new().expect("Failed to create temp file");
+        std::fs::write(temp_file.path(), "color = true\n").expect("Failed to write settings");
+
+        let settings = Settings::load_from(temp_file.path()).expect("Failed to load settings");
+        assert!(!settings.backup);
+        assert_eq!(settings.color, Some(true));
+        assert_eq!(settings.default_sort, None);
+        assert!(settings.namespace_rules.is_empty());
+    }
+
+    #[test]
+    fn test_set_alias_preserves_existing_settings() {
+        let temp_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("khelp").join("config.toml");
+
+        set_alias_in(&path, "prod", "arn:aws:eks:us-east-1:123:cluster/prod")
+            .expect("Failed to set alias");
+        std::fs::write(
+            &path,
+            format!(
+                "# keep me\nbackup = true\n{}",
+                std::fs::read_to_string(&path).expect("Failed to read settings")
+            ),
+        )
+        .expect("Failed to write settings");
+        set_alias_in(&path, "dev", "kind-dev").expect("Failed to set alias");
+
+        let content = std::fs::read_to_string(&path).expect("Failed to read settings");
+        assert!(content.contains("# keep me"));
+
+        let settings = Settings::load_from(&path).expect("Failed to load settings");
+        assert!(settings.backup);
+        assert_eq!(
+            settings.resolve_alias("prod"),
+            "arn:aws:eks:us-east-1:123:cluster/prod"
+        );
+        assert_eq!(settings.resolve_alias("dev"), "kind-dev");
+        assert_eq!(settings.resolve_alias("literal"), "literal");
+    }
+
+    #[test]
+    fn test_load_invalid_settings() {
+        let temp_file = NamedTempFile::new().expect("Failed to create temp file");
+        std::fs::write(temp_file.path(), "backup = \"sometimes\"\n")
+            .expect("Failed to write settings");
+
+        let result = Settings::load_from(temp_file.path());
+        assert!(result.is_err(), "Should fail for a mistyped field");
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Failed to parse settings file")
+        );
+    }
+}
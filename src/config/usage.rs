@@ -0,0 +1,152 @@
+use anyhow::{Context, Result};
+use console::style;
+use log::debug;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::settings::settings_path;
+
+/// Name of the usage log, stored next to khelp's settings file
+const USAGE_FILE_NAME: &str = "last_used";
+
+/// Gets the path to the log of when each context was last switched to
+pub fn usage_path() -> Option<PathBuf> {
+    Some(settings_path()?.with_file_name(USAGE_FILE_NAME))
+}
+
+/// Applies a change to the usage log, warning instead of failing if it can't be written
+///
+/// The log only feeds `list --sort recent`, so a command that already saved the
+/// kubeconfig shouldn't fail because of it.
+pub fn update_usage(update: impl FnOnce(&Path) -> Result<()>) {
+    let Some(path) = usage_path() else {
+        return;
+    };
+    if let Err(e) = update(&path) {
+        eprintln!(
+            "{} could not update context usage log: {:#}",
+            style("Warning:").yellow().bold(),
+            e
+        );
+    }
+}
+
+/// Loads the last-used time of each context, in seconds since the Unix epoch
+///
+/// A missing file is treated as an empty log, and malformed lines are skipped.
+pub fn load_usage_from(path: &Path) -> Result<BTreeMap<String, u64>> {
+    if !path.exists() {
+        debug!("No usage log at {}", path.display());
+        return Ok(BTreeMap::new());
+    }
+
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read usage log: {}", path.display()))?;
+
+    Ok(content
+        .lines()
+        .filter_map(|line| {
+            let (timestamp, context_name) = line.split_once('\t')?;
+            Some((context_name.to_string(), timestamp.trim().parse().ok()?))
+        })
+        .collect())
+}
+
+/// Records that a context was just switched to
+pub fn record_usage_to(path: &Path, context_name: &str) -> Result<()> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+
+    update_usage_in(path, |usage| {
+        usage.insert(context_name.to_string(), now);
+    })
+}
+
+/// Carries a renamed context's last-used time over to its new name
+pub fn rename_usage_in(path: &Path, old_name: &str, new_name: &str) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+
+    update_usage_in(path, |usage| {
+        if let Some(timestamp) = usage.remove(old_name) {
+            usage.insert(new_name.to_string(), timestamp);
+        }
+    })
+}
+
+/// Drops the entries of contexts that no longer exist
+pub fn remove_usage_in(path: &Path, context_names: &[String]) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+
+    update_usage_in(path, |usage| {
+        for name in context_names {
+            usage.remove(name);
+        }
+    })
+}
+
+fn update_usage_in(path: &Path, update: impl FnOnce(&mut BTreeMap<String, u64>)) -> Result<()> {
+    let mut usage = load_usage_from(path)?;
+    update(&mut usage);
+
+    let content: String = usage
+        .iter()
+        .map(|(context_name, timestamp)| format!("{}\t{}\n", timestamp, context_name))
+        .collect();
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    fs::write(path, content)
+        .with_context(|| format!("Failed to write usage log: {}", path.display()))?;
+
+    debug!("Updated usage log at {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_usage_follows_renames_and_deletes() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("khelp").join(USAGE_FILE_NAME);
+
+        assert!(load_usage_from(&path).expect("Failed to load").is_empty());
+
+        record_usage_to(&path, "dev").expect("Failed to record");
+        record_usage_to(&path, "prod").expect("Failed to record");
+        rename_usage_in(&path, "dev", "development").expect("Failed to rename");
+        remove_usage_in(&path, &["prod".to_string()]).expect("Failed to remove");
+
+        let usage = load_usage_from(&path).expect("Failed to load");
+        assert_eq!(usage.keys().collect::<Vec<_>>(), vec!["development"]);
+        assert!(usage["development"] > 0);
+    }
+
+    #[test]
+    fn test_malformed_lines_are_skipped() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join(USAGE_FILE_NAME);
+        fs::write(
+            &path,
+            "100\tdev\nnot a timestamp\tprod\ngarbage\n200\tarn:aws:eks/x\n",
+        )
+        .expect("Failed to write");
+
+        let usage = load_usage_from(&path).expect("Failed to load");
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage["dev"], 100);
+        assert_eq!(usage["arn:aws:eks/x"], 200);
+    }
+}
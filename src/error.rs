@@ -0,0 +1,118 @@
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Result type for khelp's config operations
+pub type Result<T> = std::result::Result<T, KhelpError>;
+
+/// Errors khelp can report, so callers can match on the kind of failure
+#[derive(Debug, Error)]
+pub enum KhelpError {
+    #[error("Context '{0}' not found")]
+    ContextNotFound(String),
+
+    #[error("Context '{0}' already exists")]
+    ContextExists(String),
+
+    #[error("Cluster '{cluster}' not found for context '{context}'")]
+    ClusterNotFound { cluster: String, context: String },
+
+    #[error("User '{user}' not found for context '{context}'")]
+    UserNotFound { user: String, context: String },
+
+    #[error(
+        "Unsupported auth type '{auth_type}' for user '{user}': only token and client certificate auth are supported"
+    )]
+    UnsupportedAuth {
+        auth_type: &'static str,
+        user: String,
+    },
+
+    #[error("Kubernetes config file not found at: {}", .0.display())]
+    ConfigNotFound(PathBuf),
+
+    #[error(
+        "Config file is empty: {}\n\nA valid kubeconfig file must contain at least:\n  apiVersion: v1\n  kind: Config\n  clusters: []\n  contexts: []\n  users: []\n  current-context: \"\"",
+        .0.display()
+    )]
+    EmptyConfig(PathBuf),
+
+    /// The file isn't a valid kubeconfig; `missing_required` is set when
+    /// `apiVersion` or `kind` is absent, and `line` holds the text of the line
+    /// the error points at
+    #[error("{}", parse_error_message(.path, *.missing_required, .error, .line.as_deref()))]
+    ParseError {
+        path: PathBuf,
+        missing_required: bool,
+        error: serde_yaml::Error,
+        line: Option<String>,
+    },
+
+    #[error(
+        "Invalid cluster server URL(s):\n{}\n\nServer URLs must be absolute http or https URLs. Use --allow-invalid to save anyway.",
+        .0.join("\n")
+    )]
+    InvalidServerUrls(Vec<String>),
+
+    #[error("Failed to serialize Kubernetes config to YAML")]
+    Serialize(#[source] serde_yaml::Error),
+
+    #[error("Could not find home directory; set $HOME or pass --kubeconfig <path>")]
+    NoHomeDirectory,
+
+    #[error("{context}")]
+    Io {
+        context: String,
+        #[source]
+        source: io::Error,
+    },
+}
+
+impl KhelpError {
+    /// Wraps an I/O error with a description of what was being attempted
+    pub fn io(context: impl Into<String>) -> impl FnOnce(io::Error) -> Self {
+        let context = context.into();
+        move |source| Self::Io { context, source }
+    }
+}
+
+fn parse_error_message(
+    path: &std::path::Path,
+    missing_required: bool,
+    error: &serde_yaml::Error,
+    line: Option<&str>,
+) -> String {
+    let error_msg = error.to_string();
+    let message = if missing_required {
+        format!(
+            "Invalid kubeconfig file: {}\n\nThe file appears to be missing required fields. A valid kubeconfig must include:\n  - apiVersion: v1\n  - kind: Config\n  - clusters, contexts, users arrays\n  - current-context\n\nOriginal error: {}",
+            path.display(),
+            error_msg
+        )
+    } else if error_msg.contains("missing field") {
+        format!(
+            "Invalid kubeconfig file: {}\n\n{}\n\nPlease check that your kubeconfig file has all required fields.",
+            path.display(),
+            error_msg
+        )
+    } else {
+        format!(
+            "Failed to parse kubeconfig file: {}\n\n{}",
+            path.display(),
+            error_msg
+        )
+    };
+
+    match (error.location(), line) {
+        (Some(location), Some(line)) => format!(
+            "{}\n\nAt line {}, column {}:\n{:>5} | {}\n      | {}^",
+            message,
+            location.line(),
+            location.column(),
+            location.line(),
+            line,
+            " ".repeat(location.column().saturating_sub(1))
+        ),
+        _ => message,
+    }
+}
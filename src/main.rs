@@ -1,123 +1,474 @@
 mod cli;
 mod commands;
 mod config;
+mod error;
+#[cfg(test)]
+mod test_support;
 mod utils;
 
 use anyhow::Result;
-use clap::Parser;
-use log::{debug, info};
+use clap::{Parser, ValueEnum};
+use log::{LevelFilter, debug, info};
+use std::io::Write;
+use std::time::Instant;
 
-use cli::{Cli, Commands};
+use cli::{Cli, Commands, ShellChoice};
+use config::settings::Settings;
 
 fn main() -> Result<()> {
-    // Initialize logger
-    env_logger::init();
+    let started = Instant::now();
+    let cli = Cli::parse();
 
-    debug!("Starting khelp application");
+    // Initialize logger; -v raises the level, RUST_LOG still overrides it
+    let level = match cli.verbose {
+        0 => LevelFilter::Error,
+        1 => LevelFilter::Info,
+        2 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+    let mut logger = env_logger::Builder::new();
+    logger
+        .filter_level(LevelFilter::Error)
+        .filter_module("khelp", level)
+        .parse_default_env();
+    if let cli::LogFormat::Json = cli.log_format {
+        logger.format(|buf, record| {
+            let timestamp = buf.timestamp().to_string();
+            writeln!(
+                buf,
+                "{}",
+                commands::output::json_log_line(&timestamp, record)
+            )
+        });
+    }
+    logger.init();
 
-    let cli = Cli::parse();
+    debug!("Starting khelp application");
     debug!("Command line arguments parsed");
 
-    // Set custom kubeconfig path if provided
-    if let Some(path) = cli.kubeconfig {
-        debug!("Using custom kubeconfig path: {:?}", path);
-        config::operations::set_kubeconfig_path(path);
+    // A broken settings file only stops the commands that depend on it, including every
+    // command that saves the kubeconfig since backups can't be determined, so that e.g.
+    // `prompt` keeps printing inside PS1
+    let (settings, settings_error) = match Settings::load() {
+        Ok(settings) => (settings, None),
+        Err(e) => {
+            eprintln!(
+                "{} {:#}; using default settings",
+                console::style("Warning:").yellow().bold(),
+                e
+            );
+            (Settings::default(), Some(e))
+        }
+    };
+    debug!("Settings loaded: {:?}", settings);
+    let require_settings = || match &settings_error {
+        Some(e) => Err(anyhow::anyhow!(
+            "This command needs khelp's settings: {:#}",
+            e
+        )),
+        None => Ok(&settings),
+    };
+
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    if let Some(color) = commands::output::resolve_color(cli.color, settings.color, no_color) {
+        console::set_colors_enabled(color);
+        console::set_colors_enabled_stderr(color);
     }
+    // Asking for a backup directory on the command line implies wanting a backup
+    config::operations::set_backup_enabled(settings.backup || cli.backup_dir.is_some());
+    config::operations::set_backup_dir(cli.backup_dir.or_else(|| settings.backup_dir.clone()));
+    config::operations::set_allow_invalid_servers(cli.allow_invalid);
+    commands::output::set_quiet(cli.quiet);
+
+    // Set custom kubeconfig path if provided, the flag taking precedence over the environment
+    let kubeconfig_source = match cli
+        .kubeconfig
+        .map(|path| (path, "--kubeconfig"))
+        .or_else(config::operations::kubeconfig_from_env)
+    {
+        Some((path, source)) => {
+            debug!("Using custom kubeconfig path from {}: {:?}", source, path);
+            config::operations::set_kubeconfig_path(path);
+            source
+        }
+        None => "default location",
+    };
 
     match cli.command.unwrap_or(Commands::List {
-        output: cli::OutputFormat::Table,
+        output: None,
+        sort: None,
+        group_by: None,
+        current_only: false,
+        wide: false,
+        porcelain: false,
     }) {
-        Commands::List { output } => {
+        Commands::List {
+            output,
+            sort,
+            group_by,
+            current_only,
+            wide,
+            porcelain,
+        } => {
             debug!("Executing List command");
+            let settings = require_settings()?;
+            let sort = sort.or_else(|| {
+                let default_sort = settings.default_sort.as_deref()?;
+                cli::SortKey::from_str(default_sort, true)
+                    .inspect_err(|_| {
+                        eprintln!(
+                            "{} ignoring unknown default_sort '{}' in settings",
+                            console::style("Warning:").yellow().bold(),
+                            default_sort
+                        )
+                    })
+                    .ok()
+            });
             let config = config::operations::load_kube_config()?;
-            commands::list::list_contexts(&config, &output);
+            if !commands::list::list_contexts(
+                &config,
+                commands::list::ListOptions {
+                    output,
+                    sort,
+                    group_by,
+                    current_only,
+                    wide,
+                    porcelain,
+                    namespace_rules: &settings.namespace_rules,
+                },
+            ) {
+                debug!("Current context is not set or does not exist");
+                std::process::exit(1);
+            }
         }
-        Commands::Current { output } => {
+        Commands::Current {
+            context_name,
+            output,
+            wide,
+            check,
+            template,
+            #[cfg(feature = "clipboard")]
+            copy,
+        } => {
             debug!("Executing Current command");
+            let settings = require_settings()?;
             let config = config::operations::load_kube_config()?;
-            commands::current::show_current_context(&config, &output);
+            let context_name = match context_name {
+                Some(name) => {
+                    let name = settings.resolve_alias(&name);
+                    if !config.contexts.iter().any(|c| c.name == name) {
+                        anyhow::bail!(error::KhelpError::ContextNotFound(name));
+                    }
+                    name
+                }
+                None => {
+                    // Only offer a fix when the output is meant for a person
+                    if commands::output::warn_missing_current_context(&config)
+                        && matches!(output, cli::OutputFormat::Table)
+                        && template.is_none()
+                        && commands::current::offer_current_context_repair()?
+                    {
+                        return Ok(());
+                    }
+                    config.current_context.clone()
+                }
+            };
+            #[cfg(feature = "clipboard")]
+            if copy {
+                return commands::current::copy_context_name(&context_name);
+            }
+            match template {
+                Some(template) => {
+                    println!(
+                        "{}",
+                        commands::current::render_template(&config, &context_name, &template)?
+                    );
+                }
+                None => commands::current::show_current_context(
+                    &config,
+                    &context_name,
+                    &output,
+                    wide,
+                    check,
+                ),
+            }
         }
-        Commands::Switch { context_name } => {
+        Commands::Switch {
+            context_name,
+            namespace,
+            strict,
+            force,
+            print_export,
+        } => {
             debug!("Executing Switch command");
-            commands::switch::switch_context(context_name)?;
+            let settings = require_settings()?;
+            let context_name = context_name.map(|name| settings.resolve_alias(&name));
+            if !commands::switch::switch_context(
+                context_name,
+                namespace,
+                strict,
+                force,
+                print_export,
+            )? {
+                debug!("Already on the requested context");
+                std::process::exit(1);
+            }
         }
-        Commands::Edit { context_name } => {
+        Commands::Edit {
+            context_name,
+            current,
+            all,
+            force,
+            set_server,
+            set_namespace,
+            set_cluster,
+            set_user,
+            set_ca_file,
+        } => {
             debug!("Executing Edit command");
-            commands::edit::edit_context(context_name)?;
+            let settings = require_settings()?;
+            if all {
+                commands::edit::edit_full_config(force)?;
+            } else {
+                let context_name = context_name.map(|name| settings.resolve_alias(&name));
+                commands::edit::edit_context(
+                    context_name,
+                    current,
+                    force,
+                    commands::edit::FieldChanges {
+                        server: set_server,
+                        namespace: set_namespace,
+                        cluster: set_cluster,
+                        user: set_user,
+                        ca_file: set_ca_file,
+                    },
+                )?;
+            }
         }
-        Commands::Export { context_names } => {
+        Commands::Export {
+            context_names,
+            current,
+            redact,
+            skip_broken,
+            keep_current,
+            minify,
+            sorted,
+            output_dir,
+            merge_into,
+            output,
+        } => {
             debug!("Executing Export command");
-            commands::export::export_contexts(context_names)?;
+            if merge_into.is_some() || output_dir.is_some() {
+                require_settings()?;
+            }
+            commands::export::export_contexts(
+                context_names,
+                current,
+                commands::export::ExportOptions {
+                    redact,
+                    skip_broken,
+                    keep_current,
+                    minify,
+                    sorted,
+                    output_dir,
+                    merge_into,
+                    format: output,
+                },
+            )?;
         }
         Commands::Delete {
-            context_name,
+            context_names,
+            current,
+            broken,
+            switch_to,
             force,
         } => {
             debug!("Executing Delete command");
-            commands::delete::delete_context(context_name, force)?;
+            let settings = require_settings()?;
+            let context_names = context_names
+                .iter()
+                .map(|name| settings.resolve_alias(name))
+                .collect();
+            let switch_to = switch_to.map(|name| settings.resolve_alias(&name));
+            commands::delete::delete_contexts(context_names, current, broken, force, switch_to)?;
         }
-        Commands::Cleanup { force } => {
+        Commands::Cleanup { force, all } => {
             debug!("Executing Cleanup command");
-            commands::cleanup::cleanup_orphans(force)?;
+            require_settings()?;
+            commands::cleanup::cleanup_orphans(force, all)?;
         }
-        Commands::Rename { old_name, new_name } => {
+        Commands::Rename {
+            old_name,
+            new_name,
+            strict_case,
+            cascade,
+            force,
+        } => {
             debug!("Executing Rename command");
-            commands::rename::rename_context(old_name, new_name)?;
+            require_settings()?;
+            commands::rename::rename_context(old_name, new_name, strict_case, cascade, force)?;
         }
         Commands::Add {
             file_path,
             rename,
             overwrite,
             switch,
+            adopt_current,
+            only,
+            select,
+            output,
+            headers,
+            yes,
+            prune,
         } => {
             debug!("Executing Add command with file: {:?}", file_path);
-            commands::add::add_context(file_path, rename, overwrite, switch)?;
+            require_settings()?;
+            commands::add::add_context(
+                file_path,
+                commands::add::AddOptions {
+                    rename,
+                    overwrite,
+                    switch,
+                    adopt_current,
+                    only,
+                    select,
+                    output,
+                    headers,
+                    yes,
+                    prune,
+                },
+            )?;
+        }
+        Commands::Merge {
+            name,
+            cluster_from,
+            user_from,
+            namespace,
+            force,
+        } => {
+            debug!("Executing Merge command");
+            require_settings()?;
+            commands::merge::merge_contexts(name, cluster_from, user_from, namespace, force)?;
         }
-        Commands::Completions { shell, install } => {
+        Commands::Namespace {
+            mut args,
+            current,
+            all,
+            list,
+            apply_rules,
+            force,
+        } => {
+            debug!("Executing Namespace command");
+            use commands::namespace::NamespaceTarget;
+            let settings = require_settings()?;
+
+            if list {
+                commands::namespace::pick_namespace_from_cluster()?;
+            } else if apply_rules {
+                commands::namespace::apply_namespace_rules(&settings.namespace_rules, force)?;
+            } else {
+                let namespace = args.pop().expect("clap requires at least one value");
+                let target = match (args.pop(), current, all) {
+                    (Some(_), true, _) | (Some(_), _, true) => {
+                        anyhow::bail!("A context pattern can't be combined with --current or --all")
+                    }
+                    (Some(pattern), false, false) => NamespaceTarget::Pattern(pattern),
+                    (None, true, _) => NamespaceTarget::Current,
+                    (None, _, true) => NamespaceTarget::All,
+                    (None, false, false) => NamespaceTarget::Interactive,
+                };
+                commands::namespace::set_namespace(target, namespace, force)?;
+            }
+        }
+        Commands::Alias { action } => {
+            debug!("Executing Alias command");
+            match action {
+                cli::AliasAction::Set { alias, context } => {
+                    commands::alias::set_alias(alias, context)?
+                }
+                cli::AliasAction::List => commands::alias::list_aliases(require_settings()?),
+            }
+        }
+        Commands::ConfigPath => {
+            debug!("Executing ConfigPath command");
+            commands::config_path::show_config_path(kubeconfig_source)?;
+        }
+        Commands::Check {
+            context,
+            insecure,
+            ca_file,
+        } => {
+            debug!("Executing Check command");
+            commands::check::check_connectivity(context, insecure, ca_file)?;
+        }
+        Commands::CertInfo { context } => {
+            debug!("Executing CertInfo command");
+            commands::cert_info::show_cert_info(context)?;
+        }
+        Commands::ResetCurrent { context_name } => {
+            debug!("Executing ResetCurrent command");
+            let settings = require_settings()?;
+            let context_name = context_name.map(|name| settings.resolve_alias(&name));
+            commands::reset_current::reset_current_context(context_name)?;
+        }
+        Commands::Prompt { format, shell } => {
+            debug!("Executing Prompt command");
+            commands::prompt::print_prompt(&format, shell, settings.prod_pattern.as_deref())?;
+        }
+        Commands::Resolve { output } => {
+            debug!("Executing Resolve command");
+            commands::resolve::resolve_current_context(output)?;
+        }
+        Commands::Completions {
+            shell,
+            install,
+            print_path,
+            output,
+        } => {
             debug!(
                 "Executing Completions command with shell: {:?}, install: {}",
                 shell, install
             );
 
-            if let Some(s) = shell {
-                debug!("Shell explicitly specified: {:?}", s);
-
-                debug!("Generating completions");
-                commands::completions::generate_completions(s, install)?;
-            } else if install {
-                debug!("No shell specified, detecting current shell...");
-
-                match commands::completions::detect_shell() {
-                    Ok(detected_shell) => {
-                        debug!("Successfully detected shell: {:?}", detected_shell);
-
-                        match commands::completions::generate_completions(detected_shell, true) {
-                            Ok(_) => {
-                                info!("Completions installed successfully");
-                            }
-                            Err(e) => {
-                                return Err(e);
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        return Err(e);
-                    }
+            let shell = match shell {
+                Some(ShellChoice::Shell(s)) => {
+                    debug!("Shell explicitly specified: {:?}", s);
+                    s
                 }
+                Some(ShellChoice::Auto) => commands::completions::detect_shell()?,
+                None if install || print_path || output.is_some() => {
+                    debug!("No shell specified, detecting current shell...");
+                    commands::completions::detect_shell()?
+                }
+                None => anyhow::bail!(
+                    "Specify a shell, or `auto` to detect it (see `khelp completions --help`)"
+                ),
+            };
+            debug!("Using shell: {:?}", shell);
+
+            if print_path {
+                commands::completions::print_completion_paths(shell)?;
             } else {
-                debug!("No shell specified and not installing");
-                anyhow::bail!("Shell must be specified when not using --install")
+                debug!("Generating completions");
+                commands::completions::generate_completions(shell, install, output.as_deref())?;
             }
 
             debug!("Completions command execution finished");
         }
+        #[cfg(feature = "tui")]
+        Commands::Browse => {
+            debug!("Executing Browse command");
+            require_settings()?;
+            commands::tui::browse_contexts()?;
+        }
         #[cfg(feature = "self_update")]
-        Commands::Update { apply } => {
+        Commands::Update { apply, timeout } => {
             debug!("Executing Update command with apply: {}", apply);
-            commands::update::handle_update(apply)?;
+            commands::update::handle_update(apply, std::time::Duration::from_secs(timeout))?;
         }
     }
 
     debug!("khelp execution completed successfully");
+    info!("Finished in {:.2?}", started.elapsed());
     Ok(())
 }
@@ -0,0 +1,15 @@
+//! Fixtures shared by the unit tests of several modules
+
+/// A self-signed certificate for `CN=khelp-test`, valid until 2036
+pub const TEST_CERT: &str = "-----BEGIN CERTIFICATE-----
+MIIBfzCCASWgAwIBAgIUeZjIQU3hOhJS/V7Lmzds/k2GVzcwCgYIKoZIzj0EAwIw
+FTETMBEGA1UEAwwKa2hlbHAtdGVzdDAeFw0yNjEwMTQxODQyMTlaFw0zNjEwMTEx
+ODQyMTlaMBUxEzARBgNVBAMMCmtoZWxwLXRlc3QwWTATBgcqhkjOPQIBBggqhkjO
+PQMBBwNCAAQaMnNdR0JUMUuZkAZIregqwFkDHFuP4PnSRZxyLi3kSuzYHS4hjM/6
+a81uCsOIEtO4lNODQt7KA3bnCdR1VK8xo1MwUTAdBgNVHQ4EFgQUCUEWnxQZBWG4
+2P6FTLHunrur4aIwHwYDVR0jBBgwFoAUCUEWnxQZBWG42P6FTLHunrur4aIwDwYD
+VR0TAQH/BAUwAwEB/zAKBggqhkjOPQQDAgNIADBFAiBwyQblKIWF2t8n1OVqTiDJ
+WWTXBmYiTATAy+mDQBzaqgIhAKkl/LZyz3uE55Vh1BKNk00dzWkuD5WqyPawHgII
+vdyo
+-----END CERTIFICATE-----
+";
@@ -0,0 +1,32 @@
+use anyhow::{Context, Result};
+use log::debug;
+
+/// How long to keep serving the copied text on Linux, where the clipboard is only
+/// held by the process that set it, so a clipboard manager can take it over
+#[cfg(target_os = "linux")]
+const LINUX_HOLD: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Copy text to the system clipboard
+///
+/// Fails when there is no clipboard to copy to, such as over SSH without a
+/// graphical session, so callers can fall back to printing the text.
+pub fn copy_to_clipboard(text: &str) -> Result<()> {
+    debug!("Copying to the clipboard");
+    let mut clipboard = arboard::Clipboard::new().context("no clipboard available")?;
+
+    #[cfg(target_os = "linux")]
+    {
+        use arboard::SetExtLinux;
+        clipboard
+            .set()
+            .wait_until(std::time::Instant::now() + LINUX_HOLD)
+            .text(text)
+            .context("Failed to copy to the clipboard")
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        clipboard
+            .set_text(text)
+            .context("Failed to copy to the clipboard")
+    }
+}
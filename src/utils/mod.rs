@@ -1,5 +1,12 @@
+mod prompt;
+pub use prompt::confirm;
+
 // Feature-dependent modules
+#[cfg(feature = "clipboard")]
+mod clipboard;
+#[cfg(feature = "clipboard")]
+pub use clipboard::copy_to_clipboard;
 #[cfg(feature = "self_update")]
 mod update;
 #[cfg(feature = "self_update")]
-pub use update::{check_for_updates, update};
+pub use update::{UpdateError, check_for_updates, update};
@@ -0,0 +1,20 @@
+use anyhow::{Context, Result};
+use dialoguer::{Confirm, theme::ColorfulTheme};
+
+use crate::commands::output::ensure_can_prompt;
+
+/// Ask a yes/no question, or answer yes without asking when assume_yes is set
+///
+/// Fails instead of prompting when the session isn't interactive.
+pub fn confirm(prompt: &str, default: bool, assume_yes: bool) -> Result<bool> {
+    if assume_yes {
+        return Ok(true);
+    }
+
+    ensure_can_prompt()?;
+    Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .default(default)
+        .interact()
+        .context("Failed to get confirmation")
+}
@@ -1,21 +1,122 @@
+use console::style;
 use log::info;
 use self_update::cargo_crate_version;
+use self_update::update::ReleaseUpdate;
+use std::fmt;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
 
-/// Checks if a newer version is available without updating
-pub fn check_for_updates() -> Result<bool, String> {
-    info!("Checking for updates...");
+/// How many times a GitHub request is tried before giving up
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry; doubled after each failed attempt
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Why checking for or applying an update didn't finish
+#[derive(Debug, PartialEq)]
+pub enum UpdateError {
+    /// GitHub didn't answer within the timeout
+    TimedOut(Duration),
+    /// The request or the update itself failed
+    Failed(String),
+}
+
+impl fmt::Display for UpdateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UpdateError::TimedOut(timeout) => {
+                write!(f, "no response from GitHub within {}s", timeout.as_secs())
+            }
+            UpdateError::Failed(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// Run a fallible network operation, retrying with exponential backoff
+///
+/// A timeout isn't retried, so the wait stays bounded by the timeout.
+fn with_retry<T>(
+    what: &str,
+    initial_backoff: Duration,
+    mut operation: impl FnMut() -> Result<T, UpdateError>,
+) -> Result<T, UpdateError> {
+    let mut backoff = initial_backoff;
+    let mut attempt = 1;
+    loop {
+        match operation() {
+            Ok(value) => return Ok(value),
+            Err(UpdateError::Failed(e)) if attempt < MAX_ATTEMPTS => {
+                eprintln!(
+                    "{} {} failed (attempt {}/{}): {}; retrying in {}s",
+                    style("Warning:").yellow().bold(),
+                    what,
+                    attempt,
+                    MAX_ATTEMPTS,
+                    e,
+                    backoff.as_secs_f32()
+                );
+                thread::sleep(backoff);
+                backoff *= 2;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Run a blocking operation on its own thread, giving up on it after timeout
+///
+/// self_update doesn't expose its HTTP client's timeouts, so a stalled request is
+/// abandoned rather than cancelled.
+fn with_timeout<T: Send + 'static>(
+    timeout: Duration,
+    operation: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, UpdateError> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        // The receiver is gone if the operation took too long
+        let _ = sender.send(operation());
+    });
+
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => result.map_err(UpdateError::Failed),
+        Err(RecvTimeoutError::Timeout) => Err(UpdateError::TimedOut(timeout)),
+        Err(RecvTimeoutError::Disconnected) => Err(UpdateError::Failed(
+            "Update thread stopped unexpectedly".to_string(),
+        )),
+    }
+}
 
-    let updater = self_update::backends::github::Update::configure()
+/// Configures the updater for khelp's GitHub releases
+fn updater() -> Result<Box<dyn ReleaseUpdate>, String> {
+    self_update::backends::github::Update::configure()
         .repo_owner("stvnksslr")
         .repo_name("khelp")
         .bin_name("khelp")
         .current_version(cargo_crate_version!())
         .build()
-        .map_err(|e| format!("Failed to build updater: {}", e))?;
+        .map_err(|e| format!("Failed to build updater: {}", e))
+}
+
+/// Fetches the latest release from GitHub, giving up on each attempt after timeout
+fn latest_release(timeout: Duration) -> Result<self_update::update::Release, UpdateError> {
+    with_retry("Update check", INITIAL_BACKOFF, || {
+        with_timeout(timeout, || {
+            updater()?
+                .get_latest_release()
+                .map_err(|e| format!("Failed to check for updates: {}", e))
+        })
+    })
+}
 
-    let latest_release = updater
-        .get_latest_release()
-        .map_err(|e| format!("Failed to check for updates: {}", e))?;
+/// Checks if a newer version is available without updating
+///
+/// Each request to GitHub gives up after timeout.
+pub fn check_for_updates(timeout: Duration) -> Result<bool, UpdateError> {
+    info!("Checking for updates...");
+
+    let latest_release = latest_release(timeout)?;
 
     // Compare versions
     let current_version = cargo_crate_version!();
@@ -34,18 +135,25 @@ pub fn check_for_updates() -> Result<bool, String> {
 }
 
 /// Downloads and applies the update
-pub fn update() -> Result<(), String> {
-    info!("Updating to the latest version...");
+///
+/// Only the check for a newer release gives up after timeout. The download and the
+/// replacement of the binary run to completion on this thread, since abandoning them
+/// midway could leave khelp half replaced.
+pub fn update(timeout: Duration) -> Result<(), UpdateError> {
+    if !check_for_updates(timeout)? {
+        return Ok(());
+    }
 
-    let status = self_update::backends::github::Update::configure()
-        .repo_owner("stvnksslr")
-        .repo_name("khelp")
-        .bin_name("khelp")
-        .current_version(cargo_crate_version!())
-        .build()
-        .map_err(|e| format!("Failed to build updater: {}", e))?
-        .update()
-        .map_err(|e| format!("Failed to update binary: {}", e))?;
+    info!("Updating to the latest version...");
+    let status = with_retry("Update download", INITIAL_BACKOFF, || {
+        updater()
+            .and_then(|updater| {
+                updater
+                    .update()
+                    .map_err(|e| format!("Failed to update binary: {}", e))
+            })
+            .map_err(UpdateError::Failed)
+    })?;
 
     match status.updated() {
         true => {
@@ -61,3 +169,50 @@ pub fn update() -> Result<(), String> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_retry_stops_after_max_attempts() {
+        let mut calls = 0;
+        let result: Result<(), UpdateError> = with_retry("Test", Duration::ZERO, || {
+            calls += 1;
+            Err(UpdateError::Failed(format!("failure {}", calls)))
+        });
+        assert_eq!(
+            result,
+            Err(UpdateError::Failed(format!("failure {}", MAX_ATTEMPTS)))
+        );
+        assert_eq!(calls, MAX_ATTEMPTS);
+
+        let mut calls = 0;
+        let result = with_retry("Test", Duration::ZERO, || {
+            calls += 1;
+            if calls < 2 {
+                Err(UpdateError::Failed("flaky".to_string()))
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(result, Ok(2));
+    }
+
+    #[test]
+    fn test_timeouts_are_reported_and_not_retried() {
+        let timeout = Duration::from_millis(10);
+        let mut calls = 0;
+        let result: Result<(), UpdateError> = with_retry("Test", Duration::ZERO, || {
+            calls += 1;
+            with_timeout(timeout, || {
+                thread::sleep(Duration::from_secs(1));
+                Ok(())
+            })
+        });
+        assert_eq!(result, Err(UpdateError::TimedOut(timeout)));
+        assert_eq!(calls, 1);
+
+        assert_eq!(with_timeout(Duration::from_secs(5), || Ok(7)), Ok(7));
+    }
+}
@@ -430,3 +430,96 @@ fn test_save_and_load_default_config() {
     assert!(loaded.contexts.is_empty());
     assert!(loaded.users.is_empty());
 }
+
+#[test]
+fn test_add_rejects_empty_context_name() {
+    let test_config = common::TestKubeConfig::new();
+    let external_path = test_config.temp_dir.path().join("unnamed.yaml");
+    std::fs::write(
+        &external_path,
+        r#"apiVersion: v1
+kind: Config
+current-context: ""
+clusters:
+- name: unnamed-cluster
+  cluster:
+    server: https://unnamed.example.com:6443
+contexts:
+- name: ""
+  context:
+    cluster: unnamed-cluster
+    user: unnamed-user
+users:
+- name: unnamed-user
+  user:
+    token: external-token
+"#,
+    )
+    .expect("Failed to write external config");
+    let before = std::fs::read_to_string(test_config.path()).expect("Failed to read config");
+
+    let output = test_config
+        .khelp()
+        .args(["add", "--yes"])
+        .arg(&external_path)
+        .output()
+        .expect("Failed to run add");
+
+    assert!(!output.status.success(), "add should fail: {:?}", output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("context #1 has an empty name"),
+        "Error should name the empty field: {}",
+        stderr
+    );
+    let after = std::fs::read_to_string(test_config.path()).expect("Failed to read config");
+    assert_eq!(before, after, "Nothing should be written");
+}
+
+#[test]
+fn test_add_prune_removes_entries_dropped_from_source() {
+    let test_config = common::TestKubeConfig::new();
+    let add = |upstream: std::path::PathBuf, extra: &[&str]| {
+        let output = test_config
+            .khelp()
+            .args(["add", "--yes"])
+            .args(extra)
+            .arg(upstream)
+            .output()
+            .expect("Failed to run add");
+        assert!(output.status.success(), "add failed: {:?}", output);
+    };
+
+    add(
+        test_config.write_combined_external(&["team-a", "team-b"]),
+        &[],
+    );
+    add(
+        test_config.write_combined_external(&["team-a"]),
+        &["--prune"],
+    );
+
+    let config = load_kube_config_from(test_config.path()).expect("Failed to reload config");
+    let names: Vec<&str> = config.contexts.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, vec!["test-context", "second-context", "team-a"]);
+    assert!(!config.clusters.iter().any(|c| c.name == "team-b-cluster"));
+    assert!(!config.users.iter().any(|u| u.name == "team-b-user"));
+}
+
+#[test]
+fn test_add_adopt_current_uses_source_current_context() {
+    let test_config = common::TestKubeConfig::new();
+    let upstream_path = test_config.write_combined_external(&["team-a", "team-b"]);
+
+    let output = test_config
+        .khelp()
+        .args(["add", "--yes", "--switch", "--adopt-current"])
+        .arg(&upstream_path)
+        .output()
+        .expect("Failed to run add");
+    assert!(output.status.success(), "add failed: {:?}", output);
+
+    // --switch alone would pick team-a, the first context added
+    let config = load_kube_config_from(test_config.path()).expect("Failed to reload config");
+    assert_eq!(config.current_context, "team-b");
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+
+/// Serves a single `401 Unauthorized` answer on a local port, returning its address
+fn answer_once_with_401() -> std::net::SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind");
+    let address = listener.local_addr().expect("No address");
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().expect("Failed to accept");
+        let _ = stream.read(&mut [0; 1024]);
+        let _ = stream.write_all(b"HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\n\r\n");
+    });
+    address
+}
+
+#[test]
+fn test_check_probes_without_credentials_when_token_file_is_missing() {
+    let address = answer_once_with_401();
+    let test_config = common::TestKubeConfig::with_content(&format!(
+        r#"apiVersion: v1
+kind: Config
+current-context: dev
+clusters:
+- name: dev
+  cluster:
+    server: http://{}
+contexts:
+- name: dev
+  context:
+    cluster: dev
+    user: dev
+users:
+- name: dev
+  user:
+    tokenFile: /nonexistent/khelp-token
+"#,
+        address
+    ));
+
+    let output = test_config
+        .khelp()
+        .arg("check")
+        .output()
+        .expect("Failed to run check");
+    assert!(output.status.success(), "check failed: {:?}", output);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Warning:"));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("responded with HTTP 401"));
+}
@@ -1,5 +1,7 @@
+use khelp::config::kubernetes::KubeConfig;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::process::Command;
 use tempfile::TempDir;
 
 /// Test fixture that provides an isolated kubeconfig environment
@@ -100,6 +102,7 @@ users:
     }
 
     /// Creates a test fixture with multiple contexts
+    #[allow(dead_code)]
     pub fn with_contexts(context_names: &[&str]) -> Self {
         if context_names.is_empty() {
             panic!("At least one context name must be provided");
@@ -152,10 +155,29 @@ users:
     }
 
     /// Returns the path to the kubeconfig file
+    #[allow(dead_code)]
     pub fn path(&self) -> &Path {
         &self.config_path
     }
 
+    /// Builds a command running the khelp binary against this kubeconfig
+    ///
+    /// HOME points into the fixture so the user's own settings, backups, and usage
+    /// log aren't touched.
+    #[allow(dead_code)]
+    pub fn khelp(&self) -> Command {
+        let home = self.temp_dir.path();
+        let mut command = Command::new(env!("CARGO_BIN_EXE_khelp"));
+        command
+            .env("HOME", home)
+            .env("XDG_CONFIG_HOME", home.join(".config"))
+            .env_remove("KUBECONFIG")
+            .env_remove("KHELP_KUBECONFIG")
+            .arg("--kubeconfig")
+            .arg(&self.config_path);
+        command
+    }
+
     /// Creates an external kubeconfig file for testing imports
     #[allow(dead_code)]
     pub fn create_external_config(&self, name: &str) -> PathBuf {
@@ -185,6 +207,34 @@ users:
         fs::write(&external_path, content).expect("Failed to write external config");
         external_path
     }
+
+    /// Writes one external kubeconfig holding the entries of an external config per name
+    ///
+    /// Its current-context is the last name, so it differs from the first context
+    /// an import adds. Writing again replaces the file.
+    #[allow(dead_code)]
+    pub fn write_combined_external(&self, names: &[&str]) -> PathBuf {
+        let mut combined = KubeConfig::default();
+        for name in names {
+            let external: KubeConfig = serde_yaml::from_str(
+                &fs::read_to_string(self.create_external_config(name))
+                    .expect("Failed to read external config"),
+            )
+            .expect("Failed to parse external config");
+            combined.clusters.extend(external.clusters);
+            combined.contexts.extend(external.contexts);
+            combined.users.extend(external.users);
+            combined.current_context = external.current_context;
+        }
+
+        let combined_path = self.temp_dir.path().join("combined.yaml");
+        fs::write(
+            &combined_path,
+            serde_yaml::to_string(&combined).expect("Failed to serialize combined config"),
+        )
+        .expect("Failed to write combined config");
+        combined_path
+    }
 }
 
 impl Default for TestKubeConfig {
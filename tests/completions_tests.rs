@@ -0,0 +1,36 @@
+mod common;
+
+#[test]
+fn test_completions_output_creates_parent_dirs_and_matches_stdout() {
+    let test_config = common::TestKubeConfig::new();
+    let path = test_config.temp_dir.path().join("nested/dir/khelp.bash");
+
+    let printed = test_config
+        .khelp()
+        .args(["completions", "bash"])
+        .output()
+        .expect("Failed to run completions");
+    assert!(
+        printed.status.success(),
+        "completions failed: {:?}",
+        printed
+    );
+
+    let written = test_config
+        .khelp()
+        .args(["completions", "bash", "--output"])
+        .arg(&path)
+        .output()
+        .expect("Failed to run completions --output");
+    assert!(
+        written.status.success(),
+        "completions failed: {:?}",
+        written
+    );
+    assert!(written.stdout.is_empty());
+
+    assert_eq!(
+        std::fs::read(&path).expect("Failed to read script"),
+        printed.stdout
+    );
+}
@@ -148,3 +148,77 @@ fn test_isolation_multiple_test_instances() {
         "Test configs should use different paths"
     );
 }
+
+#[test]
+fn test_current_warns_about_missing_current_context() {
+    let test_config = common::TestKubeConfig::with_content(
+        r#"apiVersion: v1
+clusters:
+- cluster:
+    server: https://127.0.0.1:6443
+  name: test-cluster
+contexts:
+- context:
+    cluster: test-cluster
+    user: test-user
+  name: test-context
+current-context: deleted-context
+kind: Config
+users:
+- name: test-user
+  user:
+    token: test-token
+"#,
+    );
+
+    let output = test_config
+        .khelp()
+        .arg("current")
+        .output()
+        .expect("Failed to run current");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("current-context 'deleted-context' is set but not found in contexts"),
+        "current should explain the missing context: {}",
+        stderr
+    );
+    assert!(stderr.contains("khelp reset-current"));
+}
+
+#[test]
+fn test_broken_settings_only_stop_commands_that_use_them() {
+    let test_config = common::TestKubeConfig::new();
+    let settings_dir = test_config.temp_dir.path().join(".config/khelp");
+    std::fs::create_dir_all(&settings_dir).expect("Failed to create settings dir");
+    std::fs::write(settings_dir.join("config.toml"), "backup = [").expect("Failed to write");
+
+    let prompt = test_config
+        .khelp()
+        .arg("prompt")
+        .output()
+        .expect("Failed to run prompt");
+    assert!(prompt.status.success(), "prompt failed: {:?}", prompt);
+    assert!(String::from_utf8_lossy(&prompt.stdout).contains("test-context"));
+    assert!(String::from_utf8_lossy(&prompt.stderr).contains("using default settings"));
+
+    let list = test_config
+        .khelp()
+        .arg("list")
+        .output()
+        .expect("Failed to run list");
+    assert!(!list.status.success());
+
+    // Saving would silently skip the backup the settings may ask for
+    let before = std::fs::read_to_string(test_config.path()).expect("Failed to read config");
+    let rename = test_config
+        .khelp()
+        .args(["rename", "test-context", "renamed-context"])
+        .output()
+        .expect("Failed to run rename");
+    assert!(!rename.status.success());
+    assert!(String::from_utf8_lossy(&rename.stderr).contains("needs khelp's settings"));
+    assert_eq!(
+        std::fs::read_to_string(test_config.path()).expect("Failed to read config"),
+        before
+    );
+}
@@ -213,3 +213,120 @@ fn test_round_trip_maintains_context_details() {
         original_context.context.namespace
     );
 }
+
+#[cfg(unix)]
+#[test]
+fn test_switch_preserves_symlinked_config() {
+    let test_config = common::TestKubeConfig::new();
+    let link_path = test_config.temp_dir.path().join("linked-config");
+    std::os::unix::fs::symlink(test_config.path(), &link_path).expect("Failed to create symlink");
+
+    let mut config = load_kube_config_from(&link_path).expect("Failed to load config");
+    config.current_context = "second-context".to_string();
+    save_kube_config_to(&config, &link_path).expect("Failed to save config");
+
+    // The link should be untouched and still point at the original file
+    let link_metadata = std::fs::symlink_metadata(&link_path).expect("Failed to stat link");
+    assert!(link_metadata.file_type().is_symlink());
+    assert_eq!(
+        std::fs::read_link(&link_path).expect("Failed to read link"),
+        test_config.path()
+    );
+
+    // The real file behind the link should carry the change
+    let reloaded_config =
+        load_kube_config_from(test_config.path()).expect("Failed to reload config");
+    assert_eq!(reloaded_config.current_context, "second-context");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_switch_keeps_owner_only_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let test_config = common::TestKubeConfig::new();
+    std::fs::set_permissions(test_config.path(), std::fs::Permissions::from_mode(0o600))
+        .expect("Failed to set permissions");
+
+    let output = test_config
+        .khelp()
+        .args(["switch", "second-context"])
+        .output()
+        .expect("Failed to run switch");
+    assert!(output.status.success(), "switch failed: {:?}", output);
+
+    let config = load_kube_config_from(test_config.path()).expect("Failed to reload config");
+    assert_eq!(config.current_context, "second-context");
+    let mode = std::fs::metadata(test_config.path())
+        .expect("Failed to stat config")
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o600);
+}
+
+#[test]
+fn test_export_piped_into_add() {
+    use std::process::Stdio;
+
+    let source = common::TestKubeConfig::new();
+    let target = common::TestKubeConfig::with_single_context("local");
+
+    let export = source
+        .khelp()
+        .args(["export", "second-context"])
+        .output()
+        .expect("Failed to run export");
+    assert!(export.status.success(), "export failed: {:?}", export);
+
+    // stdin isn't a terminal, so add must not stop to ask for confirmation
+    let mut add = target
+        .khelp()
+        .args(["add", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run add");
+    {
+        use std::io::Write;
+        let mut stdin = add.stdin.take().expect("stdin is piped");
+        stdin
+            .write_all(&export.stdout)
+            .expect("Failed to pipe export into add");
+    }
+    let add = add.wait_with_output().expect("Failed to wait for add");
+    assert!(add.status.success(), "add failed: {:?}", add);
+
+    let config = load_kube_config_from(target.path()).expect("Failed to load target config");
+    let context = config
+        .contexts
+        .iter()
+        .find(|c| c.name == "second-context")
+        .expect("Piped context should have been added");
+    assert!(
+        config
+            .clusters
+            .iter()
+            .any(|c| c.name == context.context.cluster)
+    );
+    assert!(config.users.iter().any(|u| u.name == context.context.user));
+    assert!(config.contexts.iter().any(|c| c.name == "local"));
+}
+
+#[test]
+fn test_switch_to_current_context_exits_non_zero() {
+    let test_config = common::TestKubeConfig::new();
+    let switch = |name: &str| {
+        test_config
+            .khelp()
+            .args(["switch", name])
+            .output()
+            .expect("Failed to run switch")
+    };
+
+    let output = switch("test-context");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Already on context"));
+
+    assert!(switch("second-context").status.success());
+}